@@ -14,16 +14,23 @@ DELETE FROM journal_entries;
 DELETE FROM books;
 DELETE FROM tags;
 DELETE FROM genres;
+DELETE FROM library_members;
 DELETE FROM users;
 
 -- Reset autoincrement counters
 DELETE FROM sqlite_sequence WHERE name IN ('users', 'books', 'tags', 'genres', 'journal_entries', 'book_tags', 'book_genres', 'journal_entry_tags', 'ratings', 'reading_status', 'lists', 'list_books');
 
 -- Insert test users
-INSERT INTO users (id, name, color, avatar_image, created_at, updated_at, last_login) VALUES
-    (1, 'Test', '#3B82F6', NULL, datetime('now', '-30 days'), datetime('now', '-30 days'), datetime('now', '-1 day')),
-    (2, 'Alice', '#EF4444', NULL, datetime('now', '-25 days'), datetime('now', '-25 days'), datetime('now', '-2 days')),
-    (3, 'Bob', '#10B981', NULL, datetime('now', '-20 days'), datetime('now', '-20 days'), datetime('now', '-3 days'));
+INSERT INTO users (id, name, color, avatar_image, role, created_at, updated_at, last_login) VALUES
+    (1, 'Test', '#3B82F6', NULL, 'admin', datetime('now', '-30 days'), datetime('now', '-30 days'), datetime('now', '-1 day')),
+    (2, 'Alice', '#EF4444', NULL, 'admin', datetime('now', '-25 days'), datetime('now', '-25 days'), datetime('now', '-2 days')),
+    (3, 'Bob', '#10B981', NULL, 'admin', datetime('now', '-20 days'), datetime('now', '-20 days'), datetime('now', '-3 days'));
+
+-- Everyone shares the default library
+INSERT OR IGNORE INTO library_members (library_id, user_id) VALUES
+    (1, 1),
+    (1, 2),
+    (1, 3);
 
 -- Insert test genres
 INSERT INTO genres (id, name, color, user_id, created_at, updated_at) VALUES
@@ -0,0 +1,2 @@
+-- Add a page count to books so reading stats (total pages, longest book) can be reported
+ALTER TABLE books ADD COLUMN page_count INTEGER DEFAULT NULL;
@@ -0,0 +1,18 @@
+-- Make reading statuses a managed resource instead of a fixed lookup table
+ALTER TABLE status ADD COLUMN color TEXT NOT NULL DEFAULT '#6b7280';
+ALTER TABLE status ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+-- Books with an "is finished" status count as read (stats, reports)
+ALTER TABLE status ADD COLUMN is_finished INTEGER NOT NULL DEFAULT 0;
+-- Books with an "is active" status are currently being read
+ALTER TABLE status ADD COLUMN is_active INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE status ADD COLUMN created_at TEXT DEFAULT NULL;
+ALTER TABLE status ADD COLUMN updated_at TEXT DEFAULT NULL;
+
+UPDATE status SET created_at = datetime('now'), updated_at = datetime('now');
+
+-- Give the existing statuses their semantics, colors and display order
+UPDATE status SET color = '#6b7280', position = 0 WHERE id = 0; -- UNREAD
+UPDATE status SET color = '#3B82F6', position = 1, is_active = 1 WHERE id = 2; -- READING
+UPDATE status SET color = '#F59E0B', position = 2 WHERE id = 3; -- TBR
+UPDATE status SET color = '#10B981', position = 3, is_finished = 1 WHERE id = 1; -- READ
+UPDATE status SET color = '#EF4444', position = 4 WHERE id = 99; -- DNF
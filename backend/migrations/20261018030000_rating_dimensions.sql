@@ -0,0 +1,28 @@
+-- Configurable rating dimensions (plot, characters, writing, ...), each with its own scale
+CREATE TABLE IF NOT EXISTS rating_dimensions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    min_value REAL NOT NULL DEFAULT 0,
+    max_value REAL NOT NULL DEFAULT 5,
+    -- Smallest step between allowed scores, e.g. 0.5 for half stars, 0.25 for quarter stars
+    increment REAL NOT NULL DEFAULT 0.5,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now'))
+);
+
+-- Per-dimension scores stored alongside a user's overall rating for a book
+CREATE TABLE IF NOT EXISTS rating_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rating_id INTEGER NOT NULL,
+    dimension_id INTEGER NOT NULL,
+    score REAL NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (rating_id) REFERENCES ratings (id) ON DELETE CASCADE,
+    FOREIGN KEY (dimension_id) REFERENCES rating_dimensions (id) ON DELETE CASCADE,
+    UNIQUE(rating_id, dimension_id) -- One score per dimension per rating
+);
+
+CREATE INDEX IF NOT EXISTS idx_rating_scores_rating_id ON rating_scores(rating_id);
+CREATE INDEX IF NOT EXISTS idx_rating_scores_dimension_id ON rating_scores(dimension_id);
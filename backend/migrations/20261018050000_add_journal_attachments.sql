@@ -0,0 +1,17 @@
+-- Files attached to journal entries. The bytes live on disk under the attachments directory,
+-- keyed by storage_key; journal content references them by their download URL.
+-- Images previously inlined into journal content as data URIs are moved here at startup.
+CREATE TABLE IF NOT EXISTS journal_attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    journal_entry_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (journal_entry_id) REFERENCES journal_entries (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_attachments_journal_entry_id ON journal_attachments(journal_entry_id);
@@ -0,0 +1,18 @@
+-- Favourite lines from books. A quote is placed by page number for print books
+-- or by a free-form location (e.g. an e-reader location range) when there are no pages.
+CREATE TABLE IF NOT EXISTS quotes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    book_id INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    page INTEGER,
+    location TEXT,
+    chapter TEXT,
+    note TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (book_id) REFERENCES books (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_quotes_book_id ON quotes(book_id);
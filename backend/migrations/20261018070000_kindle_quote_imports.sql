@@ -0,0 +1,8 @@
+-- Track where quotes came from so Kindle clippings can be imported more than once
+-- without duplicating anything. clipped_at is when the highlight was made on the device.
+ALTER TABLE quotes ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+ALTER TABLE quotes ADD COLUMN clipped_at TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quotes_kindle_clipping
+    ON quotes(user_id, book_id, location, text)
+    WHERE source = 'kindle';
@@ -0,0 +1,15 @@
+-- Earlier versions of journal entries. Every update (and every restore) first copies the
+-- entry's current title and content here, so an accidental save can be undone.
+CREATE TABLE IF NOT EXISTS journal_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    journal_entry_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    -- When this version was saved, i.e. the entry's updated_at at the time
+    saved_at TEXT,
+    -- When this version was replaced by a newer one
+    replaced_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (journal_entry_id) REFERENCES journal_entries (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_revisions_journal_entry_id ON journal_revisions(journal_entry_id);
@@ -0,0 +1,4 @@
+-- Who can read a journal entry: only its author ('private') or everyone in the household.
+-- Existing entries were visible to everyone, so they stay household-visible.
+ALTER TABLE journal_entries ADD COLUMN visibility TEXT NOT NULL DEFAULT 'household'
+    CHECK (visibility IN ('private', 'household'));
@@ -0,0 +1,31 @@
+-- Household members responding to each other's journal entries.
+-- A comment with a parent_id is a reply; deleting a comment deletes its replies.
+CREATE TABLE IF NOT EXISTS journal_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    journal_entry_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    parent_id INTEGER,
+    content TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (journal_entry_id) REFERENCES journal_entries (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES journal_comments (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_comments_journal_entry_id ON journal_comments(journal_entry_id);
+CREATE INDEX IF NOT EXISTS idx_journal_comments_parent_id ON journal_comments(parent_id);
+
+-- Each user can react to an entry with any number of different emoji, each once
+CREATE TABLE IF NOT EXISTS journal_reactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    journal_entry_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    emoji TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    UNIQUE (journal_entry_id, user_id, emoji),
+    FOREIGN KEY (journal_entry_id) REFERENCES journal_entries (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_reactions_journal_entry_id ON journal_reactions(journal_entry_id);
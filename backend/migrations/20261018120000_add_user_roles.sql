@@ -0,0 +1,9 @@
+-- "admin" manages users, tags and genres. "child" accounts don't see books with a
+-- restricted tag or genre and may only change their own data.
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'member' CHECK (role IN ('admin', 'member', 'child'));
+
+-- Everyone could manage everything before roles existed, so existing users start as admins
+UPDATE users SET role = 'admin';
+
+ALTER TABLE tags ADD COLUMN is_restricted INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE genres ADD COLUMN is_restricted INTEGER NOT NULL DEFAULT 0;
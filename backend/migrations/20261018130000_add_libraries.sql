@@ -0,0 +1,72 @@
+-- A library is one household's books, tags, genres and lists
+CREATE TABLE libraries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE TABLE library_members (
+    library_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    PRIMARY KEY (library_id, user_id),
+    FOREIGN KEY (library_id) REFERENCES libraries (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_library_members_user_id ON library_members(user_id);
+
+-- Everything so far belongs to the one household using the app
+INSERT INTO libraries (id, name) VALUES (1, 'Home');
+INSERT INTO library_members (library_id, user_id) SELECT 1, id FROM users;
+
+ALTER TABLE books ADD COLUMN library_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE tags ADD COLUMN library_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE lists ADD COLUMN library_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX idx_books_library_id ON books(library_id);
+CREATE INDEX idx_lists_library_id ON lists(library_id);
+
+DROP INDEX unique_tag_name;
+CREATE UNIQUE INDEX unique_tag_name ON tags (library_id, name);
+
+-- Genre names become unique per library, which means rebuilding the table. book_genres
+-- is rebuilt with it so that nothing references the old table when it's dropped.
+CREATE TABLE genres_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    color TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    library_id INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    is_restricted INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (library_id) REFERENCES libraries (id),
+    UNIQUE (library_id, name)
+);
+
+INSERT INTO genres_new (id, name, color, user_id, created_at, updated_at, is_restricted)
+SELECT id, name, color, user_id, created_at, updated_at, is_restricted FROM genres;
+
+CREATE TABLE book_genres_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    book_id INTEGER NOT NULL,
+    genre_id INTEGER NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    updated_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (book_id) REFERENCES books (id),
+    FOREIGN KEY (genre_id) REFERENCES genres_new (id),
+    UNIQUE(book_id, genre_id)
+);
+
+INSERT INTO book_genres_new (id, book_id, genre_id, created_at, updated_at)
+SELECT id, book_id, genre_id, created_at, updated_at FROM book_genres;
+
+DROP TABLE book_genres;
+DROP TABLE genres;
+ALTER TABLE genres_new RENAME TO genres;
+ALTER TABLE book_genres_new RENAME TO book_genres;
+
+CREATE INDEX idx_book_genres_book_id ON book_genres(book_id);
+CREATE INDEX idx_book_genres_genre_id ON book_genres(genre_id);
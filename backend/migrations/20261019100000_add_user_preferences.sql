@@ -0,0 +1,10 @@
+-- Per-user settings, one row per key. Values are JSON; keys that have no row use the
+-- server's default.
+CREATE TABLE user_preferences (
+    user_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TEXT DEFAULT (datetime('now')),
+    PRIMARY KEY (user_id, key),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
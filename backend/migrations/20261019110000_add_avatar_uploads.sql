@@ -0,0 +1,5 @@
+-- Uploaded avatars are stored in upload storage at a few fixed sizes. `avatar_key` is the
+-- common prefix of their storage keys, and `avatar_image` holds the URL they're served
+-- from instead of the image itself. Existing base64 avatars are moved into storage when
+-- the server starts.
+ALTER TABLE users ADD COLUMN avatar_key TEXT;
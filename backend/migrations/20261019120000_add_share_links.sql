@@ -0,0 +1,22 @@
+-- Read-only links to a list or a book for people without an account. The token is kept
+-- in the clear so owners can copy the link again; revoking or expiring it stops it working.
+CREATE TABLE IF NOT EXISTS share_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    library_id INTEGER NOT NULL,
+    list_id INTEGER,
+    book_id INTEGER,
+    open_count INTEGER NOT NULL DEFAULT 0,
+    last_opened_at TEXT,
+    expires_at TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    revoked_at TEXT,
+    CHECK ((list_id IS NULL) != (book_id IS NULL)),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (library_id) REFERENCES libraries (id) ON DELETE CASCADE,
+    FOREIGN KEY (list_id) REFERENCES lists (id) ON DELETE CASCADE,
+    FOREIGN KEY (book_id) REFERENCES books (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_links_user_id ON share_links(user_id);
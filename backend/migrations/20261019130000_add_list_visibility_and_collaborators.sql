@@ -0,0 +1,23 @@
+-- Who can see a list: its owner and collaborators ('private') or everyone in the library.
+-- Every list was shown to everyone before, so existing lists stay household-visible.
+ALTER TABLE lists ADD COLUMN visibility TEXT NOT NULL DEFAULT 'household'
+    CHECK (visibility IN ('private', 'household'));
+
+-- Users other than the owner who can edit a list's name, type and books
+CREATE TABLE IF NOT EXISTS list_collaborators (
+    list_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    PRIMARY KEY (list_id, user_id),
+    FOREIGN KEY (list_id) REFERENCES lists (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_list_collaborators_user_id ON list_collaborators(user_id);
+
+-- Who put each book on a list. Only owners could edit lists until now, so existing
+-- books were added by the list's owner.
+ALTER TABLE list_books ADD COLUMN added_by INTEGER REFERENCES users (id) ON DELETE SET NULL;
+
+UPDATE list_books
+SET added_by = (SELECT lists.user_id FROM lists WHERE lists.id = list_books.list_id);
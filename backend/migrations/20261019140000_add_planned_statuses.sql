@@ -0,0 +1,5 @@
+-- Books with an "is planned" status are on the to-be-read pile, which the random
+-- book picker draws from unless asked for another status
+ALTER TABLE status ADD COLUMN is_planned INTEGER NOT NULL DEFAULT 0;
+
+UPDATE status SET is_planned = 1 WHERE id = 3; -- TBR
@@ -0,0 +1,6 @@
+-- When a book was finished, so moving it between finished statuses or touching the
+-- row later doesn't change the year it counts towards
+ALTER TABLE reading_status ADD COLUMN finished_at TEXT DEFAULT NULL;
+
+UPDATE reading_status SET finished_at = updated_at
+WHERE status_id IN (SELECT id FROM status WHERE is_finished = 1);
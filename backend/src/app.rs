@@ -1,7 +1,8 @@
 use axum::{
     Router,
-    extract::DefaultBodyLimit,
+    extract::{DefaultBodyLimit, Extension},
     http::StatusCode,
+    middleware,
     response::Json,
     routing::{delete, get, post, put},
 };
@@ -10,6 +11,13 @@ use sqlx::{Pool, Sqlite};
 use tracing::debug;
 
 use crate::routes::*;
+use crate::utils::{FileStorage, api_token_auth, library_scope};
+
+// Request body limits. Only the upload routes accept more than plain JSON needs.
+const BODY_LIMIT: usize = 1024 * 1024;
+// A 5MB image plus multipart overhead
+const IMAGE_UPLOAD_LIMIT: usize = 6 * 1024 * 1024;
+const IMPORT_UPLOAD_LIMIT: usize = 10 * 1024 * 1024;
 
 #[derive(Serialize)]
 struct HealthResponse {
@@ -26,30 +34,149 @@ async fn health_check() -> Result<Json<HealthResponse>, StatusCode> {
 
 pub async fn app(pool: Pool<Sqlite>) -> Router {
     debug!("Creating router with routes");
+    let storage = FileStorage::for_pool(&pool)
+        .await
+        .expect("Failed to locate uploads directory");
     Router::new()
         .route("/health", get(health_check))
-        .route("/users", get(get_users))
+        .route("/users", get(get_users).post(create_user))
         .route("/users/select", post(select_user))
+        .route("/users/compatibility", get(get_compatibility_matrix))
         .route("/users/{id}", put(update_user))
+        .route(
+            "/users/{id}/avatar",
+            get(get_user_avatar)
+                .post(upload_user_avatar)
+                .delete(delete_user_avatar)
+                .layer(DefaultBodyLimit::max(IMAGE_UPLOAD_LIMIT)),
+        )
+        .route(
+            "/users/{id}/preferences",
+            get(get_user_preferences).put(update_user_preferences),
+        )
+        .route("/api-tokens", get(get_api_tokens))
+        .route("/api-tokens", post(create_api_token))
+        .route("/api-tokens/{id}", delete(revoke_api_token))
+        .route("/share-links", get(get_share_links).post(create_share_link))
+        .route("/share-links/{id}", delete(revoke_share_link))
+        .route("/shared/{token}", get(get_shared))
+        .route("/libraries", get(get_libraries).post(create_library))
+        .route("/libraries/{id}/members", post(add_library_member))
+        .route(
+            "/libraries/{id}/members/{user_id}",
+            delete(remove_library_member),
+        )
+        .route("/users/{id}/recommendations", get(get_recommendations))
+        .route("/users/{id}/journals/export", get(export_user_journals))
+        .route(
+            "/users/{id}/compatibility/{other_id}",
+            get(get_taste_compatibility),
+        )
+        .route("/users/{id}/year-in-review/{year}", get(get_year_in_review))
         .route("/books", get(get_books))
         .route("/books", post(create_book))
+        .route("/books/random", get(get_random_book))
+        .route("/books/export/obsidian", get(export_obsidian_vault))
+        .route(
+            "/books/import/obsidian",
+            post(import_obsidian_vault).layer(DefaultBodyLimit::max(IMPORT_UPLOAD_LIMIT)),
+        )
         .route("/books/{id}", get(get_book_details))
         .route("/books/{id}", put(update_book))
         .route("/books/{id}", delete(delete_book))
         .route("/books/{id}/journals", get(get_book_journals))
         .route("/books/{id}/journals", post(create_book_journal_entry))
+        .route("/books/{id}/journals/export", get(export_book_journals))
         .route(
             "/books/{book_id}/journals/{journal_id}",
             put(update_journal_entry),
         )
+        .route(
+            "/books/{book_id}/journals/{journal_id}",
+            delete(delete_journal_entry),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/revisions",
+            get(get_journal_revisions),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/revisions/diff",
+            get(get_journal_revision_diff),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/revisions/{revision_id}",
+            get(get_journal_revision),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/revisions/{revision_id}/restore",
+            post(restore_journal_revision),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/attachments",
+            get(get_journal_attachments),
+        )
+        .route(
+            "/books/{book_id}/journals/{journal_id}/attachments",
+            post(upload_journal_attachment).layer(DefaultBodyLimit::max(IMAGE_UPLOAD_LIMIT)),
+        )
+        .route("/attachments/{file}", get(download_journal_attachment))
+        .route(
+            "/books/{book_id}/journals/{journal_id}/attachments/{attachment_id}",
+            delete(delete_journal_attachment),
+        )
+        .route("/books/{id}/quotes", get(get_book_quotes))
+        .route("/books/{id}/quotes", post(create_book_quote))
+        .route("/books/{book_id}/quotes/{quote_id}", put(update_book_quote))
+        .route(
+            "/books/{book_id}/quotes/{quote_id}",
+            delete(delete_book_quote),
+        )
         .route("/books/{id}/ratings", post(upsert_rating))
         .route("/books/{id}/ratings", get(get_user_rating))
         .route("/books/{id}/ratings", delete(delete_rating))
+        .route("/books/{id}/reviews", get(get_book_reviews))
+        .route("/books/{id}/reviews", post(create_book_review))
+        .route(
+            "/books/{book_id}/reviews/{review_id}",
+            put(update_book_review),
+        )
+        .route(
+            "/books/{book_id}/reviews/{review_id}",
+            delete(delete_book_review),
+        )
         .route("/books/{id}/status", post(upsert_status))
         .route("/books/{id}/status", get(get_user_status))
         .route("/books/{id}/status", delete(delete_status))
         .route("/journals", get(get_journal_entries_query))
         .route("/journals/{id}", get(get_journal_entry_by_id_query))
+        .route("/journals/{id}/comments", get(get_journal_comments))
+        .route("/journals/{id}/comments", post(create_journal_comment))
+        .route(
+            "/journals/{journal_id}/comments/{comment_id}",
+            delete(delete_journal_comment),
+        )
+        .route("/journals/{id}/reactions", get(get_journal_reactions))
+        .route("/journals/{id}/reactions", post(add_journal_reaction))
+        .route(
+            "/journals/{journal_id}/reactions/{emoji}",
+            delete(remove_journal_reaction),
+        )
+        .route("/quotes", get(get_quotes))
+        .route("/quotes/random", get(get_random_quote))
+        .route(
+            "/quotes/import/kindle",
+            post(import_kindle_clippings).layer(DefaultBodyLimit::max(IMPORT_UPLOAD_LIMIT)),
+        )
+        .route("/rating-dimensions", get(get_rating_dimensions))
+        .route("/rating-dimensions", post(create_rating_dimension))
+        .route("/rating-dimensions/{id}", get(get_rating_dimension_by_id))
+        .route("/rating-dimensions/{id}", put(update_rating_dimension))
+        .route("/rating-dimensions/{id}", delete(delete_rating_dimension))
+        .route("/statuses", get(get_statuses))
+        .route("/statuses", post(create_status))
+        .route("/statuses/{id}", get(get_status_by_id))
+        .route("/statuses/{id}", put(update_status))
+        .route("/statuses/{id}", delete(delete_status_by_id))
         .route("/tags", get(get_tags))
         .route("/tags", post(create_tag))
         .route("/tags/{id}", get(get_tag_by_id))
@@ -65,6 +192,14 @@ pub async fn app(pool: Pool<Sqlite>) -> Router {
         .route("/lists/{id}", get(get_list))
         .route("/lists/{id}", put(update_list))
         .route("/lists/{id}", delete(delete_list))
-        .layer(DefaultBodyLimit::max(10 * 1024 * 1024)) // 10MB limit for images in journal entries
+        .route("/lists/{id}/collaborators", post(add_list_collaborator))
+        .route(
+            "/lists/{list_id}/collaborators/{user_id}",
+            delete(remove_list_collaborator),
+        )
+        .route_layer(middleware::from_fn_with_state(pool.clone(), library_scope))
+        .layer(middleware::from_fn_with_state(pool.clone(), api_token_auth))
+        .layer(Extension(storage))
+        .layer(DefaultBodyLimit::max(BODY_LIMIT))
         .with_state(pool)
 }
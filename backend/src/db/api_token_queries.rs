@@ -0,0 +1,98 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::models::api_tokens::ApiToken;
+
+pub async fn get_api_tokens_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Vec<ApiToken>, sqlx::Error> {
+    debug!("Querying database for API tokens of user {}", user_id);
+
+    sqlx::query_as!(
+        ApiToken,
+        r#"SELECT id as "id!", user_id, name, token_prefix, read_only as "read_only: bool",
+                  created_at, last_used_at, revoked_at
+           FROM api_tokens
+           WHERE user_id = ?
+           ORDER BY created_at DESC, id DESC"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Only the hash of the token is stored; the caller shows the token itself to the user once
+pub async fn create_api_token_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    name: &str,
+    read_only: bool,
+    token_hash: &str,
+    token_prefix: &str,
+) -> Result<ApiToken, sqlx::Error> {
+    debug!("Creating API token '{}' for user {}", name, user_id);
+
+    let api_token = sqlx::query_as!(
+        ApiToken,
+        r#"INSERT INTO api_tokens (user_id, name, token_hash, token_prefix, read_only)
+           VALUES (?, ?, ?, ?, ?)
+           RETURNING id as "id!", user_id, name, token_prefix, read_only as "read_only: bool",
+                     created_at, last_used_at, revoked_at"#,
+        user_id,
+        name,
+        token_hash,
+        token_prefix,
+        read_only
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        "Created API token {} for user {} (read only: {})",
+        api_token.id, user_id, read_only
+    );
+    Ok(api_token)
+}
+
+// Returns RowNotFound if the user has no such token or it was already revoked
+pub async fn revoke_api_token_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    token_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!("Revoking API token {} of user {}", token_id, user_id);
+
+    let result = sqlx::query!(
+        "UPDATE api_tokens SET revoked_at = datetime('now')
+         WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        token_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Revoked API token {} of user {}", token_id, user_id);
+    Ok(())
+}
+
+// The unrevoked token with this hash, marking it as just used
+pub async fn use_api_token_query(
+    pool: &Pool<Sqlite>,
+    token_hash: &str,
+) -> Result<Option<ApiToken>, sqlx::Error> {
+    sqlx::query_as!(
+        ApiToken,
+        r#"UPDATE api_tokens SET last_used_at = datetime('now')
+           WHERE token_hash = ? AND revoked_at IS NULL
+           RETURNING id as "id!", user_id, name, token_prefix, read_only as "read_only: bool",
+                     created_at, last_used_at, revoked_at"#,
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
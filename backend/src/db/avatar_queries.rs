@@ -0,0 +1,147 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sqlx::{Pool, Sqlite};
+use std::io;
+use tracing::{debug, info, warn};
+
+use crate::models::User;
+use crate::models::users::user_avatar_url;
+use crate::utils::{AVATAR_SIZES, FileStorage, resize_avatar};
+
+const AVATAR_KEY_PREFIX: &str = "avatars/";
+
+/// Storage key of one size of an avatar, e.g. `avatars/3fZ...k9-64.png`
+pub fn avatar_storage_key(avatar_key: &str, size: u32) -> String {
+    format!("{}-{}.png", avatar_key, size)
+}
+
+/// Write every size of an avatar to storage under a new avatar key, and return the key.
+/// Nothing is left behind if a write fails.
+pub async fn store_avatar_images(
+    storage: &FileStorage,
+    images: &[(u32, Vec<u8>)],
+) -> io::Result<String> {
+    let avatar_key = FileStorage::new_key("avatars", "png")
+        .trim_end_matches(".png")
+        .to_string();
+
+    for (size, bytes) in images {
+        if let Err(e) = storage
+            .write(&avatar_storage_key(&avatar_key, *size), bytes)
+            .await
+        {
+            remove_avatar_images(storage, &avatar_key).await;
+            return Err(e);
+        }
+    }
+
+    Ok(avatar_key)
+}
+
+/// Remove every size of an avatar from storage, logging rather than failing
+pub async fn remove_avatar_images(storage: &FileStorage, avatar_key: &str) {
+    for size in AVATAR_SIZES {
+        let storage_key = avatar_storage_key(avatar_key, size);
+        if let Err(e) = storage.remove(&storage_key).await {
+            warn!("Failed to remove stored avatar {}: {}", storage_key, e);
+        }
+    }
+}
+
+/// The avatar key of a user: `None` if there's no such user, `Some(None)` if they have
+/// no uploaded avatar
+pub async fn get_user_avatar_key_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Option<Option<String>>, sqlx::Error> {
+    let user = sqlx::query!("SELECT avatar_key FROM users WHERE id = ?", user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(user.map(|user| user.avatar_key))
+}
+
+/// Point a user's avatar at newly stored images, or clear it with `None`. Returns
+/// RowNotFound if there's no such user.
+pub async fn set_user_avatar_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    avatar_key: Option<&str>,
+) -> Result<User, sqlx::Error> {
+    debug!("Setting avatar of user {} to {:?}", user_id, avatar_key);
+
+    let avatar_url =
+        avatar_key.map(|key| user_avatar_url(user_id, key.trim_start_matches(AVATAR_KEY_PREFIX)));
+
+    let user = sqlx::query_as!(
+        User,
+        "UPDATE users SET avatar_key = ?, avatar_image = ?, updated_at = datetime('now') WHERE id = ?
+         RETURNING id, name, color, avatar_image, role, created_at, updated_at, last_login",
+        avatar_key,
+        avatar_url,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!("Updated avatar of user {}", user_id);
+    Ok(user)
+}
+
+/// Move avatars stored in `users.avatar_image` as base64 data URIs into upload storage.
+///
+/// Each decodable avatar is resized like an upload and `avatar_image` is replaced with
+/// its URL. Anything that isn't a recognisable image, and avatars that are already URLs,
+/// are left in place.
+///
+/// Returns the number of avatars moved. Running it again finds nothing left to move.
+pub async fn migrate_inline_avatars(
+    pool: &Pool<Sqlite>,
+    storage: &FileStorage,
+) -> Result<usize, sqlx::Error> {
+    debug!("Looking for inline avatars");
+
+    let users = sqlx::query!(
+        r#"SELECT id as "id!", avatar_image as "avatar_image!" FROM users
+           WHERE avatar_key IS NULL AND avatar_image LIKE 'data:image/%'"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut migrated = 0;
+    for user in users {
+        let Some((_, payload)) = user.avatar_image.split_once(";base64,") else {
+            warn!(
+                "Skipping inline avatar of user {} without base64 data",
+                user.id
+            );
+            continue;
+        };
+        let Ok(bytes) = STANDARD.decode(payload.trim()) else {
+            warn!("Skipping undecodable inline avatar of user {}", user.id);
+            continue;
+        };
+        let images = match resize_avatar(&bytes) {
+            Ok(images) => images,
+            Err(e) => {
+                warn!(
+                    "Skipping unreadable inline avatar of user {}: {}",
+                    user.id, e
+                );
+                continue;
+            }
+        };
+
+        let avatar_key = store_avatar_images(storage, &images)
+            .await
+            .map_err(sqlx::Error::Io)?;
+        if let Err(e) = set_user_avatar_query(pool, user.id, Some(&avatar_key)).await {
+            remove_avatar_images(storage, &avatar_key).await;
+            return Err(e);
+        }
+
+        info!("Moved inline avatar of user {} into storage", user.id);
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
@@ -1,13 +1,62 @@
 use reqwest::Client;
 use serde::Deserialize;
 use sqlx::{Pool, Row, Sqlite};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use tracing::{debug, info, warn};
 use url::form_urlencoded;
 
-use crate::models::books::{BookGenre, BookRating};
+use crate::db::journal_comment_queries::fetch_comment_counts_for_journals;
+use crate::db::journal_queries::fetch_tags_for_journals;
+use crate::db::journal_reaction_queries::fetch_reactions_for_journals;
+use crate::models::books::{
+    BookGenre, BookRating, BookRatingScore, DimensionAverage, RandomBookCandidate,
+    RandomBookFilters,
+};
 use crate::models::{Book, BookJournal, BookTag, BookWithDetails};
+use crate::utils::round_to_hundredths;
+
+/// Books a child can't see: those with a restricted tag or genre. Binds the viewer's
+/// user id twice and is empty unless the viewer is a child.
+pub(crate) const HIDDEN_BOOK_IDS_SQL: &str = "
+    SELECT bt.book_id FROM book_tags bt
+    JOIN tags t ON t.id = bt.tag_id
+    WHERE t.is_restricted = 1
+      AND EXISTS (SELECT 1 FROM users WHERE id = ? AND role = 'child')
+    UNION
+    SELECT bg.book_id FROM book_genres bg
+    JOIN genres g ON g.id = bg.genre_id
+    WHERE g.is_restricted = 1
+      AND EXISTS (SELECT 1 FROM users WHERE id = ? AND role = 'child')";
+
+/// Ids of the books hidden from `viewer_id`; see `HIDDEN_BOOK_IDS_SQL`
+pub(crate) async fn fetch_hidden_book_ids(
+    pool: &Pool<Sqlite>,
+    viewer_id: Option<i64>,
+) -> Result<HashSet<i64>, sqlx::Error> {
+    let Some(viewer_id) = viewer_id else {
+        return Ok(HashSet::new());
+    };
+
+    let rows = sqlx::query(HIDDEN_BOOK_IDS_SQL)
+        .bind(viewer_id)
+        .bind(viewer_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get("book_id")).collect())
+}
+
+/// The user who added a book, or None if there's no such book
+pub async fn get_book_owner_id_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    let book = sqlx::query!("SELECT user_id FROM books WHERE id = ?", id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(book.map(|book| book.user_id))
+}
 
 // Generic relationshpub async fn update_book_query(ment
 async fn manage_book_relationships(
@@ -16,6 +65,7 @@ async fn manage_book_relationships(
     item_ids: &[i64],
     table_name: &str,
     foreign_key_name: &str,
+    item_table: &str,
     item_type: &str,
 ) -> Result<(), sqlx::Error> {
     debug!(
@@ -39,10 +89,14 @@ async fn manage_book_relationships(
         book_id
     );
 
-    // Insert new relationships
+    // Insert new relationships, skipping items from other libraries than the book's
     if !item_ids.is_empty() {
-        let insert_query =
-            format!("INSERT INTO {table_name} (book_id, {foreign_key_name}) VALUES (?, ?)");
+        let insert_query = format!(
+            "INSERT INTO {table_name} (book_id, {foreign_key_name})
+             SELECT b.id, i.id FROM books b
+             INNER JOIN {item_table} i ON i.library_id = b.library_id
+             WHERE b.id = ? AND i.id = ?"
+        );
 
         for &item_id in item_ids {
             sqlx::query(&insert_query)
@@ -67,6 +121,48 @@ async fn manage_book_relationships(
     Ok(())
 }
 
+// Helper function to batch load per-dimension rating scores, keyed by rating id
+async fn fetch_scores_for_books(
+    pool: &Pool<Sqlite>,
+    book_ids: &[i64],
+) -> Result<HashMap<i64, Vec<BookRatingScore>>, sqlx::Error> {
+    if book_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = book_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT rs.rating_id, rs.dimension_id, rs.score, d.name as dimension_name
+         FROM rating_scores rs
+         INNER JOIN ratings r ON rs.rating_id = r.id
+         INNER JOIN rating_dimensions d ON rs.dimension_id = d.id
+         WHERE r.book_id IN ({placeholders})
+         ORDER BY d.position, d.id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &book_id in book_ids {
+        query_builder = query_builder.bind(book_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    let mut scores_map: HashMap<i64, Vec<BookRatingScore>> = HashMap::new();
+    for row in rows {
+        let rating_id: i64 = row.get("rating_id");
+        scores_map
+            .entry(rating_id)
+            .or_default()
+            .push(BookRatingScore {
+                dimension_id: row.get("dimension_id"),
+                dimension_name: row.get("dimension_name"),
+                score: row.get("score"),
+            });
+    }
+
+    Ok(scores_map)
+}
+
 // Helper function to batch load ratings for multiple books
 async fn fetch_ratings_for_books(
     pool: &Pool<Sqlite>,
@@ -92,14 +188,18 @@ async fn fetch_ratings_for_books(
 
     let rows = query_builder.fetch_all(pool).await?;
 
+    let mut scores_map = fetch_scores_for_books(pool, book_ids).await?;
+
     let mut ratings_map: HashMap<i64, Vec<BookRating>> = HashMap::new();
     for row in rows {
         let book_id: i64 = row.get("book_id");
+        let rating_id: i64 = row.get("id");
         let rating = BookRating {
-            id: row.get("id"),
+            id: rating_id,
             user_id: row.get("user_id"),
             book_id,
             rating: row.get("rating"),
+            scores: scores_map.remove(&rating_id).unwrap_or_default(),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
             user: crate::models::books::RatingUser {
@@ -166,6 +266,83 @@ async fn fetch_statuses_for_books(
     Ok(statuses_map)
 }
 
+// Helper function to batch load quote counts for multiple books
+async fn fetch_quote_counts_for_books(
+    pool: &Pool<Sqlite>,
+    book_ids: &[i64],
+) -> Result<HashMap<i64, i64>, sqlx::Error> {
+    if book_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = book_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT book_id, COUNT(*) as quote_count
+         FROM quotes
+         WHERE book_id IN ({placeholders})
+         GROUP BY book_id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &book_id in book_ids {
+        query_builder = query_builder.bind(book_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("book_id"), row.get("quote_count")))
+        .collect())
+}
+
+// Helper function to batch load each book's rating dimension averages, across everyone
+// who scored them
+async fn fetch_dimension_averages_for_books(
+    pool: &Pool<Sqlite>,
+    book_ids: &[i64],
+) -> Result<HashMap<i64, Vec<DimensionAverage>>, sqlx::Error> {
+    if book_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = book_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT r.book_id, d.id, d.name, d.min_value, d.max_value, AVG(rs.score) as average, COUNT(rs.id) as rating_count
+         FROM rating_scores rs
+         INNER JOIN ratings r ON rs.rating_id = r.id
+         INNER JOIN rating_dimensions d ON rs.dimension_id = d.id
+         WHERE r.book_id IN ({placeholders})
+         GROUP BY r.book_id, d.id
+         ORDER BY d.position, d.id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &book_id in book_ids {
+        query_builder = query_builder.bind(book_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    let mut averages_map: HashMap<i64, Vec<DimensionAverage>> = HashMap::new();
+    for row in rows {
+        let average: f64 = row.get("average");
+        averages_map
+            .entry(row.get("book_id"))
+            .or_default()
+            .push(DimensionAverage {
+                dimension_id: row.get("id"),
+                name: row.get("name"),
+                min_value: row.get("min_value"),
+                max_value: row.get("max_value"),
+                average: round_to_hundredths(average),
+                rating_count: row.get("rating_count"),
+            });
+    }
+
+    Ok(averages_map)
+}
+
 // Helper function to batch load current user statuses for multiple books
 async fn fetch_current_user_statuses(
     pool: &Pool<Sqlite>,
@@ -198,13 +375,102 @@ async fn fetch_current_user_statuses(
     Ok(status_map)
 }
 
-// Helper function to fetch book details with optional pre-fetched ratings/statuses
+// Helper function to batch load each book's journal entries with their tags, comment
+// counts and reactions, newest first. Private entries are only included for their author.
+async fn fetch_journals_for_books(
+    pool: &Pool<Sqlite>,
+    book_ids: &[i64],
+    current_user_id: Option<i64>,
+) -> Result<HashMap<i64, Vec<BookJournal>>, sqlx::Error> {
+    if book_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = book_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT je.id, je.book_id, je.title, je.content, je.visibility, je.created_at, je.updated_at, u.id as user_id, u.name as user_name, u.color, u.avatar_image
+         FROM journal_entries je
+         INNER JOIN users u ON je.user_id = u.id
+         WHERE je.book_id IN ({placeholders}) AND (je.visibility = 'household' OR je.user_id = ?)
+         ORDER BY je.created_at DESC"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &book_id in book_ids {
+        query_builder = query_builder.bind(book_id);
+    }
+    let rows = query_builder.bind(current_user_id).fetch_all(pool).await?;
+
+    let journal_ids: Vec<i64> = rows.iter().map(|row| row.get("id")).collect();
+    let mut journal_tags = fetch_tags_for_journals(pool, &journal_ids).await?;
+    let comment_counts = fetch_comment_counts_for_journals(pool, &journal_ids).await?;
+    let mut journal_reactions = fetch_reactions_for_journals(pool, &journal_ids).await?;
+
+    let mut journals_map: HashMap<i64, Vec<BookJournal>> = HashMap::new();
+    for row in rows {
+        let journal_id: i64 = row.get("id");
+        journals_map
+            .entry(row.get("book_id"))
+            .or_default()
+            .push(BookJournal {
+                id: journal_id,
+                title: row.get("title"),
+                content: row.get("content"),
+                visibility: row.get("visibility"),
+                tags: journal_tags.remove(&journal_id).unwrap_or_default(),
+                comment_count: comment_counts.get(&journal_id).copied().unwrap_or(0),
+                reactions: journal_reactions.remove(&journal_id).unwrap_or_default(),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+                user: crate::models::books::JournalUser {
+                    id: row.get("user_id"),
+                    name: row.get("user_name"),
+                    color: row.get("color"),
+                    avatar_image: row.get("avatar_image"),
+                },
+            });
+    }
+
+    Ok(journals_map)
+}
+
+// Everything about a set of books that is loaded for all of them at once
+struct BookDetailsBatch {
+    journals: HashMap<i64, Vec<BookJournal>>,
+    ratings: HashMap<i64, Vec<BookRating>>,
+    statuses: HashMap<i64, Vec<crate::models::books::BookStatus>>,
+    current_user_statuses: HashMap<i64, i64>,
+    quote_counts: HashMap<i64, i64>,
+    dimension_averages: HashMap<i64, Vec<DimensionAverage>>,
+}
+
+impl BookDetailsBatch {
+    async fn fetch(
+        pool: &Pool<Sqlite>,
+        book_ids: &[i64],
+        current_user_id: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        let current_user_statuses = match current_user_id {
+            Some(user_id) => fetch_current_user_statuses(pool, book_ids, user_id).await?,
+            None => HashMap::new(),
+        };
+
+        Ok(BookDetailsBatch {
+            journals: fetch_journals_for_books(pool, book_ids, current_user_id).await?,
+            ratings: fetch_ratings_for_books(pool, book_ids).await?,
+            statuses: fetch_statuses_for_books(pool, book_ids).await?,
+            current_user_statuses,
+            quote_counts: fetch_quote_counts_for_books(pool, book_ids).await?,
+            dimension_averages: fetch_dimension_averages_for_books(pool, book_ids).await?,
+        })
+    }
+}
+
+// Helper function to fetch book details, taking what was batch loaded from `batch`
 async fn fetch_book_details(
     pool: &Pool<Sqlite>,
     book: Book,
-    ratings: Vec<BookRating>,
-    statuses: Vec<crate::models::books::BookStatus>,
-    current_user_status: Option<i64>,
+    batch: &mut BookDetailsBatch,
 ) -> Result<BookWithDetails, sqlx::Error> {
     let book_id = book.id;
 
@@ -250,36 +516,7 @@ async fn fetch_book_details(
         })
         .collect();
 
-    // Get journals for the book with user information
-    let journals = sqlx::query(
-        "SELECT je.id, je.title, je.content, je.created_at, je.updated_at, u.id as user_id, u.name as user_name, u.color, u.avatar_image
-         FROM journal_entries je
-         INNER JOIN users u ON je.user_id = u.id
-         WHERE je.book_id = ?
-         ORDER BY je.created_at DESC",
-    )
-    .bind(book_id)
-    .fetch_all(pool)
-    .await?;
-
-    let book_journals: Vec<BookJournal> = journals
-        .into_iter()
-        .map(|row| BookJournal {
-            id: row.get("id"),
-            title: row.get("title"),
-            content: row.get("content"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-            user: crate::models::books::JournalUser {
-                id: row.get("user_id"),
-                name: row.get("user_name"),
-                color: row.get("color"),
-                avatar_image: row.get("avatar_image"),
-            },
-        })
-        .collect();
-
-    // Ratings and statuses are now passed as parameters (pre-fetched in batch)
+    // Journals, ratings, statuses, quote counts and dimension averages are pre-fetched in batch
 
     Ok(BookWithDetails {
         id: book.id,
@@ -288,14 +525,20 @@ async fn fetch_book_details(
         title: book.title,
         author: book.author,
         series: book.series,
+        page_count: book.page_count,
         created_at: book.created_at,
         updated_at: book.updated_at,
         tags: book_tags,
         genres: book_genres,
-        journals: book_journals,
-        ratings,
-        statuses,
-        current_user_status,
+        journals: batch.journals.remove(&book_id).unwrap_or_default(),
+        quote_count: batch.quote_counts.get(&book_id).copied().unwrap_or(0),
+        ratings: batch.ratings.remove(&book_id).unwrap_or_default(),
+        dimension_averages: batch
+            .dimension_averages
+            .remove(&book_id)
+            .unwrap_or_default(),
+        statuses: batch.statuses.remove(&book_id).unwrap_or_default(),
+        current_user_status: batch.current_user_statuses.get(&book_id).copied(),
     })
 }
 
@@ -363,7 +606,11 @@ pub async fn default_book_cover_query(
     Ok(body.url)
 }
 
-pub async fn create_book_query(pool: &Pool<Sqlite>, book: Book) -> Result<Book, sqlx::Error> {
+pub async fn create_book_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book: Book,
+) -> Result<Book, sqlx::Error> {
     debug!(
         "Attempting to create book: '{}' for user: {}",
         book.title, book.user_id
@@ -371,13 +618,15 @@ pub async fn create_book_query(pool: &Pool<Sqlite>, book: Book) -> Result<Book,
     debug!("Book details - Author: '{}'", book.author);
 
     let row = sqlx::query!(
-        "INSERT INTO books (user_id, cover_image, title, author, series) VALUES (?, ?, ?, ?, ?) 
-         RETURNING id, user_id, cover_image, title, author, series, created_at, updated_at",
+        "INSERT INTO books (user_id, library_id, cover_image, title, author, series, page_count) VALUES (?, ?, ?, ?, ?, ?, ?) 
+         RETURNING id, user_id, cover_image, title, author, series, page_count, created_at, updated_at",
         book.user_id,
+        library_id,
         book.cover_image,
         book.title,
         book.author,
-        book.series
+        book.series,
+        book.page_count
     )
     .fetch_one(pool)
     .await?;
@@ -389,6 +638,7 @@ pub async fn create_book_query(pool: &Pool<Sqlite>, book: Book) -> Result<Book,
         title: row.title,
         author: row.author,
         series: row.series,
+        page_count: row.page_count,
         created_at: row.created_at,
         updated_at: row.updated_at,
     };
@@ -410,7 +660,7 @@ pub async fn create_book_tags(
     book_id: i64,
     tag_ids: &[i64],
 ) -> Result<(), sqlx::Error> {
-    manage_book_relationships(pool, book_id, tag_ids, "book_tags", "tag_id", "tag").await
+    manage_book_relationships(pool, book_id, tag_ids, "book_tags", "tag_id", "tags", "tag").await
 }
 
 pub async fn update_book_tags(
@@ -418,7 +668,7 @@ pub async fn update_book_tags(
     book_id: i64,
     tag_ids: &[i64],
 ) -> Result<(), sqlx::Error> {
-    manage_book_relationships(pool, book_id, tag_ids, "book_tags", "tag_id", "tag").await
+    manage_book_relationships(pool, book_id, tag_ids, "book_tags", "tag_id", "tags", "tag").await
 }
 
 pub async fn update_book_genres(
@@ -426,32 +676,167 @@ pub async fn update_book_genres(
     book_id: i64,
     genre_ids: &[i64],
 ) -> Result<(), sqlx::Error> {
-    manage_book_relationships(pool, book_id, genre_ids, "book_genres", "genre_id", "genre").await
+    manage_book_relationships(
+        pool,
+        book_id,
+        genre_ids,
+        "book_genres",
+        "genre_id",
+        "genres",
+        "genre",
+    )
+    .await
 }
 
 pub async fn update_book_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     id: i64,
     book: Book,
 ) -> Result<Book, sqlx::Error> {
     let updated_book = sqlx::query_as!(
         Book,
-        "UPDATE books SET cover_image = ?, title = ?, author = ?, series = ?, updated_at = datetime('now') WHERE id = ? RETURNING id, user_id, cover_image, title, author, series, created_at, updated_at",
+        "UPDATE books SET cover_image = ?, title = ?, author = ?, series = ?, page_count = ?, updated_at = datetime('now') WHERE id = ? AND library_id = ? RETURNING id, user_id, cover_image, title, author, series, page_count, created_at, updated_at",
         book.cover_image,
         book.title,
         book.author,
         book.series,
-        id
+        book.page_count,
+        id,
+        library_id
     )
     .fetch_one(pool)
     .await?;
 
     Ok(updated_book)
 }
-pub async fn delete_book_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+/// Find the books the random picker can choose from for a user
+pub async fn get_random_book_candidates_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    filters: &RandomBookFilters,
+) -> Result<Vec<RandomBookCandidate>, sqlx::Error> {
+    debug!(
+        "Querying random book candidates for user {} with status {}",
+        user_id, filters.status_id
+    );
+
+    let mut query = String::from(
+        "SELECT b.id, MAX(julianday('now') - julianday(rs.updated_at), 0) as days_on_status
+         FROM books b
+         INNER JOIN reading_status rs ON rs.book_id = b.id AND rs.user_id = ?
+         WHERE b.library_id = ? AND rs.status_id = ?",
+    );
+
+    if filters.genre_id.is_some() {
+        query.push_str(" AND b.id IN (SELECT book_id FROM book_genres WHERE genre_id = ?)");
+    }
+    if filters.tag_id.is_some() {
+        query.push_str(" AND b.id IN (SELECT book_id FROM book_tags WHERE tag_id = ?)");
+    }
+    if filters.max_pages.is_some() {
+        query.push_str(" AND b.page_count IS NOT NULL AND b.page_count <= ?");
+    }
+    if filters.series_starters_only {
+        // Standalones, or the first book added for their series. Series have no explicit
+        // ordering, so the earliest added book stands in for the first in the series.
+        query.push_str(
+            " AND (b.series IS NULL OR b.series = ''
+                   OR b.id = (SELECT MIN(b2.id) FROM books b2
+                              WHERE b2.series = b.series AND b2.library_id = b.library_id))",
+        );
+    }
+    if filters.skip_last_finished_author {
+        query.push_str(
+            " AND LOWER(b.author) != COALESCE((
+                SELECT LOWER(fb.author)
+                FROM reading_status frs
+                INNER JOIN status fs ON frs.status_id = fs.id
+                INNER JOIN books fb ON frs.book_id = fb.id
+                WHERE frs.user_id = ? AND fs.is_finished = 1
+                ORDER BY frs.updated_at DESC
+                LIMIT 1
+            ), '')",
+        );
+    }
+    if !filters.exclude.is_empty() {
+        let placeholders = filters
+            .exclude
+            .iter()
+            .map(|_| "?")
+            .collect::<Vec<_>>()
+            .join(",");
+        query.push_str(&format!(" AND b.id NOT IN ({placeholders})"));
+    }
+    query.push_str(" ORDER BY b.id");
+
+    let mut query_builder = sqlx::query(&query)
+        .bind(user_id)
+        .bind(library_id)
+        .bind(filters.status_id);
+    if let Some(genre_id) = filters.genre_id {
+        query_builder = query_builder.bind(genre_id);
+    }
+    if let Some(tag_id) = filters.tag_id {
+        query_builder = query_builder.bind(tag_id);
+    }
+    if let Some(max_pages) = filters.max_pages {
+        query_builder = query_builder.bind(max_pages);
+    }
+    if filters.skip_last_finished_author {
+        query_builder = query_builder.bind(user_id);
+    }
+    for &book_id in &filters.exclude {
+        query_builder = query_builder.bind(book_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+    let candidates: Vec<RandomBookCandidate> = rows
+        .into_iter()
+        .map(|row| RandomBookCandidate {
+            id: row.get("id"),
+            days_on_status: row.get::<Option<f64>, _>("days_on_status").unwrap_or(0.0),
+        })
+        .collect();
+
+    info!(
+        "Found {} random book candidates for user {}",
+        candidates.len(),
+        user_id
+    );
+    Ok(candidates)
+}
+
+/// Check whether a book id exists in the books table
+pub async fn book_exists_query(pool: &Pool<Sqlite>, id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(r#"SELECT id as "id!" FROM books WHERE id = ?"#, id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some())
+}
+
+// Returns RowNotFound if the book isn't in the library
+pub async fn delete_book_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+) -> Result<(), sqlx::Error> {
     // Start a transaction to ensure all deletes succeed or fail together
     let mut tx = pool.begin().await?;
 
+    let book = sqlx::query!(
+        "SELECT id FROM books WHERE id = ? AND library_id = ?",
+        id,
+        library_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if book.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
     // Delete associated book_tags first
     sqlx::query!("DELETE FROM book_tags WHERE book_id = ?", id)
         .execute(&mut *tx)
@@ -462,6 +847,14 @@ pub async fn delete_book_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx:
         .execute(&mut *tx)
         .await?;
 
+    // Delete tags on the book's journal entries, which would otherwise block deleting them
+    sqlx::query!(
+        "DELETE FROM journal_entry_tags WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE book_id = ?)",
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
     // Delete associated journal entries (these cascade due to foreign key constraint)
     sqlx::query!("DELETE FROM journal_entries WHERE book_id = ?", id)
         .execute(&mut *tx)
@@ -485,6 +878,7 @@ pub async fn delete_book_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx:
 
 pub async fn get_book_details_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     id: i64,
     current_user_id: Option<i64>,
 ) -> Result<Option<BookWithDetails>, sqlx::Error> {
@@ -493,8 +887,9 @@ pub async fn get_book_details_query(
     // First get the book
     let book = sqlx::query_as!(
         Book,
-        "SELECT id, user_id, cover_image, title, author, series, created_at, updated_at FROM books WHERE id = ?",
-        id
+        "SELECT id, user_id, cover_image, title, author, series, page_count, created_at, updated_at FROM books WHERE id = ? AND library_id = ?",
+        id,
+        library_id
     )
     .fetch_optional(pool)
     .await?;
@@ -504,68 +899,58 @@ pub async fn get_book_details_query(
         return Ok(None);
     };
 
-    info!("Found book with ID {}: '{}'", id, book.title);
-
-    // Fetch ratings and statuses for this book
-    let book_ids = vec![id];
-    let mut ratings_map = fetch_ratings_for_books(pool, &book_ids).await?;
-    let mut statuses_map = fetch_statuses_for_books(pool, &book_ids).await?;
+    if fetch_hidden_book_ids(pool, current_user_id)
+        .await?
+        .contains(&id)
+    {
+        info!("Book {} is restricted for user {:?}", id, current_user_id);
+        return Ok(None);
+    }
 
-    let ratings = ratings_map.remove(&id).unwrap_or_default();
-    let statuses = statuses_map.remove(&id).unwrap_or_default();
+    info!("Found book with ID {}: '{}'", id, book.title);
 
-    // Get current user status if provided
-    let current_user_status = if let Some(user_id) = current_user_id {
-        let status_map = fetch_current_user_statuses(pool, &book_ids, user_id).await?;
-        status_map.get(&id).copied()
-    } else {
-        None
-    };
+    let mut batch = BookDetailsBatch::fetch(pool, &[id], current_user_id).await?;
 
     // Use helper function to fetch all details
-    let book_with_details =
-        fetch_book_details(pool, book, ratings, statuses, current_user_status).await?;
+    let book_with_details = fetch_book_details(pool, book, &mut batch).await?;
     Ok(Some(book_with_details))
 }
 
 pub async fn get_all_books_with_details_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     current_user_id: Option<i64>,
 ) -> Result<Vec<BookWithDetails>, sqlx::Error> {
-    debug!("Querying database for all books with details");
+    debug!(
+        "Querying database for all books with details in library {}",
+        library_id
+    );
 
     // First get all books
     let books = sqlx::query_as!(
         Book,
-        "SELECT id, user_id, cover_image, title, author, series, created_at, updated_at FROM books ORDER BY updated_at DESC"
+        r#"SELECT id as "id!", user_id, cover_image, title, author, series, page_count, created_at, updated_at FROM books WHERE library_id = ? ORDER BY updated_at DESC"#,
+        library_id
     )
     .fetch_all(pool)
     .await?;
 
+    let hidden_book_ids = fetch_hidden_book_ids(pool, current_user_id).await?;
+    let books: Vec<Book> = books
+        .into_iter()
+        .filter(|book| !hidden_book_ids.contains(&book.id))
+        .collect();
+
     info!("Found {} books", books.len());
 
-    // Batch fetch ratings and statuses for all books
+    // Batch fetch journals, ratings, statuses, quote counts and dimension averages for all books
     let book_ids: Vec<i64> = books.iter().map(|b| b.id).collect();
-    let mut ratings_map = fetch_ratings_for_books(pool, &book_ids).await?;
-    let mut statuses_map = fetch_statuses_for_books(pool, &book_ids).await?;
-
-    // Fetch current user statuses if user_id is provided
-    let current_user_statuses = if let Some(user_id) = current_user_id {
-        fetch_current_user_statuses(pool, &book_ids, user_id).await?
-    } else {
-        HashMap::new()
-    };
+    let mut batch = BookDetailsBatch::fetch(pool, &book_ids, current_user_id).await?;
 
     // Use helper function to fetch details for each book
     let mut books_with_details = Vec::new();
     for book in books {
-        let book_id = book.id;
-        let ratings = ratings_map.remove(&book_id).unwrap_or_default();
-        let statuses = statuses_map.remove(&book_id).unwrap_or_default();
-        let current_user_status = current_user_statuses.get(&book_id).copied();
-
-        let book_with_details =
-            fetch_book_details(pool, book, ratings, statuses, current_user_status).await?;
+        let book_with_details = fetch_book_details(pool, book, &mut batch).await?;
         books_with_details.push(book_with_details);
     }
 
@@ -578,6 +963,7 @@ pub async fn get_all_books_with_details_query(
 
 pub async fn search_books_with_details_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     search_term: &str,
     current_user_id: Option<i64>,
 ) -> Result<Vec<BookWithDetails>, sqlx::Error> {
@@ -591,10 +977,11 @@ pub async fn search_books_with_details_query(
     // First get matching books
     let books = sqlx::query_as!(
         Book,
-        "SELECT id, user_id, cover_image, title, author, series, created_at, updated_at 
+        r#"SELECT id as "id!", user_id, cover_image, title, author, series, page_count, created_at, updated_at 
          FROM books 
-         WHERE title LIKE ? OR author LIKE ? OR series LIKE ?
-         ORDER BY updated_at DESC",
+         WHERE library_id = ? AND (title LIKE ? OR author LIKE ? OR series LIKE ?)
+         ORDER BY updated_at DESC"#,
+        library_id,
         search_pattern,
         search_pattern,
         search_pattern
@@ -602,34 +989,26 @@ pub async fn search_books_with_details_query(
     .fetch_all(pool)
     .await?;
 
+    let hidden_book_ids = fetch_hidden_book_ids(pool, current_user_id).await?;
+    let books: Vec<Book> = books
+        .into_iter()
+        .filter(|book| !hidden_book_ids.contains(&book.id))
+        .collect();
+
     info!(
         "Found {} books matching search term '{}'",
         books.len(),
         search_term
     );
 
-    // Batch fetch ratings and statuses for all books
+    // Batch fetch journals, ratings, statuses, quote counts and dimension averages for all books
     let book_ids: Vec<i64> = books.iter().map(|b| b.id).collect();
-    let mut ratings_map = fetch_ratings_for_books(pool, &book_ids).await?;
-    let mut statuses_map = fetch_statuses_for_books(pool, &book_ids).await?;
-
-    // Fetch current user statuses if user_id is provided
-    let current_user_statuses = if let Some(user_id) = current_user_id {
-        fetch_current_user_statuses(pool, &book_ids, user_id).await?
-    } else {
-        HashMap::new()
-    };
+    let mut batch = BookDetailsBatch::fetch(pool, &book_ids, current_user_id).await?;
 
     // Use helper function to fetch details for each book
     let mut books_with_details = Vec::new();
     for book in books {
-        let book_id = book.id;
-        let ratings = ratings_map.remove(&book_id).unwrap_or_default();
-        let statuses = statuses_map.remove(&book_id).unwrap_or_default();
-        let current_user_status = current_user_statuses.get(&book_id).copied();
-
-        let book_with_details =
-            fetch_book_details(pool, book, ratings, statuses, current_user_status).await?;
+        let book_with_details = fetch_book_details(pool, book, &mut batch).await?;
         books_with_details.push(book_with_details);
     }
 
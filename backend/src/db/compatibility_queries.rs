@@ -0,0 +1,253 @@
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+
+use crate::db::book_queries::fetch_hidden_book_ids;
+use crate::models::compatibility::{
+    CompatibilityBook, CompatibilityCell, CompatibilityMatrix, CompatibilityRow, CompatibilityUser,
+    LovedBook, SharedRating, TasteCompatibility,
+};
+use crate::utils::{HIGH_RATING, pearson_correlation, round_to_hundredths};
+
+// How many books to include in each agree/disagree/loved list
+const TOP_BOOKS: usize = 5;
+
+struct RatedBook {
+    user_id: i64,
+    book_id: i64,
+    rating: f64,
+    title: String,
+    author: String,
+    cover_image: Option<String>,
+}
+
+impl RatedBook {
+    fn book(&self) -> CompatibilityBook {
+        CompatibilityBook {
+            id: self.book_id,
+            title: self.title.clone(),
+            author: self.author.clone(),
+            cover_image: self.cover_image.clone(),
+        }
+    }
+}
+
+// Ratings by the library's members of the library's books
+async fn fetch_rated_books(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+) -> Result<Vec<RatedBook>, sqlx::Error> {
+    sqlx::query_as!(
+        RatedBook,
+        r#"
+        SELECT r.user_id as "user_id!", r.book_id as "book_id!", r.rating, b.title, b.author, b.cover_image
+        FROM ratings r
+        INNER JOIN books b ON r.book_id = b.id
+        INNER JOIN library_members lm ON lm.user_id = r.user_id AND lm.library_id = b.library_id
+        WHERE b.library_id = ?
+        ORDER BY b.title
+        "#,
+        library_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// Pairs of (user rating, other user rating) for every book both have rated
+fn shared_ratings(
+    rated: &[RatedBook],
+    user_id: i64,
+    other_user_id: i64,
+) -> Vec<(&RatedBook, &RatedBook)> {
+    let other_ratings: HashMap<i64, &RatedBook> = rated
+        .iter()
+        .filter(|r| r.user_id == other_user_id)
+        .map(|r| (r.book_id, r))
+        .collect();
+
+    rated
+        .iter()
+        .filter(|r| r.user_id == user_id)
+        .filter_map(|r| other_ratings.get(&r.book_id).map(|other| (r, *other)))
+        .collect()
+}
+
+fn correlation_of(shared: &[(&RatedBook, &RatedBook)]) -> Option<f64> {
+    let pairs: Vec<(f64, f64)> = shared.iter().map(|(a, b)| (a.rating, b.rating)).collect();
+    pearson_correlation(&pairs).map(round_to_hundredths)
+}
+
+// Books `user_id` rated highly that `other_user_id` has neither rated nor finished
+fn loved_but_unread(
+    rated: &[RatedBook],
+    finished: &HashSet<(i64, i64)>,
+    user_id: i64,
+    other_user_id: i64,
+) -> Vec<LovedBook> {
+    let other_rated: HashSet<i64> = rated
+        .iter()
+        .filter(|r| r.user_id == other_user_id)
+        .map(|r| r.book_id)
+        .collect();
+
+    let mut loved: Vec<LovedBook> = rated
+        .iter()
+        .filter(|r| r.user_id == user_id && r.rating >= HIGH_RATING)
+        .filter(|r| {
+            !other_rated.contains(&r.book_id) && !finished.contains(&(other_user_id, r.book_id))
+        })
+        .map(|r| LovedBook {
+            book: r.book(),
+            rating: r.rating,
+        })
+        .collect();
+
+    loved.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+    loved.truncate(TOP_BOOKS);
+    loved
+}
+
+async fn fetch_compatibility_user(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+) -> Result<Option<CompatibilityUser>, sqlx::Error> {
+    sqlx::query_as!(
+        CompatibilityUser,
+        r#"SELECT u.id as "id!", u.name, u.color FROM users u
+           INNER JOIN library_members lm ON lm.user_id = u.id
+           WHERE lm.library_id = ? AND u.id = ?"#,
+        library_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Compare two users' ratings on the books they've both rated.
+///
+/// Only the library's books count, and books restricted for the viewer are left out.
+/// Returns `None` if either user is not a member of the library.
+pub async fn get_taste_compatibility_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    other_user_id: i64,
+    viewer_id: Option<i64>,
+) -> Result<Option<TasteCompatibility>, sqlx::Error> {
+    debug!(
+        "Comparing taste of user {} and user {}",
+        user_id, other_user_id
+    );
+
+    let Some(user) = fetch_compatibility_user(pool, library_id, user_id).await? else {
+        return Ok(None);
+    };
+    let Some(other_user) = fetch_compatibility_user(pool, library_id, other_user_id).await? else {
+        return Ok(None);
+    };
+
+    let hidden_book_ids = fetch_hidden_book_ids(pool, viewer_id).await?;
+    let mut rated = fetch_rated_books(pool, library_id).await?;
+    rated.retain(|r| !hidden_book_ids.contains(&r.book_id));
+
+    let finished: HashSet<(i64, i64)> = sqlx::query!(
+        r#"
+        SELECT rs.user_id as "user_id!", rs.book_id as "book_id!"
+        FROM reading_status rs
+        INNER JOIN status s ON rs.status_id = s.id
+        WHERE s.is_finished = 1 AND rs.user_id IN (?, ?)
+        "#,
+        user_id,
+        other_user_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.user_id, row.book_id))
+    .collect();
+
+    let shared = shared_ratings(&rated, user_id, other_user_id);
+    let correlation = correlation_of(&shared);
+
+    let mut by_difference: Vec<SharedRating> = shared
+        .iter()
+        .map(|(mine, theirs)| SharedRating {
+            book: mine.book(),
+            user_rating: mine.rating,
+            other_user_rating: theirs.rating,
+            difference: (mine.rating - theirs.rating).abs(),
+        })
+        .collect();
+    by_difference.sort_by(|a, b| a.difference.total_cmp(&b.difference));
+
+    let agreements: Vec<SharedRating> = by_difference.iter().take(TOP_BOOKS).cloned().collect();
+    // Only books they actually rated differently count as disagreements
+    let disagreements: Vec<SharedRating> = by_difference
+        .iter()
+        .rev()
+        .filter(|r| r.difference > 0.0)
+        .take(TOP_BOOKS)
+        .cloned()
+        .collect();
+
+    let report = TasteCompatibility {
+        shared_count: shared.len() as i64,
+        correlation,
+        agreements,
+        disagreements,
+        loved_by_user_unread_by_other: loved_but_unread(&rated, &finished, user_id, other_user_id),
+        loved_by_other_unread_by_user: loved_but_unread(&rated, &finished, other_user_id, user_id),
+        user,
+        other_user,
+    };
+
+    info!(
+        "Users {} and {} share {} rated books (correlation {:?})",
+        user_id, other_user_id, report.shared_count, report.correlation
+    );
+    Ok(Some(report))
+}
+
+/// Pairwise taste compatibility between every member of the library
+pub async fn get_compatibility_matrix_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+) -> Result<CompatibilityMatrix, sqlx::Error> {
+    debug!("Building compatibility matrix for library {}", library_id);
+
+    let users = sqlx::query_as!(
+        CompatibilityUser,
+        r#"SELECT u.id as "id!", u.name, u.color FROM users u
+           INNER JOIN library_members lm ON lm.user_id = u.id
+           WHERE lm.library_id = ?
+           ORDER BY u.id"#,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let rated = fetch_rated_books(pool, library_id).await?;
+
+    let rows: Vec<CompatibilityRow> = users
+        .iter()
+        .map(|user| CompatibilityRow {
+            user_id: user.id,
+            scores: users
+                .iter()
+                .filter(|other| other.id != user.id)
+                .map(|other| {
+                    let shared = shared_ratings(&rated, user.id, other.id);
+                    CompatibilityCell {
+                        other_user_id: other.id,
+                        shared_count: shared.len() as i64,
+                        correlation: correlation_of(&shared),
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    info!("Built compatibility matrix for {} users", users.len());
+    Ok(CompatibilityMatrix { users, rows })
+}
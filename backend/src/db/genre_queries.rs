@@ -3,12 +3,16 @@ use tracing::{debug, info};
 
 use crate::models::books::{CreateGenreRequest, Genre, UpdateGenreRequest};
 
-pub async fn get_all_genres_query(pool: &Pool<Sqlite>) -> Result<Vec<Genre>, Error> {
-    debug!("Querying database for all genres");
+pub async fn get_all_genres_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+) -> Result<Vec<Genre>, Error> {
+    debug!("Querying database for all genres in library {}", library_id);
 
     let genres = sqlx::query_as!(
         Genre,
-        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM genres ORDER BY name"
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM genres WHERE library_id = ? ORDER BY name",
+        library_id
     )
     .fetch_all(pool)
     .await?;
@@ -19,6 +23,7 @@ pub async fn get_all_genres_query(pool: &Pool<Sqlite>) -> Result<Vec<Genre>, Err
 
 pub async fn get_genres_by_name_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     name_filter: &str,
 ) -> Result<Vec<Genre>, Error> {
     debug!(
@@ -29,7 +34,8 @@ pub async fn get_genres_by_name_query(
     let pattern = format!("%{name_filter}%");
     let genres = sqlx::query_as!(
         Genre,
-        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM genres WHERE name LIKE ? ORDER BY name",
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM genres WHERE library_id = ? AND name LIKE ? ORDER BY name",
+        library_id,
         pattern
     )
     .fetch_all(pool)
@@ -39,13 +45,18 @@ pub async fn get_genres_by_name_query(
     Ok(genres)
 }
 
-pub async fn get_genre_by_id_query(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Genre>, Error> {
+pub async fn get_genre_by_id_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+) -> Result<Option<Genre>, Error> {
     debug!("Querying database for genre with id: {}", id);
 
     let genre = sqlx::query_as!(
         Genre,
-        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM genres WHERE id = ?",
-        id
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM genres WHERE id = ? AND library_id = ?",
+        id,
+        library_id
     )
     .fetch_optional(pool)
     .await?;
@@ -61,16 +72,20 @@ pub async fn get_genre_by_id_query(pool: &Pool<Sqlite>, id: i64) -> Result<Optio
 
 pub async fn create_genre_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     genre: &CreateGenreRequest,
     user_id: i64,
 ) -> Result<Genre, Error> {
     debug!("Creating new genre: {}", genre.name);
 
+    let is_restricted = genre.is_restricted.unwrap_or(false);
     let result = sqlx::query!(
-        "INSERT INTO genres (user_id, name, color) VALUES (?, ?, ?)",
+        "INSERT INTO genres (user_id, library_id, name, color, is_restricted) VALUES (?, ?, ?, ?, ?)",
         user_id,
+        library_id,
         genre.name,
-        genre.color
+        genre.color,
+        is_restricted
     )
     .execute(pool)
     .await?;
@@ -79,12 +94,13 @@ pub async fn create_genre_query(
     info!("Created genre with ID: {}", genre_id);
 
     // Fetch the created genre to return it
-    let created_genre = get_genre_by_id_query(pool, genre_id).await?;
+    let created_genre = get_genre_by_id_query(pool, library_id, genre_id).await?;
     created_genre.ok_or_else(|| Error::RowNotFound)
 }
 
 pub async fn update_genre_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     id: i64,
     genre: &UpdateGenreRequest,
 ) -> Result<Genre, Error> {
@@ -92,10 +108,12 @@ pub async fn update_genre_query(
 
     let updated_genre = sqlx::query_as!(
         Genre,
-        "UPDATE genres SET name = ?, color = ?, updated_at = datetime('now') WHERE id = ? RETURNING id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at",
+        "UPDATE genres SET name = ?, color = ?, is_restricted = COALESCE(?, is_restricted), updated_at = datetime('now') WHERE id = ? AND library_id = ? RETURNING id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at",
         genre.name,
         genre.color,
-        id
+        genre.is_restricted,
+        id,
+        library_id
     )
     .fetch_one(pool)
     .await?;
@@ -104,12 +122,20 @@ pub async fn update_genre_query(
     Ok(updated_genre)
 }
 
-pub async fn delete_genre_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), Error> {
+pub async fn delete_genre_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+) -> Result<(), Error> {
     debug!("Deleting genre with id: {}", id);
 
-    let result = sqlx::query!("DELETE FROM genres WHERE id = ?", id)
-        .execute(pool)
-        .await?;
+    let result = sqlx::query!(
+        "DELETE FROM genres WHERE id = ? AND library_id = ?",
+        id,
+        library_id
+    )
+    .execute(pool)
+    .await?;
 
     let rows_affected = result.rows_affected();
     if rows_affected == 0 {
@@ -130,8 +156,12 @@ pub async fn add_genre_to_book_query(
 ) -> Result<(), Error> {
     debug!("Adding genre {} to book {}", genre_id, book_id);
 
+    // Genres from another library than the book's are left off
     sqlx::query!(
-        "INSERT INTO book_genres (book_id, genre_id) VALUES (?, ?)",
+        "INSERT INTO book_genres (book_id, genre_id)
+         SELECT b.id, g.id FROM books b
+         INNER JOIN genres g ON g.library_id = b.library_id
+         WHERE b.id = ? AND g.id = ?",
         book_id,
         genre_id
     )
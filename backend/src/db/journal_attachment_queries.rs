@@ -0,0 +1,412 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+use std::ops::Range;
+use tracing::{debug, info, warn};
+
+use crate::models::journal_attachments::{JournalAttachment, journal_attachment_url};
+use crate::utils::{FileStorage, sniff_image_type};
+
+const DATA_URI_PREFIX: &str = "data:image/";
+const BASE64_MARKER: &str = ";base64,";
+
+struct AttachmentRow {
+    id: i64,
+    journal_entry_id: i64,
+    book_id: i64,
+    user_id: i64,
+    file_name: String,
+    content_type: String,
+    size_bytes: i64,
+    storage_key: String,
+    created_at: Option<String>,
+}
+
+impl From<AttachmentRow> for JournalAttachment {
+    fn from(row: AttachmentRow) -> Self {
+        JournalAttachment {
+            url: journal_attachment_url(&row.storage_key),
+            id: row.id,
+            journal_entry_id: row.journal_entry_id,
+            book_id: row.book_id,
+            user_id: row.user_id,
+            file_name: row.file_name,
+            content_type: row.content_type,
+            size_bytes: row.size_bytes,
+            created_at: row.created_at,
+            storage_key: row.storage_key,
+        }
+    }
+}
+
+pub async fn get_journal_attachments_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<Vec<JournalAttachment>, sqlx::Error> {
+    debug!(
+        "Querying attachments for journal {} in book {}",
+        journal_id, book_id
+    );
+
+    let rows = sqlx::query_as!(
+        AttachmentRow,
+        r#"
+        SELECT a.id as "id!", a.journal_entry_id, je.book_id, a.user_id, a.file_name,
+               a.content_type, a.size_bytes, a.storage_key, a.created_at
+        FROM journal_attachments a
+        INNER JOIN journal_entries je ON a.journal_entry_id = je.id
+        INNER JOIN books b ON je.book_id = b.id
+        WHERE a.journal_entry_id = ? AND je.book_id = ? AND b.library_id = ?
+        ORDER BY a.id
+        "#,
+        journal_id,
+        book_id,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    info!(
+        "Retrieved {} attachments for journal {}",
+        rows.len(),
+        journal_id
+    );
+    Ok(rows.into_iter().map(JournalAttachment::from).collect())
+}
+
+// Helper function to batch load the attachments of several journal entries, keyed by journal id
+pub(crate) async fn fetch_attachments_for_journals(
+    pool: &Pool<Sqlite>,
+    journal_ids: &[i64],
+) -> Result<HashMap<i64, Vec<JournalAttachment>>, sqlx::Error> {
+    if journal_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = journal_ids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    let query = format!(
+        "SELECT a.id, a.journal_entry_id, je.book_id, a.user_id, a.file_name,
+                a.content_type, a.size_bytes, a.storage_key, a.created_at
+         FROM journal_attachments a
+         INNER JOIN journal_entries je ON a.journal_entry_id = je.id
+         WHERE a.journal_entry_id IN ({placeholders})
+         ORDER BY a.id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &journal_id in journal_ids {
+        query_builder = query_builder.bind(journal_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    let mut attachments_map: HashMap<i64, Vec<JournalAttachment>> = HashMap::new();
+    for row in rows {
+        let attachment = JournalAttachment::from(AttachmentRow {
+            id: row.get("id"),
+            journal_entry_id: row.get("journal_entry_id"),
+            book_id: row.get("book_id"),
+            user_id: row.get("user_id"),
+            file_name: row.get("file_name"),
+            content_type: row.get("content_type"),
+            size_bytes: row.get("size_bytes"),
+            storage_key: row.get("storage_key"),
+            created_at: row.get("created_at"),
+        });
+        attachments_map
+            .entry(attachment.journal_entry_id)
+            .or_default()
+            .push(attachment);
+    }
+
+    Ok(attachments_map)
+}
+
+// Looks an attachment up by where it is stored, which is how its public URL names it
+pub async fn get_journal_attachment_by_key_query(
+    pool: &Pool<Sqlite>,
+    storage_key: &str,
+) -> Result<Option<JournalAttachment>, sqlx::Error> {
+    debug!("Querying attachment stored at {}", storage_key);
+
+    let row = sqlx::query_as!(
+        AttachmentRow,
+        r#"
+        SELECT a.id as "id!", a.journal_entry_id, je.book_id, a.user_id, a.file_name,
+               a.content_type, a.size_bytes, a.storage_key, a.created_at
+        FROM journal_attachments a
+        INNER JOIN journal_entries je ON a.journal_entry_id = je.id
+        WHERE a.storage_key = ?
+        "#,
+        storage_key
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(JournalAttachment::from))
+}
+
+pub async fn create_journal_attachment_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    user_id: i64,
+    file_name: &str,
+    content_type: &str,
+    size_bytes: i64,
+    storage_key: &str,
+) -> Result<JournalAttachment, sqlx::Error> {
+    debug!(
+        "Creating attachment '{}' ({} bytes) for journal {}",
+        file_name, size_bytes, journal_id
+    );
+
+    let row = sqlx::query_as!(
+        AttachmentRow,
+        r#"
+        INSERT INTO journal_attachments (journal_entry_id, user_id, file_name, content_type, size_bytes, storage_key)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING id as "id!", journal_entry_id, user_id, file_name, content_type, size_bytes, storage_key, created_at,
+            (SELECT book_id FROM journal_entries WHERE id = journal_entry_id) as "book_id!: i64"
+        "#,
+        journal_id,
+        user_id,
+        file_name,
+        content_type,
+        size_bytes,
+        storage_key
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        "Created attachment {} for journal {}",
+        row.id, row.journal_entry_id
+    );
+    Ok(row.into())
+}
+
+// Returns the storage key of the deleted attachment, or RowNotFound
+pub async fn delete_journal_attachment_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    journal_id: i64,
+    attachment_id: i64,
+) -> Result<String, sqlx::Error> {
+    debug!(
+        "Deleting attachment {} of journal {} in book {}",
+        attachment_id, journal_id, book_id
+    );
+
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM journal_attachments
+        WHERE id = ? AND journal_entry_id = ?
+          AND journal_entry_id IN (SELECT id FROM journal_entries WHERE book_id = ?)
+        RETURNING storage_key
+        "#,
+        attachment_id,
+        journal_id,
+        book_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    info!("Deleted attachment {}", attachment_id);
+    Ok(row.storage_key)
+}
+
+// Storage keys of the files attached to a journal, so they can be removed along with it
+pub async fn get_journal_attachment_keys_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let keys = sqlx::query_scalar!(
+        "SELECT storage_key FROM journal_attachments WHERE journal_entry_id = ?",
+        journal_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+// Storage keys of the files attached to any of a book's journals
+pub async fn get_book_attachment_keys_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let keys = sqlx::query_scalar!(
+        r#"
+        SELECT a.storage_key
+        FROM journal_attachments a
+        INNER JOIN journal_entries je ON a.journal_entry_id = je.id
+        WHERE je.book_id = ?
+        "#,
+        book_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+// Byte range and base64 payload of every `data:image/...;base64,...` URI in the content
+fn find_inline_images(content: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(offset) = content[search_from..].find(DATA_URI_PREFIX) {
+        let start = search_from + offset;
+        let Some(marker) = content[start..].find(BASE64_MARKER) else {
+            break;
+        };
+
+        // The media type between `data:` and `;base64,` must be a plain token like `image/png`
+        let media_type = &content[start + "data:".len()..start + marker];
+        if !media_type
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/+.-".contains(c))
+        {
+            search_from = start + DATA_URI_PREFIX.len();
+            continue;
+        }
+
+        let payload_start = start + marker + BASE64_MARKER.len();
+        let payload_end = content[payload_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '='))
+            .map_or(content.len(), |len| payload_start + len);
+
+        found.push((start..payload_end, &content[payload_start..payload_end]));
+        search_from = payload_end;
+    }
+
+    found
+}
+
+/// Whether the content still has an image inlined as a base64 data URI. New content has
+/// to upload images as attachments instead.
+pub fn has_inline_images(content: &str) -> bool {
+    !find_inline_images(content).is_empty()
+}
+
+/// Move images inlined into journal content as base64 data URIs into attachments.
+///
+/// Each decodable image is written to storage, recorded as an attachment of its journal,
+/// and its data URI in the content is replaced with the attachment URL. Anything that isn't
+/// a recognisable image is left in place. Journal `updated_at` is not changed.
+///
+/// Returns the number of images extracted. Running it again finds nothing left to extract.
+pub async fn extract_inline_journal_images(
+    pool: &Pool<Sqlite>,
+    storage: &FileStorage,
+) -> Result<usize, sqlx::Error> {
+    debug!("Looking for inline images in journal content");
+
+    let journals = sqlx::query!(
+        r#"
+        SELECT je.id as "id!", je.user_id, je.content
+        FROM journal_entries je
+        WHERE je.content LIKE '%data:image/%'
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut extracted = 0;
+    for journal in journals {
+        let mut images = Vec::new();
+        for (range, payload) in find_inline_images(&journal.content) {
+            let Ok(bytes) = STANDARD.decode(payload) else {
+                warn!(
+                    "Skipping undecodable inline image in journal {}",
+                    journal.id
+                );
+                continue;
+            };
+            let Some((content_type, extension)) = sniff_image_type(&bytes) else {
+                warn!(
+                    "Skipping unrecognised inline image in journal {}",
+                    journal.id
+                );
+                continue;
+            };
+            images.push((range, bytes, content_type, extension));
+        }
+        if images.is_empty() {
+            continue;
+        }
+
+        let mut written_keys = Vec::new();
+        let result: Result<(), sqlx::Error> = async {
+            let mut tx = pool.begin().await?;
+            let mut content = String::with_capacity(journal.content.len());
+            let mut copied_to = 0;
+
+            for (index, (range, bytes, content_type, extension)) in images.iter().enumerate() {
+                let storage_key = FileStorage::new_key("journals", extension);
+                storage
+                    .write(&storage_key, bytes)
+                    .await
+                    .map_err(sqlx::Error::Io)?;
+                written_keys.push(storage_key.clone());
+
+                let file_name = format!("image-{}.{}", index + 1, extension);
+                let size_bytes = bytes.len() as i64;
+                sqlx::query!(
+                    r#"
+                    INSERT INTO journal_attachments (journal_entry_id, user_id, file_name, content_type, size_bytes, storage_key)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#,
+                    journal.id,
+                    journal.user_id,
+                    file_name,
+                    content_type,
+                    size_bytes,
+                    storage_key
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                content.push_str(&journal.content[copied_to..range.start]);
+                content.push_str(&journal_attachment_url(&storage_key));
+                copied_to = range.end;
+            }
+            content.push_str(&journal.content[copied_to..]);
+
+            sqlx::query!(
+                "UPDATE journal_entries SET content = ? WHERE id = ?",
+                content,
+                journal.id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            for key in &written_keys {
+                let _ = storage.remove(key).await;
+            }
+            return Err(e);
+        }
+
+        info!(
+            "Extracted {} inline images from journal {}",
+            images.len(),
+            journal.id
+        );
+        extracted += images.len();
+    }
+
+    Ok(extracted)
+}
@@ -0,0 +1,187 @@
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::models::books::JournalUser;
+use crate::models::journal_comments::JournalComment;
+
+// Helper function to batch count the comments (including replies) on several journal entries
+pub(crate) async fn fetch_comment_counts_for_journals(
+    pool: &Pool<Sqlite>,
+    journal_ids: &[i64],
+) -> Result<HashMap<i64, i64>, sqlx::Error> {
+    if journal_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = journal_ids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    let query = format!(
+        "SELECT journal_entry_id, COUNT(*) as comment_count
+         FROM journal_comments
+         WHERE journal_entry_id IN ({placeholders})
+         GROUP BY journal_entry_id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &journal_id in journal_ids {
+        query_builder = query_builder.bind(journal_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("journal_entry_id"), row.get("comment_count")))
+        .collect())
+}
+
+/// Comments on a journal entry as threads: top-level comments oldest first,
+/// each with its replies nested under it
+pub async fn get_journal_comments_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+) -> Result<Vec<JournalComment>, sqlx::Error> {
+    debug!("Querying database for comments on journal {}", journal_id);
+
+    let rows = sqlx::query!(
+        r#"SELECT c.id as "id!", c.journal_entry_id, c.parent_id, c.content, c.created_at, c.updated_at,
+                  u.id as "user_id!", u.name as user_name, u.color as user_color, u.avatar_image
+         FROM journal_comments c
+         INNER JOIN users u ON c.user_id = u.id
+         WHERE c.journal_entry_id = ?
+         ORDER BY c.created_at, c.id"#,
+        journal_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    info!("Found {} comments on journal {}", rows.len(), journal_id);
+
+    let mut replies: HashMap<i64, Vec<JournalComment>> = HashMap::new();
+    let mut top_level = Vec::new();
+    let comments: Vec<JournalComment> = rows
+        .into_iter()
+        .map(|row| JournalComment {
+            id: row.id,
+            journal_entry_id: row.journal_entry_id,
+            parent_id: row.parent_id,
+            content: row.content,
+            user: JournalUser {
+                id: row.user_id,
+                name: row.user_name,
+                color: row.user_color,
+                avatar_image: row.avatar_image,
+            },
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            replies: Vec::new(),
+        })
+        .collect();
+
+    for comment in comments {
+        match comment.parent_id {
+            Some(parent_id) => replies.entry(parent_id).or_default().push(comment),
+            None => top_level.push(comment),
+        }
+    }
+
+    fn attach_replies(
+        mut comment: JournalComment,
+        replies: &mut HashMap<i64, Vec<JournalComment>>,
+    ) -> JournalComment {
+        comment.replies = replies
+            .remove(&comment.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|reply| attach_replies(reply, replies))
+            .collect();
+        comment
+    }
+
+    Ok(top_level
+        .into_iter()
+        .map(|comment| attach_replies(comment, &mut replies))
+        .collect())
+}
+
+/// Whether `comment_id` is a comment on `journal_id`, i.e. something that can be replied to
+pub async fn journal_comment_exists_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    comment_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT id FROM journal_comments WHERE id = ? AND journal_entry_id = ?",
+        comment_id,
+        journal_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+pub async fn create_journal_comment_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    user_id: i64,
+    content: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, sqlx::Error> {
+    debug!(
+        "Creating comment on journal {} by user {} (reply to {:?})",
+        journal_id, user_id, parent_id
+    );
+
+    let comment_id = sqlx::query!(
+        r#"INSERT INTO journal_comments (journal_entry_id, user_id, parent_id, content)
+         VALUES (?, ?, ?, ?)
+         RETURNING id as "id!""#,
+        journal_id,
+        user_id,
+        parent_id,
+        content
+    )
+    .fetch_one(pool)
+    .await?
+    .id;
+
+    info!("Created comment {} on journal {}", comment_id, journal_id);
+    Ok(comment_id)
+}
+
+// A comment can be deleted by whoever wrote it or by the author of the journal entry
+// it's on; anyone else gets RowNotFound. Replies are deleted with it.
+pub async fn delete_journal_comment_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    comment_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Deleting comment {} on journal {} by user {}",
+        comment_id, journal_id, user_id
+    );
+
+    let result = sqlx::query!(
+        "DELETE FROM journal_comments
+         WHERE id = ? AND journal_entry_id = ?
+           AND (user_id = ? OR journal_entry_id IN (SELECT id FROM journal_entries WHERE user_id = ?))",
+        comment_id,
+        journal_id,
+        user_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Deleted comment {} on journal {}", comment_id, journal_id);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use sqlx::{Pool, Row, Sqlite};
+use tracing::debug;
+
+use crate::db::book_queries::HIDDEN_BOOK_IDS_SQL;
+use crate::db::journal_attachment_queries::fetch_attachments_for_journals;
+use crate::db::journal_queries::fetch_tags_for_journals;
+use crate::models::JournalEntry;
+use crate::models::journal_exports::JournalExportEntry;
+
+// Load the journal entries on a library's books to export, optionally limited to one book
+// and/or one author. Private entries are only included for their own author, and entries on
+// books restricted for the viewer are left out.
+pub async fn get_journal_export_entries_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: Option<i64>,
+    user_id: Option<i64>,
+    viewer_id: Option<i64>,
+) -> Result<Vec<JournalExportEntry>, sqlx::Error> {
+    debug!(
+        "Loading journal export entries (book: {:?}, user: {:?}, viewer: {:?})",
+        book_id, user_id, viewer_id
+    );
+
+    let rows = sqlx::query(&format!(
+        "SELECT je.id, je.book_id, je.user_id, je.title, je.content, je.visibility,
+                je.created_at, je.updated_at,
+                b.title as book_title, b.author as book_author, r.rating
+         FROM journal_entries je
+         INNER JOIN books b ON je.book_id = b.id
+         LEFT JOIN ratings r ON r.book_id = je.book_id AND r.user_id = je.user_id
+         WHERE b.library_id = ?
+           AND (? IS NULL OR je.book_id = ?)
+           AND (? IS NULL OR je.user_id = ?)
+           AND (je.visibility = 'household' OR je.user_id = ?)
+           AND je.book_id NOT IN ({HIDDEN_BOOK_IDS_SQL})
+         ORDER BY b.title COLLATE NOCASE, b.id, je.created_at, je.id"
+    ))
+    .bind(library_id)
+    .bind(book_id)
+    .bind(book_id)
+    .bind(user_id)
+    .bind(user_id)
+    .bind(viewer_id)
+    .bind(viewer_id)
+    .bind(viewer_id)
+    .fetch_all(pool)
+    .await?;
+
+    let journal_ids: Vec<i64> = rows.iter().map(|row| row.get("id")).collect();
+    let mut tags_map = fetch_tags_for_journals(pool, &journal_ids).await?;
+    let mut attachments_map = fetch_attachments_for_journals(pool, &journal_ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let id: i64 = row.get("id");
+            JournalExportEntry {
+                journal: JournalEntry {
+                    id,
+                    book_id: row.get("book_id"),
+                    user_id: row.get("user_id"),
+                    title: row.get("title"),
+                    content: row.get("content"),
+                    visibility: row.get("visibility"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    tags: tags_map.remove(&id).unwrap_or_default(),
+                },
+                book_title: row.get("book_title"),
+                book_author: row.get("book_author"),
+                rating: row.get("rating"),
+                attachments: attachments_map.remove(&id).unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+// The title of a book being exported, or None if there's no such book in the library
+pub async fn get_export_book_title_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let book = sqlx::query!(
+        "SELECT title FROM books WHERE id = ? AND library_id = ?",
+        book_id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(book.map(|book| book.title))
+}
+
+// The name of a user whose journals are being exported, or None if there's no such user
+pub async fn get_export_user_name_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let user = sqlx::query!("SELECT name FROM users WHERE id = ?", user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(user.map(|user| user.name))
+}
@@ -1,12 +1,146 @@
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
 use tracing::{debug, info, warn};
 
-use crate::models::JournalEntry;
+use crate::db::book_queries::{HIDDEN_BOOK_IDS_SQL, fetch_hidden_book_ids};
+use crate::models::journal_entries::JournalFeedBook;
+use crate::models::{BookTag, JournalEntry, JournalFeedItem, JournalFeedParams};
+
+struct JournalRow {
+    id: i64,
+    book_id: i64,
+    user_id: i64,
+    title: String,
+    content: String,
+    visibility: String,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+}
+
+impl JournalRow {
+    fn into_entry(self, tags: Vec<BookTag>) -> JournalEntry {
+        JournalEntry {
+            id: self.id,
+            book_id: self.book_id,
+            user_id: self.user_id,
+            title: self.title,
+            content: self.content,
+            visibility: self.visibility,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            tags,
+        }
+    }
+}
+
+// Helper function to batch load the tags of several journal entries, keyed by journal id
+pub(crate) async fn fetch_tags_for_journals(
+    pool: &Pool<Sqlite>,
+    journal_ids: &[i64],
+) -> Result<HashMap<i64, Vec<BookTag>>, sqlx::Error> {
+    if journal_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = journal_ids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    let query = format!(
+        "SELECT jet.journal_entry_id, t.id, t.name, t.color
+         FROM journal_entry_tags jet
+         INNER JOIN tags t ON jet.tag_id = t.id
+         WHERE jet.journal_entry_id IN ({placeholders})
+         ORDER BY t.name"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &journal_id in journal_ids {
+        query_builder = query_builder.bind(journal_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    let mut tags_map: HashMap<i64, Vec<BookTag>> = HashMap::new();
+    for row in rows {
+        tags_map
+            .entry(row.get("journal_entry_id"))
+            .or_default()
+            .push(BookTag {
+                id: row.get("id"),
+                name: row.get("name"),
+                color: row.get("color"),
+            });
+    }
+
+    Ok(tags_map)
+}
+
+async fn with_tags(
+    pool: &Pool<Sqlite>,
+    rows: Vec<JournalRow>,
+) -> Result<Vec<JournalEntry>, sqlx::Error> {
+    let journal_ids: Vec<i64> = rows.iter().map(|row| row.id).collect();
+    let mut tags_map = fetch_tags_for_journals(pool, &journal_ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let tags = tags_map.remove(&row.id).unwrap_or_default();
+            row.into_entry(tags)
+        })
+        .collect())
+}
+
+// Replace a journal entry's tags. Unknown tag ids fail with a foreign key violation, and
+// tags from another library than the entry's book are skipped.
+async fn replace_journal_tags(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    journal_id: i64,
+    tag_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM journal_entry_tags WHERE journal_entry_id = ?",
+        journal_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let mut unique_tag_ids = tag_ids.to_vec();
+    unique_tag_ids.sort_unstable();
+    unique_tag_ids.dedup();
+
+    for tag_id in unique_tag_ids {
+        sqlx::query!(
+            "INSERT INTO journal_entry_tags (journal_entry_id, tag_id)
+             SELECT ?, ? WHERE NOT EXISTS (
+                 SELECT 1 FROM tags t, journal_entries je
+                 INNER JOIN books b ON je.book_id = b.id
+                 WHERE t.id = ? AND je.id = ? AND t.library_id != b.library_id
+             )",
+            journal_id,
+            tag_id,
+            tag_id,
+            journal_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        debug!(
+            "Created journal tag relationship: journal_entry_id={}, tag_id={}",
+            journal_id, tag_id
+        );
+    }
+
+    Ok(())
+}
 
 // Journal queries
 pub async fn create_journal_entry(
     pool: &Pool<Sqlite>,
     journal: JournalEntry,
+    tag_ids: &[i64],
 ) -> Result<JournalEntry, sqlx::Error> {
     debug!(
         "Creating new journal entry for book ID: {} by user ID: {}",
@@ -17,85 +151,223 @@ pub async fn create_journal_entry(
         journal.title, journal.content
     );
 
+    let mut tx = pool.begin().await?;
+
     let row = sqlx::query!(
-        "INSERT INTO journal_entries (book_id, user_id, title, content) VALUES (?, ?, ?, ?) 
-         RETURNING id, book_id, user_id, title, content, created_at, updated_at",
+        "INSERT INTO journal_entries (book_id, user_id, title, content, visibility) VALUES (?, ?, ?, ?, ?)
+         RETURNING id, book_id, user_id, title, content, visibility, created_at, updated_at",
         journal.book_id,
         journal.user_id,
         journal.title,
-        journal.content
+        journal.content,
+        journal.visibility
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    let created_journal = JournalEntry {
+    let created_row = JournalRow {
         id: row.id.unwrap(),
         book_id: row.book_id,
         user_id: row.user_id,
         title: row.title,
         content: row.content,
+        visibility: row.visibility,
         created_at: row.created_at,
         updated_at: row.updated_at,
     };
 
+    replace_journal_tags(&mut tx, created_row.id, tag_ids).await?;
+
     // Update the associated book's updated_at timestamp
     sqlx::query!(
         "UPDATE books SET updated_at = datetime('now') WHERE id = ?",
-        created_journal.book_id
+        created_row.book_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    tx.commit().await?;
+
+    let created_journal = with_tags(pool, vec![created_row]).await?.remove(0);
+
     info!(
         "Successfully created journal entry '{}' with ID: {} for user: {}",
         created_journal.title, created_journal.id, created_journal.user_id
     );
     debug!(
-        "New journal entry record: ID={}, Book ID={}, User ID={}, Title='{}'",
-        created_journal.id, created_journal.book_id, created_journal.user_id, created_journal.title
+        "New journal entry record: ID={}, Book ID={}, User ID={}, Title='{}', Tags={}",
+        created_journal.id,
+        created_journal.book_id,
+        created_journal.user_id,
+        created_journal.title,
+        created_journal.tags.len()
     );
     debug!("Updated book ID {} timestamp", created_journal.book_id);
 
     Ok(created_journal)
 }
 
-pub async fn get_all_journals(pool: &Pool<Sqlite>) -> Result<Vec<JournalEntry>, sqlx::Error> {
-    debug!("Querying database for all journal entries");
+/// A page of the journal feed: entries on `library_id`'s books that `viewer_id` can read,
+/// newest first, with their book.
+///
+/// Entries are ordered by created_at and then id, and `cursor` is the (created_at, id) of
+/// the last entry on the previous page. Private entries are only returned to their author,
+/// and entries on books restricted for the viewer are left out. Up to `limit` entries are
+/// returned.
+pub async fn get_all_journals(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    params: &JournalFeedParams,
+    viewer_id: Option<i64>,
+    cursor: Option<(&str, i64)>,
+    limit: i64,
+) -> Result<Vec<JournalFeedItem>, sqlx::Error> {
+    debug!(
+        "Querying database for journal feed with params: {:?} for viewer {:?}",
+        params, viewer_id
+    );
 
-    let journals = sqlx::query_as!(
-        JournalEntry,
-        "SELECT id, book_id, user_id, title, content, created_at, updated_at FROM journal_entries"
-    )
-    .fetch_all(pool)
-    .await?;
+    let mut query = String::from(
+        "SELECT je.id, je.book_id, je.user_id, je.title, je.content, je.visibility, je.created_at, je.updated_at,
+                b.title as book_title, b.cover_image as book_cover_image
+         FROM journal_entries je
+         INNER JOIN books b ON je.book_id = b.id
+         WHERE b.library_id = ? AND (je.visibility = 'household' OR je.user_id = ?)",
+    );
+    query.push_str(&format!(" AND je.book_id NOT IN ({HIDDEN_BOOK_IDS_SQL})"));
+    let pattern = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{q}%"));
 
-    info!("Retrieved {} journal entries from database", journals.len());
-    if journals.is_empty() {
-        debug!("No journal entries found in database");
-    } else {
-        debug!(
-            "Journal titles: {:?}",
-            journals.iter().map(|j| &j.title).collect::<Vec<_>>()
+    if params.user_id.is_some() {
+        query.push_str(" AND je.user_id = ?");
+    }
+    if params.book_id.is_some() {
+        query.push_str(" AND je.book_id = ?");
+    }
+    if params.tag_id.is_some() {
+        query.push_str(
+            " AND je.id IN (SELECT journal_entry_id FROM journal_entry_tags WHERE tag_id = ?)",
         );
     }
+    if params.from.is_some() {
+        query.push_str(" AND date(je.created_at) >= ?");
+    }
+    if params.to.is_some() {
+        query.push_str(" AND date(je.created_at) <= ?");
+    }
+    if pattern.is_some() {
+        query.push_str(" AND (je.title LIKE ? OR je.content LIKE ?)");
+    }
+    if cursor.is_some() {
+        query.push_str(" AND (je.created_at < ? OR (je.created_at = ? AND je.id < ?))");
+    }
+    query.push_str(" ORDER BY je.created_at DESC, je.id DESC LIMIT ?");
 
+    let mut query_builder = sqlx::query(&query)
+        .bind(library_id)
+        .bind(viewer_id)
+        .bind(viewer_id)
+        .bind(viewer_id);
+    if let Some(user_id) = params.user_id {
+        query_builder = query_builder.bind(user_id);
+    }
+    if let Some(book_id) = params.book_id {
+        query_builder = query_builder.bind(book_id);
+    }
+    if let Some(tag_id) = params.tag_id {
+        query_builder = query_builder.bind(tag_id);
+    }
+    if let Some(from) = &params.from {
+        query_builder = query_builder.bind(from);
+    }
+    if let Some(to) = &params.to {
+        query_builder = query_builder.bind(to);
+    }
+    if let Some(pattern) = &pattern {
+        query_builder = query_builder.bind(pattern).bind(pattern);
+    }
+    if let Some((created_at, id)) = cursor {
+        query_builder = query_builder.bind(created_at).bind(created_at).bind(id);
+    }
+
+    let rows = query_builder.bind(limit).fetch_all(pool).await?;
+
+    let mut books: HashMap<i64, JournalFeedBook> = HashMap::new();
+    let journal_rows: Vec<JournalRow> = rows
+        .iter()
+        .map(|row| {
+            books.insert(
+                row.get("id"),
+                JournalFeedBook {
+                    id: row.get("book_id"),
+                    title: row.get("book_title"),
+                    cover_image: row.get("book_cover_image"),
+                },
+            );
+            JournalRow {
+                id: row.get("id"),
+                book_id: row.get("book_id"),
+                user_id: row.get("user_id"),
+                title: row.get("title"),
+                content: row.get("content"),
+                visibility: row.get("visibility"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            }
+        })
+        .collect();
+
+    let journals: Vec<JournalFeedItem> = with_tags(pool, journal_rows)
+        .await?
+        .into_iter()
+        .filter_map(|journal| {
+            let book = books.remove(&journal.id)?;
+            Some(JournalFeedItem { journal, book })
+        })
+        .collect();
+
+    info!("Retrieved {} journal entries for the feed", journals.len());
     Ok(journals)
 }
 
+// A private entry is only found for its author, and entries on another library's books
+// aren't found at all
 pub async fn get_journal_by_id(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     id: i64,
+    viewer_id: Option<i64>,
 ) -> Result<Option<JournalEntry>, sqlx::Error> {
     debug!("Querying database for journal entry with ID: {}", id);
 
-    let journal = sqlx::query_as!(
-        JournalEntry,
-        "SELECT id, book_id, user_id, title, content, created_at, updated_at FROM journal_entries WHERE id = ?",
-        id
+    let row = sqlx::query_as!(
+        JournalRow,
+        r#"SELECT je.id as "id!", je.book_id, je.user_id, je.title, je.content, je.visibility, je.created_at, je.updated_at
+         FROM journal_entries je
+         INNER JOIN books b ON je.book_id = b.id
+         WHERE je.id = ? AND b.library_id = ? AND (je.visibility = 'household' OR je.user_id = ?)"#,
+        id,
+        library_id,
+        viewer_id
     )
     .fetch_optional(pool)
     .await?;
 
+    let journal = match row {
+        Some(row)
+            if !fetch_hidden_book_ids(pool, viewer_id)
+                .await?
+                .contains(&row.book_id) =>
+        {
+            with_tags(pool, vec![row]).await?.pop()
+        }
+        _ => None,
+    };
+
     match journal.as_ref() {
         Some(j) => {
             info!("Found journal entry with ID {}: '{}'", id, j.title);
@@ -108,23 +380,42 @@ pub async fn get_journal_by_id(
     Ok(journal)
 }
 
+// Private entries are only returned to their author, and none are returned for a book
+// restricted for the viewer
 pub async fn get_journals_by_book_id(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     book_id: i64,
+    viewer_id: Option<i64>,
 ) -> Result<Vec<JournalEntry>, sqlx::Error> {
     debug!(
         "Querying database for journal entries for book ID: {}",
         book_id
     );
 
-    let journals = sqlx::query_as!(
-        JournalEntry,
-        "SELECT id, book_id, user_id, title, content, created_at, updated_at FROM journal_entries WHERE book_id = ?",
-        book_id
+    if fetch_hidden_book_ids(pool, viewer_id)
+        .await?
+        .contains(&book_id)
+    {
+        info!("Book {} is restricted for user {:?}", book_id, viewer_id);
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query_as!(
+        JournalRow,
+        r#"SELECT je.id as "id!", je.book_id, je.user_id, je.title, je.content, je.visibility, je.created_at, je.updated_at
+         FROM journal_entries je
+         INNER JOIN books b ON je.book_id = b.id
+         WHERE je.book_id = ? AND b.library_id = ? AND (je.visibility = 'household' OR je.user_id = ?)"#,
+        book_id,
+        library_id,
+        viewer_id
     )
     .fetch_all(pool)
     .await?;
 
+    let journals = with_tags(pool, rows).await?;
+
     info!(
         "Retrieved {} journal entries for book ID {}",
         journals.len(),
@@ -143,51 +434,156 @@ pub async fn get_journals_by_book_id(
     Ok(journals)
 }
 
+/// The author of a journal entry, or None if there's no such entry
+pub async fn get_journal_owner_id_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    let journal = sqlx::query!("SELECT user_id FROM journal_entries WHERE id = ?", id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(journal.map(|journal| journal.user_id))
+}
+
+// Returns RowNotFound if the journal doesn't exist or belongs to a different book.
+// Tags are only replaced when `tag_ids` is provided. If the title or content changes,
+// the previous version is kept in journal_revisions.
 pub async fn update_journal_entry(
     pool: &Pool<Sqlite>,
+    book_id: i64,
     journal_id: i64,
     title: Option<String>,
     content: Option<String>,
+    visibility: Option<String>,
+    tag_ids: Option<&[i64]>,
 ) -> Result<JournalEntry, sqlx::Error> {
-    debug!("Updating journal entry with ID: {}", journal_id);
+    debug!(
+        "Updating journal entry with ID: {} in book: {}",
+        journal_id, book_id
+    );
+
+    let mut tx = pool.begin().await?;
 
     // Get current journal data to fill in missing fields
     let current_journal = sqlx::query_as!(
-        JournalEntry,
-        "SELECT id, book_id, user_id, title, content, created_at, updated_at FROM journal_entries WHERE id = ?",
-        journal_id
+        JournalRow,
+        "SELECT id, book_id, user_id, title, content, visibility, created_at, updated_at FROM journal_entries WHERE id = ? AND book_id = ?",
+        journal_id,
+        book_id
     )
-    .fetch_one(pool)
-    .await?;
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    let final_title = title.unwrap_or_else(|| current_journal.title.clone());
+    let final_content = content.unwrap_or_else(|| current_journal.content.clone());
+    let final_visibility = visibility.unwrap_or(current_journal.visibility);
 
-    let final_title = title.unwrap_or(current_journal.title);
-    let final_content = content.unwrap_or(current_journal.content);
+    // Keep the version being replaced so the update can be undone
+    if final_title != current_journal.title || final_content != current_journal.content {
+        sqlx::query!(
+            "INSERT INTO journal_revisions (journal_entry_id, title, content, saved_at) VALUES (?, ?, ?, ?)",
+            journal_id,
+            current_journal.title,
+            current_journal.content,
+            current_journal.updated_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        debug!("Saved revision of journal entry {}", journal_id);
+    }
 
     let row = sqlx::query!(
-        "UPDATE journal_entries SET title = ?, content = ?, updated_at = datetime('now') WHERE id = ? 
-         RETURNING id, book_id, user_id, title, content, created_at, updated_at",
+        "UPDATE journal_entries SET title = ?, content = ?, visibility = ?, updated_at = datetime('now') WHERE id = ?
+         RETURNING id, book_id, user_id, title, content, visibility, created_at, updated_at",
         final_title,
         final_content,
+        final_visibility,
         journal_id
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    let updated_journal = JournalEntry {
+    if let Some(tag_ids) = tag_ids {
+        debug!(
+            "Updating tags for journal {} with {} tags",
+            journal_id,
+            tag_ids.len()
+        );
+        replace_journal_tags(&mut tx, journal_id, tag_ids).await?;
+    }
+
+    tx.commit().await?;
+
+    let updated_row = JournalRow {
         id: row.id,
         book_id: row.book_id,
         user_id: row.user_id,
         title: row.title,
         content: row.content,
+        visibility: row.visibility,
         created_at: row.created_at,
         updated_at: row.updated_at,
     };
+    let updated_journal = with_tags(pool, vec![updated_row]).await?.remove(0);
 
     info!(
-        "Updated journal entry with ID {}: title='{}', content length={}",
+        "Updated journal entry with ID {}: title='{}', content length={}, tags={}",
         updated_journal.id,
         updated_journal.title,
-        updated_journal.content.len()
+        updated_journal.content.len(),
+        updated_journal.tags.len()
     );
     Ok(updated_journal)
 }
+
+// Returns RowNotFound if the journal doesn't exist or belongs to a different book
+pub async fn delete_journal_entry(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Deleting journal entry with ID: {} in book: {}",
+        journal_id, book_id
+    );
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM journal_entry_tags WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE id = ? AND book_id = ?)",
+        journal_id,
+        book_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM journal_entries WHERE id = ? AND book_id = ?",
+        journal_id,
+        book_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    // Update the associated book's updated_at timestamp
+    sqlx::query!(
+        "UPDATE books SET updated_at = datetime('now') WHERE id = ?",
+        book_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!(
+        "Deleted journal entry with ID {} from book {}",
+        journal_id, book_id
+    );
+    Ok(())
+}
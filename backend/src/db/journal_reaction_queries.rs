@@ -0,0 +1,130 @@
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::models::journal_reactions::JournalReactionSummary;
+
+// Helper function to batch load reaction summaries for several journal entries, keyed by
+// journal id. Emoji are in the order they were first used on each entry.
+pub(crate) async fn fetch_reactions_for_journals(
+    pool: &Pool<Sqlite>,
+    journal_ids: &[i64],
+) -> Result<HashMap<i64, Vec<JournalReactionSummary>>, sqlx::Error> {
+    if journal_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = journal_ids
+        .iter()
+        .map(|_| "?")
+        .collect::<Vec<_>>()
+        .join(",");
+    let query = format!(
+        "SELECT journal_entry_id, emoji, user_id
+         FROM journal_reactions
+         WHERE journal_entry_id IN ({placeholders})
+         ORDER BY id"
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for &journal_id in journal_ids {
+        query_builder = query_builder.bind(journal_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+
+    let mut reactions_map: HashMap<i64, Vec<JournalReactionSummary>> = HashMap::new();
+    for row in rows {
+        let emoji: String = row.get("emoji");
+        let summaries = reactions_map
+            .entry(row.get("journal_entry_id"))
+            .or_default();
+
+        let index = match summaries.iter().position(|s| s.emoji == emoji) {
+            Some(index) => index,
+            None => {
+                summaries.push(JournalReactionSummary {
+                    emoji,
+                    count: 0,
+                    user_ids: Vec::new(),
+                });
+                summaries.len() - 1
+            }
+        };
+        summaries[index].count += 1;
+        summaries[index].user_ids.push(row.get("user_id"));
+    }
+
+    Ok(reactions_map)
+}
+
+pub async fn get_journal_reactions_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+) -> Result<Vec<JournalReactionSummary>, sqlx::Error> {
+    debug!("Querying database for reactions on journal {}", journal_id);
+
+    let mut reactions_map = fetch_reactions_for_journals(pool, &[journal_id]).await?;
+    Ok(reactions_map.remove(&journal_id).unwrap_or_default())
+}
+
+// Reacting twice with the same emoji is a no-op
+pub async fn add_journal_reaction_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    user_id: i64,
+    emoji: &str,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Adding reaction {} to journal {} by user {}",
+        emoji, journal_id, user_id
+    );
+
+    sqlx::query!(
+        "INSERT INTO journal_reactions (journal_entry_id, user_id, emoji) VALUES (?, ?, ?)
+         ON CONFLICT (journal_entry_id, user_id, emoji) DO NOTHING",
+        journal_id,
+        user_id,
+        emoji
+    )
+    .execute(pool)
+    .await?;
+
+    info!(
+        "User {} reacted {} to journal {}",
+        user_id, emoji, journal_id
+    );
+    Ok(())
+}
+
+// Returns RowNotFound if the user hadn't reacted with this emoji
+pub async fn remove_journal_reaction_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    user_id: i64,
+    emoji: &str,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Removing reaction {} from journal {} by user {}",
+        emoji, journal_id, user_id
+    );
+
+    let result = sqlx::query!(
+        "DELETE FROM journal_reactions WHERE journal_entry_id = ? AND user_id = ? AND emoji = ?",
+        journal_id,
+        user_id,
+        emoji
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!(
+        "User {} removed reaction {} from journal {}",
+        user_id, emoji, journal_id
+    );
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::models::journal_revisions::JournalRevision;
+
+/// Earlier versions of a journal entry in a book, newest first
+pub async fn get_journal_revisions_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<Vec<JournalRevision>, sqlx::Error> {
+    debug!(
+        "Querying database for revisions of journal {} in book {}",
+        journal_id, book_id
+    );
+
+    let revisions = sqlx::query_as!(
+        JournalRevision,
+        r#"SELECT r.id as "id!", r.journal_entry_id, r.title, r.content, r.saved_at, r.replaced_at
+         FROM journal_revisions r
+         INNER JOIN journal_entries j ON r.journal_entry_id = j.id
+         INNER JOIN books b ON j.book_id = b.id
+         WHERE r.journal_entry_id = ? AND j.book_id = ? AND b.library_id = ?
+         ORDER BY r.id DESC"#,
+        journal_id,
+        book_id,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    info!(
+        "Found {} revisions of journal {}",
+        revisions.len(),
+        journal_id
+    );
+    Ok(revisions)
+}
+
+pub async fn get_journal_revision_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: i64,
+    journal_id: i64,
+    revision_id: i64,
+) -> Result<Option<JournalRevision>, sqlx::Error> {
+    debug!(
+        "Querying database for revision {} of journal {} in book {}",
+        revision_id, journal_id, book_id
+    );
+
+    sqlx::query_as!(
+        JournalRevision,
+        r#"SELECT r.id as "id!", r.journal_entry_id, r.title, r.content, r.saved_at, r.replaced_at
+         FROM journal_revisions r
+         INNER JOIN journal_entries j ON r.journal_entry_id = j.id
+         INNER JOIN books b ON j.book_id = b.id
+         WHERE r.id = ? AND r.journal_entry_id = ? AND j.book_id = ? AND b.library_id = ?"#,
+        revision_id,
+        journal_id,
+        book_id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await
+}
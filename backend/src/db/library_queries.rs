@@ -0,0 +1,157 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::models::libraries::Library;
+
+pub async fn get_libraries_for_user_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Vec<Library>, sqlx::Error> {
+    debug!("Querying database for libraries of user {}", user_id);
+
+    sqlx::query_as!(
+        Library,
+        r#"SELECT l.id as "id!", l.name, l.created_at
+           FROM libraries l
+           INNER JOIN library_members lm ON lm.library_id = l.id
+           WHERE lm.user_id = ?
+           ORDER BY l.id"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// The library used when a request doesn't pick one: the first one the user joined
+pub async fn get_default_library_id_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT MIN(library_id) as "library_id: i64" FROM library_members WHERE user_id = ?"#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn is_library_member_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let member = sqlx::query!(
+        "SELECT user_id FROM library_members WHERE library_id = ? AND user_id = ?",
+        library_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(member.is_some())
+}
+
+// The user who creates a library is its first member
+pub async fn create_library_query(
+    pool: &Pool<Sqlite>,
+    name: &str,
+    user_id: i64,
+) -> Result<Library, sqlx::Error> {
+    debug!("Creating library '{}' for user {}", name, user_id);
+
+    let mut tx = pool.begin().await?;
+
+    let library = sqlx::query_as!(
+        Library,
+        r#"INSERT INTO libraries (name) VALUES (?) RETURNING id as "id!", name, created_at"#,
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO library_members (library_id, user_id) VALUES (?, ?)",
+        library.id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!("Created library {} '{}'", library.id, library.name);
+    Ok(library)
+}
+
+// Adding someone who is already a member does nothing
+pub async fn add_library_member_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT OR IGNORE INTO library_members (library_id, user_id) VALUES (?, ?)",
+        library_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    info!("Added user {} to library {}", user_id, library_id);
+    Ok(())
+}
+
+// Returns RowNotFound if the user isn't a member
+pub async fn remove_library_member_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM library_members WHERE library_id = ? AND user_id = ?",
+        library_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Removed user {} from library {}", user_id, library_id);
+    Ok(())
+}
+
+/// Whether the book is in the library. False for books that don't exist.
+pub async fn book_in_library_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    library_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let book = sqlx::query!(
+        r#"SELECT id as "id!" FROM books WHERE id = ? AND library_id = ?"#,
+        book_id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(book.is_some())
+}
+
+/// Whether the journal entry is on a book in the library
+pub async fn journal_in_library_query(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+    library_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let journal = sqlx::query!(
+        r#"SELECT je.id as "id!" FROM journal_entries je
+           INNER JOIN books b ON je.book_id = b.id
+           WHERE je.id = ? AND b.library_id = ?"#,
+        journal_id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(journal.is_some())
+}
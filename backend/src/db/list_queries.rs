@@ -1,11 +1,19 @@
 use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+
+use crate::db::book_queries::fetch_hidden_book_ids;
 
 use crate::models::lists::{
-    BookInList, CreateListRequest, List, ListUser, ListWithBooks, UpdateListRequest,
+    BookInList, CreateListRequest, List, ListAccess, ListUser, ListWithBooks, UpdateListRequest,
 };
 
-// Get all lists with their books
-pub async fn get_all_lists_query(pool: &Pool<Sqlite>) -> Result<Vec<ListWithBooks>, sqlx::Error> {
+// Get the lists in a library the viewer can see, with their books, leaving out books
+// restricted for the viewer. Without a viewer only household lists are included.
+pub async fn get_all_lists_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    viewer_id: Option<i64>,
+) -> Result<Vec<ListWithBooks>, sqlx::Error> {
     // First, get all lists
     let lists = sqlx::query_as!(
         List,
@@ -15,52 +23,85 @@ pub async fn get_all_lists_query(pool: &Pool<Sqlite>) -> Result<Vec<ListWithBook
             user_id as "user_id!",
             type_id as "type_id!",
             name,
+            visibility,
             created_at,
             updated_at
         FROM lists
+        WHERE library_id = ?
+          AND (visibility = 'household' OR user_id = ?
+               OR EXISTS (SELECT 1 FROM list_collaborators lc
+                          WHERE lc.list_id = lists.id AND lc.user_id = ?))
         ORDER BY created_at DESC
-        "#
+        "#,
+        library_id,
+        viewer_id,
+        viewer_id
     )
     .fetch_all(pool)
     .await?;
 
-    // For each list, get the books (we'll get books for all users since we're not filtering by user)
+    let hidden_book_ids = fetch_hidden_book_ids(pool, viewer_id).await?;
+
     let mut lists_with_books = Vec::new();
     for list in lists {
-        let books = get_books_for_list(pool, list.id, list.user_id).await?;
-
-        // Get user info
-        let user = sqlx::query_as!(
-            ListUser,
-            r#"
-            SELECT id as "id!", name, color, avatar_image
-            FROM users
-            WHERE id = ?
-            "#,
-            list.user_id
-        )
-        .fetch_one(pool)
-        .await?;
-
-        lists_with_books.push(ListWithBooks {
-            id: list.id,
-            user_id: list.user_id,
-            type_id: list.type_id,
-            name: list.name,
-            books,
-            user,
-        });
+        lists_with_books.push(list_with_books(pool, list, &hidden_book_ids).await?);
     }
 
     Ok(lists_with_books)
 }
 
-// Get a single list by ID with its books
+/// What `user_id` may do with a list in the library, or None if they can't see it
+/// (or there's no such list)
+pub async fn get_list_access_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    list_id: i64,
+    user_id: i64,
+) -> Result<Option<ListAccess>, sqlx::Error> {
+    let list = sqlx::query!(
+        r#"
+        SELECT
+            l.user_id,
+            l.visibility,
+            EXISTS (SELECT 1 FROM list_collaborators lc
+                    WHERE lc.list_id = l.id AND lc.user_id = ?) as "is_collaborator!: bool"
+        FROM lists l
+        WHERE l.id = ? AND l.library_id = ?
+        "#,
+        user_id,
+        list_id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(list.and_then(|list| {
+        if list.user_id == user_id {
+            Some(ListAccess::Own)
+        } else if list.is_collaborator {
+            Some(ListAccess::Edit)
+        } else if list.visibility == "household" {
+            Some(ListAccess::View)
+        } else {
+            None
+        }
+    }))
+}
+
+// Get a single list by ID with its books, if `user_id` can see it
 pub async fn get_list_by_id_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     list_id: i64,
     user_id: i64,
 ) -> Result<Option<ListWithBooks>, sqlx::Error> {
+    if get_list_access_query(pool, library_id, list_id, user_id)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+
     let list = sqlx::query_as!(
         List,
         r#"
@@ -69,52 +110,74 @@ pub async fn get_list_by_id_query(
             user_id as "user_id!",
             type_id as "type_id!",
             name,
+            visibility,
             created_at,
             updated_at
         FROM lists
-        WHERE id = ? AND user_id = ?
+        WHERE id = ?
         "#,
-        list_id,
-        user_id
+        list_id
     )
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await?;
 
-    match list {
-        Some(list) => {
-            let books = get_books_for_list(pool, list.id, list.user_id).await?;
-
-            // Get user info
-            let user = sqlx::query_as!(
-                ListUser,
-                r#"
-                SELECT id as "id!", name, color, avatar_image
-                FROM users
-                WHERE id = ?
-                "#,
-                list.user_id
-            )
-            .fetch_one(pool)
-            .await?;
+    let hidden_book_ids = fetch_hidden_book_ids(pool, Some(user_id)).await?;
+    Ok(Some(list_with_books(pool, list, &hidden_book_ids).await?))
+}
 
-            Ok(Some(ListWithBooks {
-                id: list.id,
-                user_id: list.user_id,
-                type_id: list.type_id,
-                name: list.name,
-                books,
-                user,
-            }))
-        }
-        None => Ok(None),
-    }
+// Helper function to add a list's books, owner and collaborators
+async fn list_with_books(
+    pool: &Pool<Sqlite>,
+    list: List,
+    hidden_book_ids: &HashSet<i64>,
+) -> Result<ListWithBooks, sqlx::Error> {
+    let books = get_books_for_list(pool, list.id, list.user_id, hidden_book_ids).await?;
+
+    // Get user info
+    let user = sqlx::query_as!(
+        ListUser,
+        r#"
+        SELECT id as "id!", name, color, avatar_image
+        FROM users
+        WHERE id = ?
+        "#,
+        list.user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let collaborators = sqlx::query_as!(
+        ListUser,
+        r#"
+        SELECT u.id as "id!", u.name, u.color, u.avatar_image
+        FROM list_collaborators lc
+        INNER JOIN users u ON lc.user_id = u.id
+        WHERE lc.list_id = ?
+        ORDER BY lc.created_at, u.id
+        "#,
+        list.id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ListWithBooks {
+        id: list.id,
+        user_id: list.user_id,
+        type_id: list.type_id,
+        name: list.name,
+        visibility: list.visibility,
+        books,
+        user,
+        collaborators,
+    })
 }
 
-// Helper function to get books for a specific list
+// Helper function to get books for a specific list, minus any in `hidden_book_ids`
 async fn get_books_for_list(
     pool: &Pool<Sqlite>,
     list_id: i64,
     user_id: i64,
+    hidden_book_ids: &HashSet<i64>,
 ) -> Result<Vec<BookInList>, sqlx::Error> {
     let books = sqlx::query_as!(
         BookInList,
@@ -122,7 +185,9 @@ async fn get_books_for_list(
         SELECT 
             b.id as "id!",
             b.cover_image,
-            s.name as status_name
+            s.name as status_name,
+            s.color as status_color,
+            lb.added_by
         FROM list_books lb
         INNER JOIN books b ON lb.book_id = b.id
         LEFT JOIN reading_status rs ON rs.book_id = b.id AND rs.user_id = ?
@@ -136,12 +201,16 @@ async fn get_books_for_list(
     .fetch_all(pool)
     .await?;
 
-    Ok(books)
+    Ok(books
+        .into_iter()
+        .filter(|book| !hidden_book_ids.contains(&book.id))
+        .collect())
 }
 
-// Create a new list
+// Create a new list. Books from other libraries are left out.
 pub async fn create_list_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     user_id: i64,
     request: CreateListRequest,
 ) -> Result<ListWithBooks, sqlx::Error> {
@@ -152,19 +221,22 @@ pub async fn create_list_query(
     let list = sqlx::query_as!(
         List,
         r#"
-        INSERT INTO lists (user_id, type_id, name)
-        VALUES (?, ?, ?)
+        INSERT INTO lists (user_id, library_id, type_id, name, visibility)
+        VALUES (?, ?, ?, ?, COALESCE(?, 'household'))
         RETURNING 
             id as "id!",
             user_id as "user_id!",
             type_id as "type_id!",
             name,
+            visibility,
             created_at,
             updated_at
         "#,
         user_id,
+        library_id,
         request.type_id,
-        request.name
+        request.name,
+        request.visibility
     )
     .fetch_one(&mut *tx)
     .await?;
@@ -173,10 +245,13 @@ pub async fn create_list_query(
     for (position, book_id) in request.books.iter().enumerate() {
         let pos = position as i64;
         sqlx::query!(
-            "INSERT INTO list_books (list_id, book_id, position) VALUES (?, ?, ?)",
+            "INSERT INTO list_books (list_id, book_id, position, added_by)
+             SELECT ?, id, ?, ? FROM books WHERE id = ? AND library_id = ?",
             list.id,
+            pos,
+            user_id,
             book_id,
-            pos
+            library_id
         )
         .execute(&mut *tx)
         .await?;
@@ -186,27 +261,32 @@ pub async fn create_list_query(
     tx.commit().await?;
 
     // Fetch the complete list with books
-    match get_list_by_id_query(pool, list.id, user_id).await? {
+    match get_list_by_id_query(pool, library_id, list.id, user_id).await? {
         Some(list_with_books) => Ok(list_with_books),
         None => Err(sqlx::Error::RowNotFound),
     }
 }
 
-// Update an existing list
+// Update an existing list as `user_id`, who the caller has checked can edit it. Books
+// from other libraries are left out, and books already on the list keep who added them.
+// Books hidden from `user_id` can't be in their request, so they stay where they were.
 pub async fn update_list_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     list_id: i64,
     user_id: i64,
     request: UpdateListRequest,
 ) -> Result<ListWithBooks, sqlx::Error> {
+    let hidden_book_ids = fetch_hidden_book_ids(pool, Some(user_id)).await?;
+
     // Start a transaction
     let mut tx = pool.begin().await?;
 
-    // First verify the list exists and belongs to the user
+    // First verify the list exists
     let existing_list = sqlx::query!(
-        "SELECT id FROM lists WHERE id = ? AND user_id = ?",
+        "SELECT id FROM lists WHERE id = ? AND library_id = ?",
         list_id,
-        user_id
+        library_id
     )
     .fetch_optional(&mut *tx)
     .await?;
@@ -246,8 +326,52 @@ pub async fn update_list_query(
         }
     }
 
+    if let Some(visibility) = &request.visibility {
+        sqlx::query!(
+            "UPDATE lists SET visibility = ?, updated_at = datetime('now') WHERE id = ?",
+            visibility,
+            list_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
     // Update books if provided
     if let Some(book_ids) = request.books {
+        let existing_books = sqlx::query!(
+            "SELECT book_id, added_by FROM list_books WHERE list_id = ? ORDER BY position",
+            list_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let added_by: HashMap<i64, Option<i64>> = existing_books
+            .iter()
+            .map(|row| (row.book_id, row.added_by))
+            .collect();
+
+        // Hidden books keep their place in the list and the requested books fill the rest
+        let mut kept: Vec<(usize, i64)> = existing_books
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| hidden_book_ids.contains(&row.book_id))
+            .map(|(index, row)| (index, row.book_id))
+            .collect();
+        kept.reverse();
+        let mut requested = book_ids
+            .into_iter()
+            .filter(|book_id| !hidden_book_ids.contains(book_id));
+        let mut book_ids = Vec::new();
+        loop {
+            let next = match kept.last() {
+                Some((index, _)) if *index <= book_ids.len() => kept.pop().map(|(_, id)| id),
+                _ => requested.next().or_else(|| kept.pop().map(|(_, id)| id)),
+            };
+            match next {
+                Some(book_id) => book_ids.push(book_id),
+                None => break,
+            }
+        }
+
         // Delete existing books
         sqlx::query!("DELETE FROM list_books WHERE list_id = ?", list_id)
             .execute(&mut *tx)
@@ -256,11 +380,15 @@ pub async fn update_list_query(
         // Insert new books with their positions
         for (position, book_id) in book_ids.iter().enumerate() {
             let pos = position as i64;
+            let book_added_by = added_by.get(book_id).copied().unwrap_or(Some(user_id));
             sqlx::query!(
-                "INSERT INTO list_books (list_id, book_id, position) VALUES (?, ?, ?)",
+                "INSERT INTO list_books (list_id, book_id, position, added_by)
+                 SELECT ?, id, ?, ? FROM books WHERE id = ? AND library_id = ?",
                 list_id,
+                pos,
+                book_added_by,
                 book_id,
-                pos
+                library_id
             )
             .execute(&mut *tx)
             .await?;
@@ -271,20 +399,60 @@ pub async fn update_list_query(
     tx.commit().await?;
 
     // Fetch and return the updated list with books
-    match get_list_by_id_query(pool, list_id, user_id).await? {
+    match get_list_by_id_query(pool, library_id, list_id, user_id).await? {
         Some(list_with_books) => Ok(list_with_books),
         None => Err(sqlx::Error::RowNotFound),
     }
 }
 
-// Delete a list
+// Delete a list, which only its owner can do
 pub async fn delete_list_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    list_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM lists WHERE id = ? AND user_id = ? AND library_id = ?",
+        list_id,
+        user_id,
+        library_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+// Let a library member edit a list. Adding an existing collaborator again does nothing.
+pub async fn add_list_collaborator_query(
+    pool: &Pool<Sqlite>,
+    list_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT OR IGNORE INTO list_collaborators (list_id, user_id) VALUES (?, ?)",
+        list_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Returns RowNotFound if the user isn't a collaborator on the list
+pub async fn remove_list_collaborator_query(
     pool: &Pool<Sqlite>,
     list_id: i64,
     user_id: i64,
 ) -> Result<(), sqlx::Error> {
     let result = sqlx::query!(
-        "DELETE FROM lists WHERE id = ? AND user_id = ?",
+        "DELETE FROM list_collaborators WHERE list_id = ? AND user_id = ?",
         list_id,
         user_id
     )
@@ -1,17 +1,53 @@
+pub mod api_token_queries;
+pub mod avatar_queries;
 pub mod book_queries;
+pub mod compatibility_queries;
 pub mod genre_queries;
+pub mod journal_attachment_queries;
+pub mod journal_comment_queries;
+pub mod journal_export_queries;
 pub mod journal_queries;
+pub mod journal_reaction_queries;
+pub mod journal_revision_queries;
+pub mod library_queries;
 pub mod list_queries;
+pub mod obsidian_queries;
 pub mod pool;
+pub mod preference_queries;
+pub mod quote_queries;
+pub mod rating_dimension_queries;
 pub mod rating_queries;
 pub mod reading_status_queries;
+pub mod recommendation_queries;
+pub mod review_queries;
+pub mod share_link_queries;
+pub mod status_queries;
 pub mod tag_queries;
 pub mod user_queries;
+pub mod year_in_review_queries;
 
 // Re-export commonly used functions
+pub use api_token_queries::*;
+pub use avatar_queries::*;
+pub use compatibility_queries::*;
 pub use genre_queries::*;
+pub use journal_attachment_queries::*;
+pub use journal_comment_queries::*;
+pub use journal_export_queries::*;
 pub use journal_queries::*;
+pub use journal_reaction_queries::*;
+pub use journal_revision_queries::*;
+pub use library_queries::*;
 pub use list_queries::*;
+pub use obsidian_queries::*;
 pub use pool::init_db;
+pub use preference_queries::*;
+pub use quote_queries::*;
+pub use rating_dimension_queries::*;
+pub use recommendation_queries::*;
+pub use review_queries::*;
+pub use share_link_queries::*;
+pub use status_queries::*;
 pub use tag_queries::*;
 pub use user_queries::*;
+pub use year_in_review_queries::*;
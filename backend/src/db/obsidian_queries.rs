@@ -0,0 +1,142 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::db::book_in_library_query;
+use crate::db::journal_queries::{create_journal_entry, get_journal_by_id, update_journal_entry};
+use crate::models::JournalEntry;
+use crate::models::obsidian::{ObsidianImportNote, ObsidianImportSummary};
+use crate::utils::obsidian::{
+    ObsidianNote, ObsidianSection, journal_html_to_note_markdown, note_markdown_to_journal_html,
+};
+
+enum SectionOutcome {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+async fn import_section(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    book_id: i64,
+    section: &ObsidianSection,
+) -> Result<SectionOutcome, sqlx::Error> {
+    // Only the user's own entries on this book are updated. A marker for anything
+    // else (another book's entry, a deleted one) is treated as a new entry.
+    let existing = match section.journal_id {
+        Some(journal_id) => get_journal_by_id(pool, library_id, journal_id, Some(user_id))
+            .await?
+            .filter(|journal| journal.user_id == user_id && journal.book_id == book_id),
+        None => None,
+    };
+
+    match existing {
+        Some(journal) => {
+            // Compare as Markdown, since converting back to HTML needn't give the same markup
+            if journal.title == section.title
+                && journal_html_to_note_markdown(&journal.content) == section.markdown
+            {
+                return Ok(SectionOutcome::Unchanged);
+            }
+
+            update_journal_entry(
+                pool,
+                book_id,
+                journal.id,
+                Some(section.title.clone()),
+                Some(note_markdown_to_journal_html(&section.markdown)),
+                None,
+                None,
+            )
+            .await?;
+            Ok(SectionOutcome::Updated)
+        }
+        None => {
+            let journal = JournalEntry {
+                id: 0,
+                book_id,
+                user_id,
+                title: section.title.clone(),
+                content: note_markdown_to_journal_html(&section.markdown),
+                visibility: "household".to_string(),
+                created_at: None,
+                updated_at: None,
+                tags: Vec::new(),
+            };
+            create_journal_entry(pool, journal, &[]).await?;
+            Ok(SectionOutcome::Created)
+        }
+    }
+}
+
+// Bring the journal sections of Obsidian notes into the library as `user_id`'s entries.
+// Notes are matched to books by the home_library_book_id in their front matter; sections
+// with a journal marker update that entry and the others become new entries. Notes for
+// books outside `library_id` are skipped.
+pub async fn import_obsidian_notes_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    notes: &[ObsidianNote],
+) -> Result<ObsidianImportSummary, sqlx::Error> {
+    debug!(
+        "Importing {} Obsidian notes for user {}",
+        notes.len(),
+        user_id
+    );
+
+    let mut summary = ObsidianImportSummary {
+        notes_imported: 0,
+        notes_skipped: 0,
+        journals_created: 0,
+        journals_updated: 0,
+        journals_unchanged: 0,
+        notes: Vec::new(),
+    };
+
+    for note in notes {
+        let mut result = ObsidianImportNote {
+            file_name: note.file_name.clone(),
+            book_id: note.book_id,
+            status: "skipped".to_string(),
+            reason: None,
+            journals_created: 0,
+            journals_updated: 0,
+            journals_unchanged: 0,
+        };
+
+        match note.book_id {
+            None => result.reason = Some("no home_library_book_id in front matter".to_string()),
+            Some(book_id) if !book_in_library_query(pool, book_id, library_id).await? => {
+                result.reason = Some(format!("no book with home_library_book_id {book_id}"));
+            }
+            Some(book_id) => {
+                for section in &note.sections {
+                    match import_section(pool, library_id, user_id, book_id, section).await? {
+                        SectionOutcome::Created => result.journals_created += 1,
+                        SectionOutcome::Updated => result.journals_updated += 1,
+                        SectionOutcome::Unchanged => result.journals_unchanged += 1,
+                    }
+                }
+                result.status = "imported".to_string();
+            }
+        }
+
+        if result.reason.is_some() {
+            summary.notes_skipped += 1;
+        } else {
+            summary.notes_imported += 1;
+        }
+        summary.journals_created += result.journals_created;
+        summary.journals_updated += result.journals_updated;
+        summary.journals_unchanged += result.journals_unchanged;
+        summary.notes.push(result);
+    }
+
+    info!(
+        "Imported {} Obsidian notes for user {}: {} journals created, {} updated",
+        summary.notes_imported, user_id, summary.journals_created, summary.journals_updated
+    );
+    Ok(summary)
+}
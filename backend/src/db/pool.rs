@@ -2,6 +2,10 @@ use sqlx::{Pool, Sqlite};
 use std::env;
 use tracing::{debug, info};
 
+use crate::db::avatar_queries::migrate_inline_avatars;
+use crate::db::journal_attachment_queries::extract_inline_journal_images;
+use crate::utils::FileStorage;
+
 pub async fn init_db() -> Pool<Sqlite> {
     debug!("Initializing SQLite database connection");
 
@@ -27,5 +31,24 @@ pub async fn init_db() -> Pool<Sqlite> {
         .expect("Failed to run migrations");
     info!("Database migrations completed successfully");
 
+    debug!("Moving inline journal images into attachments");
+    let storage = FileStorage::for_pool(&pool)
+        .await
+        .expect("Failed to locate uploads directory");
+    let extracted = extract_inline_journal_images(&pool, &storage)
+        .await
+        .expect("Failed to extract inline journal images");
+    if extracted > 0 {
+        info!("Moved {} inline journal images into attachments", extracted);
+    }
+
+    debug!("Moving inline avatars into storage");
+    let migrated = migrate_inline_avatars(&pool, &storage)
+        .await
+        .expect("Failed to move inline avatars");
+    if migrated > 0 {
+        info!("Moved {} inline avatars into storage", migrated);
+    }
+
     pool
 }
@@ -0,0 +1,81 @@
+use serde_json::{Map, Value};
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info, warn};
+
+use crate::models::preferences::UserPreferences;
+
+/// A user's preferences, with defaults for keys they haven't set. Stored values that no
+/// longer fit the schema are ignored.
+pub async fn get_user_preferences_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<UserPreferences, sqlx::Error> {
+    debug!("Querying database for preferences of user {}", user_id);
+
+    let rows = sqlx::query!(
+        "SELECT key, value FROM user_preferences WHERE user_id = ?",
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut preferences = UserPreferences::default();
+    for row in rows {
+        let applied = serde_json::from_str::<Value>(&row.value)
+            .map_err(|e| e.to_string())
+            .and_then(|value| preferences.set(&row.key, &value));
+        if let Err(e) = applied {
+            warn!(
+                "Ignoring stored preference {} of user {}: {}",
+                row.key, user_id, e
+            );
+        }
+    }
+
+    Ok(preferences)
+}
+
+/// Save the given preferences, leaving the others as they are. A null value resets the
+/// key to its default. The values must already have been validated.
+pub async fn update_user_preferences_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    changes: &Map<String, Value>,
+) -> Result<UserPreferences, sqlx::Error> {
+    debug!(
+        "Updating preferences {:?} of user {}",
+        changes.keys().collect::<Vec<_>>(),
+        user_id
+    );
+
+    let mut tx = pool.begin().await?;
+
+    for (key, value) in changes {
+        if value.is_null() {
+            sqlx::query!(
+                "DELETE FROM user_preferences WHERE user_id = ? AND key = ?",
+                user_id,
+                key
+            )
+            .execute(&mut *tx)
+            .await?;
+            continue;
+        }
+
+        let value = value.to_string();
+        sqlx::query!(
+            "INSERT INTO user_preferences (user_id, key, value) VALUES (?, ?, ?)
+             ON CONFLICT (user_id, key) DO UPDATE SET value = excluded.value, updated_at = datetime('now')",
+            user_id,
+            key,
+            value
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    info!("Updated {} preferences of user {}", changes.len(), user_id);
+    get_user_preferences_query(pool, user_id).await
+}
@@ -0,0 +1,613 @@
+use rand::Rng;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashSet;
+use tracing::{debug, info};
+
+use crate::db::book_queries::HIDDEN_BOOK_IDS_SQL;
+use crate::models::quotes::{
+    CreateQuoteRequest, KindleImportBook, KindleImportSummary, Quote, QuoteBook, QuoteSearchParams,
+    QuoteUser, UpdateQuoteRequest,
+};
+use crate::utils::kindle::{
+    Clipping, ClippingKind, display_author, normalize_author, normalize_title,
+};
+
+// Quotes joined with who saved them and the book they're from
+const QUOTE_SELECT: &str = "
+    SELECT q.id, q.book_id, q.user_id, q.text, q.page, q.location, q.chapter, q.note, q.source, q.clipped_at,
+           q.created_at, q.updated_at,
+           u.name as user_name, u.color as user_color, b.title as book_title, b.author as book_author
+    FROM quotes q
+    INNER JOIN users u ON q.user_id = u.id
+    INNER JOIN books b ON q.book_id = b.id";
+
+fn quote_from_row(row: &SqliteRow) -> Quote {
+    Quote {
+        id: row.get("id"),
+        book_id: row.get("book_id"),
+        user_id: row.get("user_id"),
+        text: row.get("text"),
+        page: row.get("page"),
+        location: row.get("location"),
+        chapter: row.get("chapter"),
+        note: row.get("note"),
+        source: row.get("source"),
+        clipped_at: row.get("clipped_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        user: QuoteUser {
+            id: row.get("user_id"),
+            name: row.get("user_name"),
+            color: row.get("user_color"),
+        },
+        book: QuoteBook {
+            id: row.get("book_id"),
+            title: row.get("book_title"),
+            author: row.get("book_author"),
+        },
+    }
+}
+
+// None are returned for a book restricted for the viewer
+pub async fn get_quotes_by_book_id_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    book_id: i64,
+    viewer_id: Option<i64>,
+) -> Result<Vec<Quote>, sqlx::Error> {
+    debug!("Querying database for quotes from book {}", book_id);
+
+    // Quotes without a page sort after those with one
+    let query = format!(
+        "{QUOTE_SELECT} WHERE q.book_id = ? AND b.library_id = ?
+           AND q.book_id NOT IN ({HIDDEN_BOOK_IDS_SQL})
+         ORDER BY q.page IS NULL, q.page, q.created_at"
+    );
+    let rows = sqlx::query(&query)
+        .bind(book_id)
+        .bind(library_id)
+        .bind(viewer_id)
+        .bind(viewer_id)
+        .fetch_all(pool)
+        .await?;
+
+    let quotes: Vec<Quote> = rows.iter().map(quote_from_row).collect();
+    info!("Found {} quotes from book {}", quotes.len(), book_id);
+    Ok(quotes)
+}
+
+pub async fn get_quote_by_id_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    quote_id: i64,
+) -> Result<Option<Quote>, sqlx::Error> {
+    debug!(
+        "Querying database for quote {} from book {}",
+        quote_id, book_id
+    );
+
+    let query = format!("{QUOTE_SELECT} WHERE q.id = ? AND q.book_id = ?");
+    let row = sqlx::query(&query)
+        .bind(quote_id)
+        .bind(book_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.as_ref().map(quote_from_row))
+}
+
+/// Search quotes across every book in the library the viewer can see, newest first.
+///
+/// `q` matches the quote text, note, chapter, and the book's title and author.
+pub async fn search_quotes_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    params: &QuoteSearchParams,
+    viewer_id: Option<i64>,
+) -> Result<Vec<Quote>, sqlx::Error> {
+    debug!("Searching quotes with params: {:?}", params);
+
+    let mut query = format!(
+        "{QUOTE_SELECT} WHERE b.library_id = ? AND q.book_id NOT IN ({HIDDEN_BOOK_IDS_SQL})"
+    );
+    let pattern = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{q}%"));
+
+    if pattern.is_some() {
+        query.push_str(
+            " AND (q.text LIKE ? OR q.note LIKE ? OR q.chapter LIKE ? OR b.title LIKE ? OR b.author LIKE ?)",
+        );
+    }
+    if params.user_id.is_some() {
+        query.push_str(" AND q.user_id = ?");
+    }
+    if params.book_id.is_some() {
+        query.push_str(" AND q.book_id = ?");
+    }
+    query.push_str(" ORDER BY q.created_at DESC, q.id DESC");
+
+    let mut query_builder = sqlx::query(&query)
+        .bind(library_id)
+        .bind(viewer_id)
+        .bind(viewer_id);
+    if let Some(pattern) = &pattern {
+        for _ in 0..5 {
+            query_builder = query_builder.bind(pattern);
+        }
+    }
+    if let Some(user_id) = params.user_id {
+        query_builder = query_builder.bind(user_id);
+    }
+    if let Some(book_id) = params.book_id {
+        query_builder = query_builder.bind(book_id);
+    }
+
+    let rows = query_builder.fetch_all(pool).await?;
+    let quotes: Vec<Quote> = rows.iter().map(quote_from_row).collect();
+
+    info!("Found {} quotes matching search", quotes.len());
+    Ok(quotes)
+}
+
+/// Pick one quote from the library's books the viewer can see, optionally only from a
+/// user's quotes.
+///
+/// With a `seed` the same quote comes back for the same seed while the set of quotes
+/// is unchanged, which is how the daily quote stays put all day.
+pub async fn get_random_quote_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: Option<i64>,
+    viewer_id: Option<i64>,
+    seed: Option<u64>,
+) -> Result<Option<Quote>, sqlx::Error> {
+    debug!(
+        "Picking a random quote for user {:?} with seed {:?}",
+        user_id, seed
+    );
+
+    let filter = format!(
+        "WHERE b.library_id = ? AND (? IS NULL OR q.user_id = ?)
+           AND q.book_id NOT IN ({HIDDEN_BOOK_IDS_SQL})"
+    );
+    let count: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM quotes q INNER JOIN books b ON q.book_id = b.id {filter}"
+    ))
+    .bind(library_id)
+    .bind(user_id)
+    .bind(user_id)
+    .bind(viewer_id)
+    .bind(viewer_id)
+    .fetch_one(pool)
+    .await?;
+
+    if count == 0 {
+        return Ok(None);
+    }
+
+    let offset = match seed {
+        Some(seed) => (seed % count as u64) as i64,
+        None => rand::thread_rng().gen_range(0..count),
+    };
+
+    let query = format!("{QUOTE_SELECT} {filter} ORDER BY q.id LIMIT 1 OFFSET ?");
+    let row = sqlx::query(&query)
+        .bind(library_id)
+        .bind(user_id)
+        .bind(user_id)
+        .bind(viewer_id)
+        .bind(viewer_id)
+        .bind(offset)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.as_ref().map(quote_from_row))
+}
+
+pub async fn create_quote_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    user_id: i64,
+    request: &CreateQuoteRequest,
+) -> Result<Quote, sqlx::Error> {
+    debug!("Creating quote from book {} by user {}", book_id, user_id);
+
+    let quote_id = sqlx::query!(
+        r#"INSERT INTO quotes (user_id, book_id, text, page, location, chapter, note)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         RETURNING id as "id!""#,
+        user_id,
+        book_id,
+        request.text,
+        request.page,
+        request.location,
+        request.chapter,
+        request.note
+    )
+    .fetch_one(pool)
+    .await?
+    .id;
+
+    info!(
+        "Created quote {} from book {} by user {}",
+        quote_id, book_id, user_id
+    );
+
+    get_quote_by_id_query(pool, book_id, quote_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+// Only the user who saved the quote can update it; anyone else gets RowNotFound
+pub async fn update_quote_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    quote_id: i64,
+    user_id: i64,
+    request: &UpdateQuoteRequest,
+) -> Result<Quote, sqlx::Error> {
+    debug!(
+        "Updating quote {} from book {} by user {}",
+        quote_id, book_id, user_id
+    );
+
+    let result = sqlx::query!(
+        "UPDATE quotes
+         SET text = COALESCE(?, text),
+             page = COALESCE(?, page),
+             location = COALESCE(?, location),
+             chapter = COALESCE(?, chapter),
+             note = COALESCE(?, note),
+             updated_at = datetime('now')
+         WHERE id = ? AND book_id = ? AND user_id = ?",
+        request.text,
+        request.page,
+        request.location,
+        request.chapter,
+        request.note,
+        quote_id,
+        book_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Updated quote {} from book {}", quote_id, book_id);
+
+    get_quote_by_id_query(pool, book_id, quote_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+// Only the user who saved the quote can delete it
+pub async fn delete_quote_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    quote_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Deleting quote {} from book {} by user {}",
+        quote_id, book_id, user_id
+    );
+
+    let result = sqlx::query!(
+        "DELETE FROM quotes WHERE id = ? AND book_id = ? AND user_id = ?",
+        quote_id,
+        book_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Deleted quote {} from book {}", quote_id, book_id);
+    Ok(())
+}
+
+struct LibraryBook {
+    id: i64,
+    title: String,
+    author: String,
+}
+
+// Prefer a book whose title and author both match; fall back to the title alone
+// when only one book has it
+fn match_clipping_book(books: &[LibraryBook], title: &str, author: Option<&str>) -> Option<i64> {
+    let title = normalize_title(title);
+    let title_matches: Vec<&LibraryBook> = books
+        .iter()
+        .filter(|book| normalize_title(&book.title) == title)
+        .collect();
+
+    if let Some(author) = author.map(normalize_author).filter(|a| !a.is_empty()) {
+        let both_match = title_matches.iter().find(|book| {
+            let book_author = normalize_author(&book.author);
+            book_author == author || book_author.contains(&author) || author.contains(&book_author)
+        });
+        if let Some(book) = both_match {
+            return Some(book.id);
+        }
+    }
+
+    match title_matches.as_slice() {
+        [book] => Some(book.id),
+        _ => None,
+    }
+}
+
+// Saves a Kindle clipping as a quote unless the same one was imported before.
+// Returns whether a new quote was created.
+async fn insert_kindle_quote(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    user_id: i64,
+    book_id: i64,
+    clipping: &Clipping,
+) -> Result<bool, sqlx::Error> {
+    let existing = sqlx::query!(
+        "SELECT id FROM quotes
+         WHERE user_id = ? AND book_id = ? AND source = 'kindle' AND location IS ? AND text = ?",
+        user_id,
+        book_id,
+        clipping.location,
+        clipping.text
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    if existing.is_some() {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "INSERT INTO quotes (user_id, book_id, text, page, location, source, clipped_at)
+         VALUES (?, ?, ?, ?, ?, 'kindle', ?)",
+        user_id,
+        book_id,
+        clipping.text,
+        clipping.page,
+        clipping.location,
+        clipping.clipped_at
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(true)
+}
+
+// Attaches a note to the highlight it was written on. A highlight that already has a
+// different note keeps it, so notes edited since the last import aren't overwritten, and
+// the new note is saved as a quote of its own instead.
+// Returns whether the note was added.
+async fn attach_kindle_note(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    user_id: i64,
+    book_id: i64,
+    highlight: &Clipping,
+    note: &Clipping,
+) -> Result<bool, sqlx::Error> {
+    let existing = sqlx::query!(
+        "SELECT id, note FROM quotes
+         WHERE user_id = ? AND book_id = ? AND source = 'kindle' AND location IS ? AND text = ?",
+        user_id,
+        book_id,
+        highlight.location,
+        highlight.text
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match existing {
+        Some(quote) if quote.note.is_none() => {
+            sqlx::query!(
+                "UPDATE quotes SET note = ?, updated_at = datetime('now') WHERE id = ?",
+                note.text,
+                quote.id
+            )
+            .execute(&mut **tx)
+            .await?;
+            Ok(true)
+        }
+        Some(quote) if quote.note.as_deref() == Some(note.text.as_str()) => Ok(false),
+        _ => insert_kindle_quote(tx, user_id, book_id, note).await,
+    }
+}
+
+/// Import parsed Kindle clippings as the user's quotes.
+///
+/// Clippings are grouped by book and matched against `library_id`'s books by title and author.
+/// Books that can't be matched are created when `create_missing_books` is set and
+/// otherwise reported as missing. Notes are attached to the highlight they were written
+/// on, or saved as a quote of their own. Clippings that were imported before are counted
+/// as duplicates, so the same file can be imported again safely. With `dry_run` the
+/// import is rolled back after counting.
+pub async fn import_kindle_clippings_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    clippings: &[Clipping],
+    create_missing_books: bool,
+    dry_run: bool,
+) -> Result<KindleImportSummary, sqlx::Error> {
+    debug!(
+        "Importing {} Kindle clippings for user {} (create missing books: {}, dry run: {})",
+        clippings.len(),
+        user_id,
+        create_missing_books,
+        dry_run
+    );
+
+    // Group clippings by book, keeping the order they appear in the file
+    let mut groups: Vec<(&str, Option<&str>, Vec<&Clipping>)> = Vec::new();
+    let mut bookmarks_skipped = 0;
+    for clipping in clippings {
+        if clipping.kind == ClippingKind::Bookmark {
+            bookmarks_skipped += 1;
+            continue;
+        }
+        if clipping.text.is_empty() {
+            continue;
+        }
+        let author = clipping.author.as_deref();
+        match groups
+            .iter_mut()
+            .find(|(title, group_author, _)| *title == clipping.title && *group_author == author)
+        {
+            Some((_, _, group)) => group.push(clipping),
+            None => groups.push((&clipping.title, author, vec![clipping])),
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let mut library: Vec<LibraryBook> = sqlx::query_as!(
+        LibraryBook,
+        r#"SELECT id as "id!", title, author FROM books WHERE library_id = ?"#,
+        library_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Books created by this import; a dry run rolls them back, so their ids mean nothing
+    let mut created_book_ids = HashSet::new();
+    let mut books = Vec::new();
+    for (title, author, group) in groups {
+        let matched_id = match_clipping_book(&library, title, author);
+        let mut book = KindleImportBook {
+            title: title.to_string(),
+            author: author.map(str::to_string),
+            book_id: matched_id.filter(|id| !(dry_run && created_book_ids.contains(id))),
+            status: "matched".to_string(),
+            highlights: group
+                .iter()
+                .filter(|c| c.kind == ClippingKind::Highlight)
+                .count() as i64,
+            notes: group
+                .iter()
+                .filter(|c| c.kind == ClippingKind::Note)
+                .count() as i64,
+            imported: 0,
+            duplicates: 0,
+        };
+
+        let book_id = match matched_id {
+            Some(book_id) => book_id,
+            None if create_missing_books => {
+                let book_author = author.map(display_author).unwrap_or_default();
+                let created_id = sqlx::query!(
+                    r#"INSERT INTO books (user_id, library_id, title, author) VALUES (?, ?, ?, ?)
+                     RETURNING id as "id!""#,
+                    user_id,
+                    library_id,
+                    title,
+                    book_author
+                )
+                .fetch_one(&mut *tx)
+                .await?
+                .id;
+                info!(
+                    "Created book {} for Kindle clippings of '{}'",
+                    created_id, title
+                );
+                // Later groups for the same book, e.g. with the author written differently,
+                // match this one rather than creating it again
+                library.push(LibraryBook {
+                    id: created_id,
+                    title: title.to_string(),
+                    author: book_author,
+                });
+                created_book_ids.insert(created_id);
+
+                book.status = "created".to_string();
+                book.book_id = (!dry_run).then_some(created_id);
+                created_id
+            }
+            None => {
+                debug!("No book in the library matches Kindle title '{}'", title);
+                book.status = "missing".to_string();
+                books.push(book);
+                continue;
+            }
+        };
+
+        let highlights: Vec<&Clipping> = group
+            .iter()
+            .copied()
+            .filter(|c| c.kind == ClippingKind::Highlight)
+            .collect();
+
+        for highlight in &highlights {
+            if insert_kindle_quote(&mut tx, user_id, book_id, highlight).await? {
+                book.imported += 1;
+            } else {
+                book.duplicates += 1;
+            }
+        }
+
+        for note in group.iter().filter(|c| c.kind == ClippingKind::Note) {
+            // A note's location is where it was typed, at the end of the highlight it's on
+            let highlight = note.location_range().and_then(|(_, note_location)| {
+                highlights.iter().rev().find(|h| {
+                    h.location_range()
+                        .is_some_and(|(start, end)| start <= note_location && note_location <= end)
+                })
+            });
+
+            let added = match highlight {
+                Some(highlight) => {
+                    attach_kindle_note(&mut tx, user_id, book_id, highlight, note).await?
+                }
+                None => insert_kindle_quote(&mut tx, user_id, book_id, note).await?,
+            };
+            if added {
+                book.imported += 1;
+            } else {
+                book.duplicates += 1;
+            }
+        }
+
+        if book.imported > 0 {
+            sqlx::query!(
+                "UPDATE books SET updated_at = datetime('now') WHERE id = ?",
+                book_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        books.push(book);
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    let summary = KindleImportSummary {
+        dry_run,
+        imported: books.iter().map(|b| b.imported).sum(),
+        duplicates: books.iter().map(|b| b.duplicates).sum(),
+        missing_books: books.iter().filter(|b| b.status == "missing").count() as i64,
+        bookmarks_skipped,
+        books,
+    };
+
+    info!(
+        "Kindle import for user {}: {} imported, {} duplicates, {} missing books",
+        user_id, summary.imported, summary.duplicates, summary.missing_books
+    );
+    Ok(summary)
+}
@@ -0,0 +1,190 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::models::ratings::{
+    CreateRatingDimensionRequest, RatingDimension, UpdateRatingDimensionRequest,
+};
+
+const DEFAULT_MIN_VALUE: f64 = 0.0;
+const DEFAULT_MAX_VALUE: f64 = 5.0;
+const DEFAULT_INCREMENT: f64 = 0.5;
+
+pub async fn get_all_rating_dimensions_query(
+    pool: &Pool<Sqlite>,
+) -> Result<Vec<RatingDimension>, sqlx::Error> {
+    debug!("Querying database for all rating dimensions");
+
+    let dimensions = sqlx::query_as!(
+        RatingDimension,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            min_value,
+            max_value,
+            increment,
+            position,
+            created_at,
+            updated_at
+        FROM rating_dimensions
+        ORDER BY position, id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    info!("Found {} rating dimensions", dimensions.len());
+    Ok(dimensions)
+}
+
+pub async fn get_rating_dimension_by_id_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Option<RatingDimension>, sqlx::Error> {
+    debug!("Querying database for rating dimension with id: {}", id);
+
+    let dimension = sqlx::query_as!(
+        RatingDimension,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            min_value,
+            max_value,
+            increment,
+            position,
+            created_at,
+            updated_at
+        FROM rating_dimensions
+        WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(dimension)
+}
+
+pub async fn create_rating_dimension_query(
+    pool: &Pool<Sqlite>,
+    dimension: &CreateRatingDimensionRequest,
+) -> Result<RatingDimension, sqlx::Error> {
+    debug!("Creating new rating dimension: {}", dimension.name);
+
+    let min_value = dimension.min_value.unwrap_or(DEFAULT_MIN_VALUE);
+    let max_value = dimension.max_value.unwrap_or(DEFAULT_MAX_VALUE);
+    let increment = dimension.increment.unwrap_or(DEFAULT_INCREMENT);
+
+    // New dimensions go to the end of the display order unless a position is given
+    let created_dimension = sqlx::query_as!(
+        RatingDimension,
+        r#"
+        INSERT INTO rating_dimensions (name, min_value, max_value, increment, position)
+        VALUES (
+            ?,
+            ?,
+            ?,
+            ?,
+            COALESCE(?, (SELECT COALESCE(MAX(position), -1) + 1 FROM rating_dimensions))
+        )
+        RETURNING
+            id as "id!",
+            name,
+            min_value,
+            max_value,
+            increment,
+            position,
+            created_at,
+            updated_at
+        "#,
+        dimension.name,
+        min_value,
+        max_value,
+        increment,
+        dimension.position
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        "Created rating dimension '{}' with ID: {}",
+        created_dimension.name, created_dimension.id
+    );
+    Ok(created_dimension)
+}
+
+pub async fn update_rating_dimension_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    dimension: &UpdateRatingDimensionRequest,
+) -> Result<RatingDimension, sqlx::Error> {
+    debug!("Updating rating dimension with id: {}", id);
+
+    // Get current dimension data to fill in missing fields
+    let current = get_rating_dimension_by_id_query(pool, id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let name = dimension.name.clone().unwrap_or(current.name);
+    let min_value = dimension.min_value.unwrap_or(current.min_value);
+    let max_value = dimension.max_value.unwrap_or(current.max_value);
+    let increment = dimension.increment.unwrap_or(current.increment);
+    let position = dimension.position.unwrap_or(current.position);
+
+    let updated_dimension = sqlx::query_as!(
+        RatingDimension,
+        r#"
+        UPDATE rating_dimensions
+        SET name = ?, min_value = ?, max_value = ?, increment = ?, position = ?, updated_at = datetime('now')
+        WHERE id = ?
+        RETURNING
+            id as "id!",
+            name,
+            min_value,
+            max_value,
+            increment,
+            position,
+            created_at,
+            updated_at
+        "#,
+        name,
+        min_value,
+        max_value,
+        increment,
+        position,
+        id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!("Updated rating dimension with ID: {}", id);
+    Ok(updated_dimension)
+}
+
+// Deleting a dimension also removes every score recorded against it
+pub async fn delete_rating_dimension_by_id_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!("Deleting rating dimension with id: {}", id);
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM rating_scores WHERE dimension_id = ?", id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query!("DELETE FROM rating_dimensions WHERE id = ?", id)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    tx.commit().await?;
+
+    info!("Deleted rating dimension with ID: {}", id);
+    Ok(())
+}
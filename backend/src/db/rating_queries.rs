@@ -1,33 +1,77 @@
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Sqlite, Transaction};
 use tracing::{debug, info};
 
-// Create or update a rating for a book by a user
+use crate::models::ratings::RatingScoreInput;
+
+// Create or update a rating for a book by a user.
+// When `scores` is given it replaces the rating's per-dimension scores; otherwise they are kept.
 pub async fn upsert_rating_query(
     pool: &Pool<Sqlite>,
     user_id: i64,
     book_id: i64,
     rating: f64,
+    scores: Option<&[RatingScoreInput]>,
 ) -> Result<(), sqlx::Error> {
     debug!(
         "Upserting rating for book {} by user {} with rating {}",
         book_id, user_id, rating
     );
 
-    sqlx::query!(
-        "INSERT INTO ratings (user_id, book_id, rating) VALUES (?, ?, ?)
-         ON CONFLICT(user_id, book_id) DO UPDATE SET rating = ?, updated_at = datetime('now')",
+    let mut tx = pool.begin().await?;
+    upsert_rating_in_transaction(&mut tx, user_id, book_id, rating, scores).await?;
+    tx.commit().await?;
+
+    info!(
+        "Successfully upserted rating for book {} by user {}",
+        book_id, user_id
+    );
+
+    Ok(())
+}
+
+// `upsert_rating_query` as part of a larger transaction, such as saving a review
+pub(crate) async fn upsert_rating_in_transaction(
+    tx: &mut Transaction<'_, Sqlite>,
+    user_id: i64,
+    book_id: i64,
+    rating: f64,
+    scores: Option<&[RatingScoreInput]>,
+) -> Result<(), sqlx::Error> {
+    let rating_id = sqlx::query!(
+        r#"INSERT INTO ratings (user_id, book_id, rating) VALUES (?, ?, ?)
+         ON CONFLICT(user_id, book_id) DO UPDATE SET rating = ?, updated_at = datetime('now')
+         RETURNING id as "id!""#,
         user_id,
         book_id,
         rating,
         rating
     )
-    .execute(pool)
-    .await?;
+    .fetch_one(&mut **tx)
+    .await?
+    .id;
 
-    info!(
-        "Successfully upserted rating for book {} by user {}",
-        book_id, user_id
-    );
+    if let Some(scores) = scores {
+        sqlx::query!("DELETE FROM rating_scores WHERE rating_id = ?", rating_id)
+            .execute(&mut **tx)
+            .await?;
+
+        for score in scores {
+            sqlx::query!(
+                "INSERT INTO rating_scores (rating_id, dimension_id, score) VALUES (?, ?, ?)",
+                rating_id,
+                score.dimension_id,
+                score.score
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        debug!(
+            "Stored {} dimension scores for rating {}",
+            scores.len(),
+            rating_id
+        );
+    }
 
     Ok(())
 }
@@ -40,14 +84,26 @@ pub async fn delete_rating_query(
 ) -> Result<(), sqlx::Error> {
     debug!("Deleting rating for book {} by user {}", book_id, user_id);
 
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM rating_scores WHERE rating_id IN (SELECT id FROM ratings WHERE user_id = ? AND book_id = ?)",
+        user_id,
+        book_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
     sqlx::query!(
         "DELETE FROM ratings WHERE user_id = ? AND book_id = ?",
         user_id,
         book_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    tx.commit().await?;
+
     info!(
         "Successfully deleted rating for book {} by user {}",
         book_id, user_id
@@ -13,11 +13,22 @@ pub async fn upsert_status_query(
         user_id, book_id, status_id
     );
 
+    // finished_at is stamped when the book first reaches a finished status, kept while it
+    // stays finished, and cleared when it moves back to an unfinished one
     sqlx::query!(
-        "INSERT INTO reading_status (user_id, book_id, status_id)
-         VALUES (?, ?, ?)
+        "INSERT INTO reading_status (user_id, book_id, status_id, finished_at)
+         VALUES (?, ?, ?, CASE WHEN (SELECT is_finished FROM status WHERE id = ?) = 1 THEN datetime('now') END)
          ON CONFLICT(user_id, book_id) 
-         DO UPDATE SET status_id = ?, updated_at = datetime('now')",
+         DO UPDATE SET
+            status_id = excluded.status_id,
+            updated_at = datetime('now'),
+            finished_at = CASE
+                WHEN excluded.finished_at IS NULL THEN NULL
+                WHEN reading_status.finished_at IS NOT NULL
+                     AND (SELECT is_finished FROM status WHERE id = reading_status.status_id) = 1
+                    THEN reading_status.finished_at
+                ELSE excluded.finished_at
+            END",
         user_id,
         book_id,
         status_id,
@@ -100,3 +111,28 @@ pub async fn get_status_query(
         }
     }
 }
+
+/// Check whether a user's reading status for a book is one marked as finished
+pub async fn has_finished_book_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    book_id: i64,
+) -> Result<bool, sqlx::Error> {
+    debug!(
+        "Checking if user_id: {} has finished book_id: {}",
+        user_id, book_id
+    );
+
+    let row = sqlx::query!(
+        r#"SELECT rs.id as "id!"
+         FROM reading_status rs
+         INNER JOIN status s ON rs.status_id = s.id
+         WHERE rs.user_id = ? AND rs.book_id = ? AND s.is_finished = 1"#,
+        user_id,
+        book_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some())
+}
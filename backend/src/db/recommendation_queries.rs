@@ -0,0 +1,362 @@
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+
+use crate::db::book_queries::fetch_hidden_book_ids;
+use crate::models::recommendations::{Recommendation, RecommendationReason, RecommendedBook};
+use crate::utils::{HIGH_RATING, pearson_correlation, round_to_hundredths};
+
+// Household members need this many books rated in common before their taste is compared
+const MIN_SHARED_RATINGS: usize = 3;
+// Correlation above which another household member counts as having similar taste
+const SIMILAR_TASTE_THRESHOLD: f64 = 0.3;
+
+const GENRE_MATCH_WEIGHT: f64 = 1.0;
+const TAG_MATCH_WEIGHT: f64 = 0.5;
+const SIMILAR_TASTE_WEIGHT: f64 = 2.0;
+const SERIES_NEXT_WEIGHT: f64 = 3.0;
+const SEQUENCED_LIST_WEIGHT: f64 = 2.0;
+
+// Name of the list type whose lists are read in order
+const SEQUENCED_LIST_TYPE: &str = "SEQUENCED";
+
+struct RatingRow {
+    user_id: i64,
+    user_name: String,
+    book_id: i64,
+    rating: f64,
+}
+
+struct ScoredBook {
+    book: RecommendedBook,
+    reasons: Vec<RecommendationReason>,
+}
+
+impl ScoredBook {
+    fn add_reason(&mut self, kind: &str, explanation: String, weight: f64) {
+        self.reasons.push(RecommendationReason {
+            kind: kind.to_string(),
+            explanation,
+            weight: round_to_hundredths(weight),
+        });
+    }
+}
+
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// Suggest books for a user to read next.
+///
+/// Candidates are books the user hasn't rated and hasn't finished or started reading.
+/// Each candidate is scored from these signals, and every signal that fires is returned as a reason:
+/// - genres and tags shared with books the user rated highly
+/// - high ratings from household members whose ratings correlate with the user's
+/// - being the next unread book in a series the user has finished a book of
+/// - position in the user's SEQUENCED lists
+///
+/// Only books in `library_id` are suggested, leaving out books restricted for the user or
+/// for the viewer. Returns `None` if the user does not exist.
+pub async fn get_recommendations_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    viewer_id: Option<i64>,
+    limit: usize,
+) -> Result<Option<Vec<Recommendation>>, sqlx::Error> {
+    debug!("Building recommendations for user {}", user_id);
+
+    let user = sqlx::query!(r#"SELECT id as "id!" FROM users WHERE id = ?"#, user_id)
+        .fetch_optional(pool)
+        .await?;
+    if user.is_none() {
+        return Ok(None);
+    }
+
+    let candidates = sqlx::query_as!(
+        RecommendedBook,
+        r#"
+        SELECT b.id as "id!", b.title, b.author, b.cover_image, b.series
+        FROM books b
+        WHERE b.library_id = ?
+        AND b.id NOT IN (
+            SELECT rs.book_id
+            FROM reading_status rs
+            INNER JOIN status s ON rs.status_id = s.id
+            WHERE rs.user_id = ? AND (s.is_finished = 1 OR s.is_active = 1)
+        )
+        AND b.id NOT IN (SELECT book_id FROM ratings WHERE user_id = ?)
+        ORDER BY b.id
+        "#,
+        library_id,
+        user_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut hidden_book_ids = fetch_hidden_book_ids(pool, Some(user_id)).await?;
+    hidden_book_ids.extend(fetch_hidden_book_ids(pool, viewer_id).await?);
+
+    let mut scored: Vec<ScoredBook> = candidates
+        .into_iter()
+        .filter(|book| !hidden_book_ids.contains(&book.id))
+        .map(|book| ScoredBook {
+            book,
+            reasons: Vec::new(),
+        })
+        .collect();
+
+    let ratings = sqlx::query_as!(
+        RatingRow,
+        r#"
+        SELECT r.user_id as "user_id!", u.name as user_name, r.book_id as "book_id!", r.rating
+        FROM ratings r
+        INNER JOIN users u ON r.user_id = u.id
+        INNER JOIN books b ON r.book_id = b.id
+        WHERE b.library_id = ?
+        "#,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut ratings_by_user: HashMap<i64, HashMap<i64, f64>> = HashMap::new();
+    let mut user_names: HashMap<i64, String> = HashMap::new();
+    for row in &ratings {
+        ratings_by_user
+            .entry(row.user_id)
+            .or_default()
+            .insert(row.book_id, row.rating);
+        user_names.insert(row.user_id, row.user_name.clone());
+    }
+    let own_ratings = ratings_by_user.remove(&user_id).unwrap_or_default();
+
+    // Genre and tag overlap with the user's highly rated books
+    let liked_books: HashSet<i64> = own_ratings
+        .iter()
+        .filter(|(_, rating)| **rating >= HIGH_RATING)
+        .map(|(book_id, _)| *book_id)
+        .collect();
+
+    let book_genres = sqlx::query!(
+        r#"
+        SELECT bg.book_id as "book_id!", g.id as "id!", g.name
+        FROM book_genres bg
+        INNER JOIN genres g ON bg.genre_id = g.id
+        INNER JOIN books b ON bg.book_id = b.id
+        WHERE b.library_id = ?
+        ORDER BY g.name
+        "#,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+    let book_tags = sqlx::query!(
+        r#"
+        SELECT bt.book_id as "book_id!", t.id as "id!", t.name
+        FROM book_tags bt
+        INNER JOIN tags t ON bt.tag_id = t.id
+        INNER JOIN books b ON bt.book_id = b.id
+        WHERE b.library_id = ?
+        ORDER BY t.name
+        "#,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let liked_genres: HashSet<i64> = book_genres
+        .iter()
+        .filter(|row| liked_books.contains(&row.book_id))
+        .map(|row| row.id)
+        .collect();
+    let liked_tags: HashSet<i64> = book_tags
+        .iter()
+        .filter(|row| liked_books.contains(&row.book_id))
+        .map(|row| row.id)
+        .collect();
+
+    for candidate in scored.iter_mut() {
+        let genres: Vec<&str> = book_genres
+            .iter()
+            .filter(|row| row.book_id == candidate.book.id && liked_genres.contains(&row.id))
+            .map(|row| row.name.as_str())
+            .collect();
+        if !genres.is_empty() {
+            candidate.add_reason(
+                "genre_overlap",
+                format!(
+                    "Shares {} with books you rated {}+ stars",
+                    join_names(&genres),
+                    HIGH_RATING
+                ),
+                genres.len() as f64 * GENRE_MATCH_WEIGHT,
+            );
+        }
+
+        let tags: Vec<&str> = book_tags
+            .iter()
+            .filter(|row| row.book_id == candidate.book.id && liked_tags.contains(&row.id))
+            .map(|row| row.name.as_str())
+            .collect();
+        if !tags.is_empty() {
+            candidate.add_reason(
+                "tag_overlap",
+                format!(
+                    "Tagged {} like books you rated {}+ stars",
+                    join_names(&tags),
+                    HIGH_RATING
+                ),
+                tags.len() as f64 * TAG_MATCH_WEIGHT,
+            );
+        }
+    }
+
+    // High ratings from household members with similar taste
+    let mut similar_users: Vec<(i64, f64)> = ratings_by_user
+        .iter()
+        .filter_map(|(other_id, other_ratings)| {
+            let pairs: Vec<(f64, f64)> = own_ratings
+                .iter()
+                .filter_map(|(book_id, rating)| {
+                    other_ratings.get(book_id).map(|other| (*rating, *other))
+                })
+                .collect();
+            if pairs.len() < MIN_SHARED_RATINGS {
+                return None;
+            }
+            pearson_correlation(&pairs)
+                .filter(|correlation| *correlation > SIMILAR_TASTE_THRESHOLD)
+                .map(|correlation| (*other_id, correlation))
+        })
+        .collect();
+    similar_users.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for candidate in scored.iter_mut() {
+        for (other_id, correlation) in &similar_users {
+            let Some(rating) = ratings_by_user[other_id].get(&candidate.book.id) else {
+                continue;
+            };
+            if *rating >= HIGH_RATING {
+                candidate.add_reason(
+                    "similar_taste",
+                    format!(
+                        "{} rated it {} stars and your ratings agree ({:.2} correlation)",
+                        user_names[other_id], rating, correlation
+                    ),
+                    SIMILAR_TASTE_WEIGHT * correlation,
+                );
+            }
+        }
+    }
+
+    // The next unread book in each series the user has finished at least one book of.
+    // Series have no explicit ordering, so books are taken in the order they were added.
+    let finished_series = sqlx::query!(
+        r#"
+        SELECT b.series as "series!", COUNT(*) as "count!: i64"
+        FROM reading_status rs
+        INNER JOIN status s ON rs.status_id = s.id
+        INNER JOIN books b ON rs.book_id = b.id
+        WHERE rs.user_id = ? AND b.library_id = ? AND s.is_finished = 1
+          AND b.series IS NOT NULL AND b.series != ''
+        GROUP BY b.series
+        "#,
+        user_id,
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for series in &finished_series {
+        // Candidates are ordered by id, so the first match is the earliest unread book
+        if let Some(candidate) = scored
+            .iter_mut()
+            .find(|c| c.book.series.as_deref() == Some(series.series.as_str()))
+        {
+            candidate.add_reason(
+                "series_next",
+                format!(
+                    "Next unread book in the {} series, which you've finished {} of",
+                    series.series, series.count
+                ),
+                SERIES_NEXT_WEIGHT,
+            );
+        }
+    }
+
+    // Position in the user's SEQUENCED lists: the first unread book counts most
+    let list_books = sqlx::query!(
+        r#"
+        SELECT l.id as "list_id!", l.name, lb.book_id as "book_id!"
+        FROM lists l
+        INNER JOIN list_types lt ON l.type_id = lt.id
+        INNER JOIN list_books lb ON lb.list_id = l.id
+        WHERE l.user_id = ? AND l.library_id = ? AND lt.name = ?
+        ORDER BY l.id, lb.position
+        "#,
+        user_id,
+        library_id,
+        SEQUENCED_LIST_TYPE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut unread_rank_by_list: HashMap<i64, usize> = HashMap::new();
+    for row in &list_books {
+        let Some(candidate) = scored.iter_mut().find(|c| c.book.id == row.book_id) else {
+            continue;
+        };
+        let rank = unread_rank_by_list.entry(row.list_id).or_insert(0);
+        let explanation = if *rank == 0 {
+            format!("Up next in your list \"{}\"", row.name)
+        } else {
+            format!(
+                "Number {} still to read in your list \"{}\"",
+                *rank + 1,
+                row.name
+            )
+        };
+        candidate.add_reason(
+            "sequenced_list",
+            explanation,
+            SEQUENCED_LIST_WEIGHT / (*rank + 1) as f64,
+        );
+        *rank += 1;
+    }
+
+    let mut recommendations: Vec<Recommendation> = scored
+        .into_iter()
+        .filter(|candidate| !candidate.reasons.is_empty())
+        .map(|mut candidate| {
+            candidate
+                .reasons
+                .sort_by(|a, b| b.weight.total_cmp(&a.weight));
+            Recommendation {
+                score: round_to_hundredths(candidate.reasons.iter().map(|r| r.weight).sum()),
+                book: candidate.book,
+                reasons: candidate.reasons,
+            }
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.book.title.cmp(&b.book.title))
+    });
+    recommendations.truncate(limit);
+
+    info!(
+        "Built {} recommendations for user {}",
+        recommendations.len(),
+        user_id
+    );
+
+    Ok(Some(recommendations))
+}
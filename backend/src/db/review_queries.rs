@@ -0,0 +1,189 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Pool, Row, Sqlite};
+use tracing::{debug, info};
+
+use crate::db::rating_queries::upsert_rating_in_transaction;
+use crate::models::reviews::{CreateReviewRequest, Review, ReviewUser, UpdateReviewRequest};
+
+// Reviews joined with their author and the author's rating for the book
+const REVIEW_SELECT: &str = "
+    SELECT rv.id, rv.book_id, rv.user_id, rv.headline, rv.body, rv.is_spoiler, rv.created_at, rv.updated_at,
+           r.rating, u.name as user_name, u.color as user_color, u.avatar_image as user_avatar_image
+    FROM reviews rv
+    INNER JOIN users u ON rv.user_id = u.id
+    LEFT JOIN ratings r ON r.user_id = rv.user_id AND r.book_id = rv.book_id";
+
+fn review_from_row(row: &SqliteRow) -> Review {
+    Review {
+        id: row.get("id"),
+        book_id: row.get("book_id"),
+        user_id: row.get("user_id"),
+        headline: row.get("headline"),
+        body: row.get("body"),
+        is_spoiler: row.get("is_spoiler"),
+        is_redacted: false,
+        rating: row.get("rating"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        user: ReviewUser {
+            id: row.get("user_id"),
+            name: row.get("user_name"),
+            color: row.get("user_color"),
+            avatar_image: row.get("user_avatar_image"),
+        },
+    }
+}
+
+pub async fn get_reviews_by_book_id_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+) -> Result<Vec<Review>, sqlx::Error> {
+    debug!("Querying database for reviews of book {}", book_id);
+
+    let query = format!("{REVIEW_SELECT} WHERE rv.book_id = ? ORDER BY rv.created_at DESC");
+    let rows = sqlx::query(&query).bind(book_id).fetch_all(pool).await?;
+
+    let reviews: Vec<Review> = rows.iter().map(review_from_row).collect();
+    info!("Found {} reviews for book {}", reviews.len(), book_id);
+    Ok(reviews)
+}
+
+pub async fn get_review_by_id_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    review_id: i64,
+) -> Result<Option<Review>, sqlx::Error> {
+    debug!(
+        "Querying database for review {} of book {}",
+        review_id, book_id
+    );
+
+    let query = format!("{REVIEW_SELECT} WHERE rv.id = ? AND rv.book_id = ?");
+    let row = sqlx::query(&query)
+        .bind(review_id)
+        .bind(book_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.as_ref().map(review_from_row))
+}
+
+pub async fn create_review_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    user_id: i64,
+    request: &CreateReviewRequest,
+) -> Result<Review, sqlx::Error> {
+    debug!("Creating review of book {} by user {}", book_id, user_id);
+
+    let is_spoiler = request.is_spoiler.unwrap_or(false);
+
+    let mut tx = pool.begin().await?;
+
+    let review_id = sqlx::query!(
+        r#"INSERT INTO reviews (user_id, book_id, headline, body, is_spoiler)
+         VALUES (?, ?, ?, ?, ?)
+         RETURNING id as "id!""#,
+        user_id,
+        book_id,
+        request.headline,
+        request.body,
+        is_spoiler
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .id;
+
+    if let Some(rating) = request.rating {
+        upsert_rating_in_transaction(&mut tx, user_id, book_id, rating, None).await?;
+    }
+
+    tx.commit().await?;
+
+    info!(
+        "Created review {} of book {} by user {}",
+        review_id, book_id, user_id
+    );
+
+    get_review_by_id_query(pool, book_id, review_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+// Only the review's author can update it; anyone else gets RowNotFound
+pub async fn update_review_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    review_id: i64,
+    user_id: i64,
+    request: &UpdateReviewRequest,
+) -> Result<Review, sqlx::Error> {
+    debug!(
+        "Updating review {} of book {} by user {}",
+        review_id, book_id, user_id
+    );
+
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query!(
+        "UPDATE reviews
+         SET headline = COALESCE(?, headline),
+             body = COALESCE(?, body),
+             is_spoiler = COALESCE(?, is_spoiler),
+             updated_at = datetime('now')
+         WHERE id = ? AND book_id = ? AND user_id = ?",
+        request.headline,
+        request.body,
+        request.is_spoiler,
+        review_id,
+        book_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    if let Some(rating) = request.rating {
+        upsert_rating_in_transaction(&mut tx, user_id, book_id, rating, None).await?;
+    }
+
+    tx.commit().await?;
+
+    info!("Updated review {} of book {}", review_id, book_id);
+
+    get_review_by_id_query(pool, book_id, review_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+// Only the review's author can delete it; the rating itself is kept
+pub async fn delete_review_query(
+    pool: &Pool<Sqlite>,
+    book_id: i64,
+    review_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!(
+        "Deleting review {} of book {} by user {}",
+        review_id, book_id, user_id
+    );
+
+    let result = sqlx::query!(
+        "DELETE FROM reviews WHERE id = ? AND book_id = ? AND user_id = ?",
+        review_id,
+        book_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Deleted review {} of book {}", review_id, book_id);
+    Ok(())
+}
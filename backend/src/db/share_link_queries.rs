@@ -0,0 +1,254 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::db::book_queries::{fetch_hidden_book_ids, get_book_details_query};
+use crate::db::list_queries::get_list_access_query;
+use crate::models::lists::ListAccess;
+use crate::models::share_links::{
+    ShareLink, SharedBook, SharedBookSummary, SharedJournal, SharedList, SharedView,
+};
+
+// The current user's links in a library, newest first, including revoked and expired ones
+pub async fn get_share_links_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+) -> Result<Vec<ShareLink>, sqlx::Error> {
+    debug!(
+        "Querying database for share links of user {} in library {}",
+        user_id, library_id
+    );
+
+    sqlx::query_as!(
+        ShareLink,
+        r#"SELECT id as "id!", token, user_id, library_id, list_id, book_id, open_count,
+                  last_opened_at, expires_at, created_at, revoked_at
+           FROM share_links
+           WHERE user_id = ? AND library_id = ?
+           ORDER BY created_at DESC, id DESC"#,
+        user_id,
+        library_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// The caller checks the user can see the list or book being shared
+pub async fn create_share_link_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    token: &str,
+    list_id: Option<i64>,
+    book_id: Option<i64>,
+    expires_in_days: Option<i64>,
+) -> Result<ShareLink, sqlx::Error> {
+    debug!(
+        "Creating share link for list {:?} / book {:?} for user {}",
+        list_id, book_id, user_id
+    );
+
+    // datetime('now', NULL) is NULL, so links without an expiry never expire
+    let expiry_modifier = expires_in_days.map(|days| format!("+{} days", days));
+
+    let share_link = sqlx::query_as!(
+        ShareLink,
+        r#"INSERT INTO share_links (token, user_id, library_id, list_id, book_id, expires_at)
+           VALUES (?, ?, ?, ?, ?, datetime('now', ?))
+           RETURNING id as "id!", token, user_id, library_id, list_id, book_id, open_count,
+                     last_opened_at, expires_at, created_at, revoked_at"#,
+        token,
+        user_id,
+        library_id,
+        list_id,
+        book_id,
+        expiry_modifier
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        "Created share link {} for user {} (expires: {:?})",
+        share_link.id, user_id, share_link.expires_at
+    );
+    Ok(share_link)
+}
+
+// Returns RowNotFound if the user has no such link or it was already revoked
+pub async fn revoke_share_link_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    share_link_id: i64,
+) -> Result<(), sqlx::Error> {
+    debug!("Revoking share link {} of user {}", share_link_id, user_id);
+
+    let result = sqlx::query!(
+        "UPDATE share_links SET revoked_at = datetime('now')
+         WHERE id = ? AND user_id = ? AND revoked_at IS NULL",
+        share_link_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Revoked share link {} of user {}", share_link_id, user_id);
+    Ok(())
+}
+
+// The live link with this token: not revoked and not expired
+pub async fn get_live_share_link_query(
+    pool: &Pool<Sqlite>,
+    token: &str,
+) -> Result<Option<ShareLink>, sqlx::Error> {
+    sqlx::query_as!(
+        ShareLink,
+        r#"SELECT id as "id!", token, user_id, library_id, list_id, book_id, open_count,
+                  last_opened_at, expires_at, created_at, revoked_at
+           FROM share_links
+           WHERE token = ? AND revoked_at IS NULL
+             AND (expires_at IS NULL OR expires_at > datetime('now'))"#,
+        token
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// Count one more view of a link, returning how many times it has been opened
+pub async fn record_share_link_open_query(
+    pool: &Pool<Sqlite>,
+    share_link_id: i64,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        "UPDATE share_links
+         SET open_count = open_count + 1, last_opened_at = datetime('now')
+         WHERE id = ?
+         RETURNING open_count",
+        share_link_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// What a share link shows: the list or book as the person who shared it sees it, minus
+/// anything private. Books restricted for that person stay hidden, and only
+/// household-visible journal entries are included, whoever wrote them.
+///
+/// Returns None if the list or book has since moved out of reach.
+pub async fn get_shared_view_query(
+    pool: &Pool<Sqlite>,
+    share_link: &ShareLink,
+) -> Result<Option<SharedView>, sqlx::Error> {
+    let shared_by = sqlx::query_scalar!("SELECT name FROM users WHERE id = ?", share_link.user_id)
+        .fetch_one(pool)
+        .await?;
+
+    let mut view = SharedView {
+        shared_by,
+        expires_at: share_link.expires_at.clone(),
+        list: None,
+        book: None,
+    };
+
+    if let Some(list_id) = share_link.list_id {
+        view.list = get_shared_list(pool, share_link, list_id).await?;
+        if view.list.is_none() {
+            return Ok(None);
+        }
+    } else if let Some(book_id) = share_link.book_id {
+        view.book = get_shared_book(pool, share_link, book_id).await?;
+        if view.book.is_none() {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(view))
+}
+
+async fn get_shared_list(
+    pool: &Pool<Sqlite>,
+    share_link: &ShareLink,
+    list_id: i64,
+) -> Result<Option<SharedList>, sqlx::Error> {
+    // A collaborator's links stop working once they're removed from the list
+    if get_list_access_query(pool, share_link.library_id, list_id, share_link.user_id)
+        .await?
+        .is_none_or(|access| access < ListAccess::Edit)
+    {
+        return Ok(None);
+    }
+
+    let Some(name) = sqlx::query_scalar!(
+        "SELECT name FROM lists WHERE id = ? AND library_id = ?",
+        list_id,
+        share_link.library_id
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let hidden_book_ids = fetch_hidden_book_ids(pool, Some(share_link.user_id)).await?;
+    let books = sqlx::query!(
+        r#"SELECT b.id as "id!", b.title, b.author, b.series, b.cover_image
+           FROM list_books lb
+           INNER JOIN books b ON lb.book_id = b.id
+           WHERE lb.list_id = ?
+           ORDER BY lb.position"#,
+        list_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .filter(|book| !hidden_book_ids.contains(&book.id))
+    .map(|book| SharedBookSummary {
+        title: book.title,
+        author: book.author,
+        series: book.series,
+        cover_image: book.cover_image,
+    })
+    .collect();
+
+    Ok(Some(SharedList { name, books }))
+}
+
+async fn get_shared_book(
+    pool: &Pool<Sqlite>,
+    share_link: &ShareLink,
+    book_id: i64,
+) -> Result<Option<SharedBook>, sqlx::Error> {
+    let Some(book) = get_book_details_query(
+        pool,
+        share_link.library_id,
+        book_id,
+        Some(share_link.user_id),
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(SharedBook {
+        title: book.title,
+        author: book.author,
+        series: book.series,
+        cover_image: book.cover_image,
+        page_count: book.page_count,
+        genres: book.genres.into_iter().map(|genre| genre.name).collect(),
+        journals: book
+            .journals
+            .into_iter()
+            .filter(|journal| journal.visibility == "household")
+            .map(|journal| SharedJournal {
+                title: journal.title,
+                content: journal.content,
+                author: journal.user.name,
+                created_at: journal.created_at,
+            })
+            .collect(),
+    }))
+}
@@ -0,0 +1,223 @@
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, info};
+
+use crate::models::statuses::{CreateStatusRequest, Status, UpdateStatusRequest};
+
+const DEFAULT_STATUS_COLOR: &str = "#6b7280";
+
+pub async fn get_all_statuses_query(pool: &Pool<Sqlite>) -> Result<Vec<Status>, sqlx::Error> {
+    debug!("Querying database for all statuses");
+
+    let statuses = sqlx::query_as!(
+        Status,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            color,
+            position,
+            is_finished as "is_finished: bool",
+            is_active as "is_active: bool",
+            is_planned as "is_planned: bool",
+            created_at,
+            updated_at
+        FROM status
+        ORDER BY position, id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    info!("Found {} statuses", statuses.len());
+    Ok(statuses)
+}
+
+pub async fn get_status_by_id_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Option<Status>, sqlx::Error> {
+    debug!("Querying database for status with id: {}", id);
+
+    let status = sqlx::query_as!(
+        Status,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            color,
+            position,
+            is_finished as "is_finished: bool",
+            is_active as "is_active: bool",
+            is_planned as "is_planned: bool",
+            created_at,
+            updated_at
+        FROM status
+        WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(status)
+}
+
+/// Check whether a status id exists in the status table
+pub async fn status_exists_query(pool: &Pool<Sqlite>, id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(r#"SELECT id as "id!" FROM status WHERE id = ?"#, id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.is_some())
+}
+
+/// The first planned (to be read) status in display order, if any status is planned
+pub async fn get_planned_status_id_query(pool: &Pool<Sqlite>) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT id as "id!" FROM status WHERE is_planned = 1 ORDER BY position, id LIMIT 1"#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| row.id))
+}
+
+/// Count how many reading statuses currently use a status
+pub async fn get_status_usage_count_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT COUNT(*) as "count!: i64" FROM reading_status WHERE status_id = ?"#,
+        id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.count)
+}
+
+pub async fn create_status_query(
+    pool: &Pool<Sqlite>,
+    status: &CreateStatusRequest,
+) -> Result<Status, sqlx::Error> {
+    debug!("Creating new status: {}", status.name);
+
+    let color = status
+        .color
+        .clone()
+        .unwrap_or_else(|| DEFAULT_STATUS_COLOR.to_string());
+    let is_finished = status.is_finished.unwrap_or(false);
+    let is_active = status.is_active.unwrap_or(false);
+    let is_planned = status.is_planned.unwrap_or(false);
+
+    // New statuses go to the end of the display order unless a position is given
+    let created_status = sqlx::query_as!(
+        Status,
+        r#"
+        INSERT INTO status (id, name, color, position, is_finished, is_active, is_planned, created_at, updated_at)
+        VALUES (
+            (SELECT COALESCE(MAX(id), 0) + 1 FROM status),
+            ?,
+            ?,
+            COALESCE(?, (SELECT COALESCE(MAX(position), -1) + 1 FROM status)),
+            ?,
+            ?,
+            ?,
+            datetime('now'),
+            datetime('now')
+        )
+        RETURNING
+            id as "id!",
+            name,
+            color,
+            position,
+            is_finished as "is_finished: bool",
+            is_active as "is_active: bool",
+            is_planned as "is_planned: bool",
+            created_at,
+            updated_at
+        "#,
+        status.name,
+        color,
+        status.position,
+        is_finished,
+        is_active,
+        is_planned
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!(
+        "Created status '{}' with ID: {}",
+        created_status.name, created_status.id
+    );
+    Ok(created_status)
+}
+
+pub async fn update_status_query(
+    pool: &Pool<Sqlite>,
+    id: i64,
+    status: &UpdateStatusRequest,
+) -> Result<Status, sqlx::Error> {
+    debug!("Updating status with id: {}", id);
+
+    // Get current status data to fill in missing fields
+    let current = get_status_by_id_query(pool, id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let name = status.name.clone().unwrap_or(current.name);
+    let color = status.color.clone().unwrap_or(current.color);
+    let position = status.position.unwrap_or(current.position);
+    let is_finished = status.is_finished.unwrap_or(current.is_finished);
+    let is_active = status.is_active.unwrap_or(current.is_active);
+    let is_planned = status.is_planned.unwrap_or(current.is_planned);
+
+    let updated_status = sqlx::query_as!(
+        Status,
+        r#"
+        UPDATE status
+        SET name = ?, color = ?, position = ?, is_finished = ?, is_active = ?, is_planned = ?,
+            updated_at = datetime('now')
+        WHERE id = ?
+        RETURNING
+            id as "id!",
+            name,
+            color,
+            position,
+            is_finished as "is_finished: bool",
+            is_active as "is_active: bool",
+            is_planned as "is_planned: bool",
+            created_at,
+            updated_at
+        "#,
+        name,
+        color,
+        position,
+        is_finished,
+        is_active,
+        is_planned,
+        id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    info!("Updated status with ID: {}", id);
+    Ok(updated_status)
+}
+
+pub async fn delete_status_by_id_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
+    debug!("Deleting status with id: {}", id);
+
+    let result = sqlx::query!("DELETE FROM status WHERE id = ?", id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    info!("Deleted status with ID: {}", id);
+    Ok(())
+}
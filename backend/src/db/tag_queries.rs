@@ -1,22 +1,31 @@
 use sqlx::{Pool, Sqlite};
 
-use crate::models::tags::Tag;
+use crate::models::tags::{Tag, UpdateTagRequest};
 
-pub async fn get_all_tags_query(pool: &Pool<Sqlite>) -> Result<Vec<Tag>, sqlx::Error> {
-    let rows = sqlx::query_as!(Tag, "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM tags ORDER BY name")
-        .fetch_all(pool)
-        .await?;
+pub async fn get_all_tags_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+) -> Result<Vec<Tag>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        Tag,
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM tags WHERE library_id = ? ORDER BY name",
+        library_id
+    )
+    .fetch_all(pool)
+    .await?;
     Ok(rows)
 }
 
 pub async fn get_tags_by_name_query(
     pool: &Pool<Sqlite>,
+    library_id: i64,
     name_filter: &str,
 ) -> Result<Vec<Tag>, sqlx::Error> {
     let pattern = format!("%{name_filter}%");
     let rows = sqlx::query_as!(
         Tag,
-        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM tags WHERE name LIKE ? ORDER BY name",
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM tags WHERE library_id = ? AND name LIKE ? ORDER BY name",
+        library_id,
         pattern
     )
     .fetch_all(pool)
@@ -24,21 +33,36 @@ pub async fn get_tags_by_name_query(
     Ok(rows)
 }
 
-pub async fn get_tag_by_id_query(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Tag>, sqlx::Error> {
-    let tag = sqlx::query_as!(Tag, "SELECT id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at FROM tags WHERE id = ?", id)
-        .fetch_optional(pool)
-        .await?;
+pub async fn get_tag_by_id_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+) -> Result<Option<Tag>, sqlx::Error> {
+    let tag = sqlx::query_as!(
+        Tag,
+        "SELECT id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at FROM tags WHERE id = ? AND library_id = ?",
+        id,
+        library_id
+    )
+    .fetch_optional(pool)
+    .await?;
 
     Ok(tag)
 }
 
-pub async fn create_tag_query(pool: &Pool<Sqlite>, tag: Tag) -> Result<Tag, sqlx::Error> {
+pub async fn create_tag_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    tag: Tag,
+) -> Result<Tag, sqlx::Error> {
     let tag = sqlx::query_as!(
         Tag,
-        "INSERT INTO tags (user_id, name, color) VALUES (?, ?, ?) RETURNING id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at",
+        "INSERT INTO tags (user_id, library_id, name, color, is_restricted) VALUES (?, ?, ?, ?, ?) RETURNING id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at",
         tag.user_id,
+        library_id,
         tag.name,
-        tag.color
+        tag.color,
+        tag.is_restricted
     )
     .fetch_one(pool)
     .await?;
@@ -46,13 +70,20 @@ pub async fn create_tag_query(pool: &Pool<Sqlite>, tag: Tag) -> Result<Tag, sqlx
     Ok(tag)
 }
 
-pub async fn update_tag_query(pool: &Pool<Sqlite>, id: i64, tag: Tag) -> Result<Tag, sqlx::Error> {
+pub async fn update_tag_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+    tag: &UpdateTagRequest,
+) -> Result<Tag, sqlx::Error> {
     let updated_tag = sqlx::query_as!(
         Tag,
-        "UPDATE tags SET name = ?, color = ?, updated_at = datetime('now') WHERE id = ? RETURNING id as \"id!\", user_id as \"user_id!\", name, color, created_at, updated_at",
+        "UPDATE tags SET name = ?, color = ?, is_restricted = COALESCE(?, is_restricted), updated_at = datetime('now') WHERE id = ? AND library_id = ? RETURNING id as \"id!\", user_id as \"user_id!\", name, color, is_restricted as \"is_restricted: bool\", created_at, updated_at",
         tag.name,
         tag.color,
-        id
+        tag.is_restricted,
+        id,
+        library_id
     )
     .fetch_one(pool)
     .await?;
@@ -60,14 +91,35 @@ pub async fn update_tag_query(pool: &Pool<Sqlite>, id: i64, tag: Tag) -> Result<
     Ok(updated_tag)
 }
 
-pub async fn delete_tag_query(pool: &Pool<Sqlite>, id: i64) -> Result<(), sqlx::Error> {
-    let result = sqlx::query!("DELETE FROM tags WHERE id = ?", id)
-        .execute(pool)
-        .await?;
+pub async fn delete_tag_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Untag journal entries first; their tag references don't cascade
+    sqlx::query!(
+        "DELETE FROM journal_entry_tags WHERE tag_id IN (SELECT id FROM tags WHERE id = ? AND library_id = ?)",
+        id,
+        library_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM tags WHERE id = ? AND library_id = ?",
+        id,
+        library_id
+    )
+    .execute(&mut *tx)
+    .await?;
 
     if result.rows_affected() == 0 {
         return Err(sqlx::Error::RowNotFound);
     }
 
+    tx.commit().await?;
+
     Ok(())
 }
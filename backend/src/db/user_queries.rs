@@ -4,12 +4,15 @@ use tracing::{debug, info};
 use crate::models::User;
 
 // User queries
-pub async fn get_all_users(pool: &Pool<Sqlite>) -> Result<Vec<User>, sqlx::Error> {
-    debug!("Querying database for all users");
+// The members of a library
+pub async fn get_all_users(pool: &Pool<Sqlite>, library_id: i64) -> Result<Vec<User>, sqlx::Error> {
+    debug!("Querying database for users in library {}", library_id);
 
     let users = sqlx::query_as!(
         User,
-        "SELECT id, name, color, avatar_image, created_at, updated_at, last_login FROM users"
+        r#"SELECT id as "id!", name, color, avatar_image, role, created_at, updated_at, last_login FROM users
+         WHERE id IN (SELECT user_id FROM library_members WHERE library_id = ?)"#,
+        library_id
     )
     .fetch_all(pool)
     .await?;
@@ -34,7 +37,7 @@ pub async fn select_user(pool: &Pool<Sqlite>, user_id: i64) -> Result<User, sqlx
     let user = sqlx::query_as!(
         User,
         "UPDATE users SET last_login = CURRENT_TIMESTAMP WHERE id = ? 
-         RETURNING id, name, color, avatar_image, created_at, updated_at, last_login",
+         RETURNING id, name, color, avatar_image, role, created_at, updated_at, last_login",
         user_id
     )
     .fetch_one(pool)
@@ -53,13 +56,14 @@ pub async fn update_user(
     name: Option<String>,
     color: Option<String>,
     avatar_image: Option<Option<String>>,
+    role: Option<String>,
 ) -> Result<User, sqlx::Error> {
     debug!("Updating user with ID: {}", user_id);
 
     // Get current user data to fill in missing fields
     let current_user = sqlx::query_as!(
         User,
-        "SELECT id, name, color, avatar_image, created_at, updated_at, last_login FROM users WHERE id = ?",
+        "SELECT id, name, color, avatar_image, role, created_at, updated_at, last_login FROM users WHERE id = ?",
         user_id
     )
     .fetch_one(pool)
@@ -72,14 +76,16 @@ pub async fn update_user(
         Some(inner) => inner,
         None => current_user.avatar_image,
     };
+    let final_role = role.unwrap_or(current_user.role);
 
     let updated_user = sqlx::query_as!(
         User,
-        "UPDATE users SET name = ?, color = ?, avatar_image = ?, updated_at = datetime('now') WHERE id = ? 
-         RETURNING id, name, color, avatar_image, created_at, updated_at, last_login",
+        "UPDATE users SET name = ?, color = ?, avatar_image = ?, role = ?, updated_at = datetime('now') WHERE id = ? 
+         RETURNING id, name, color, avatar_image, role, created_at, updated_at, last_login",
         final_name,
         final_color,
         final_avatar_image,
+        final_role,
         user_id
     )
     .fetch_one(pool)
@@ -91,3 +97,60 @@ pub async fn update_user(
     );
     Ok(updated_user)
 }
+
+// New users join the library they were created in
+pub async fn create_user_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    name: &str,
+    color: &str,
+    role: &str,
+) -> Result<User, sqlx::Error> {
+    debug!(
+        "Creating user '{}' with role {} in library {}",
+        name, role, library_id
+    );
+
+    let mut tx = pool.begin().await?;
+
+    let user = sqlx::query_as!(
+        User,
+        "INSERT INTO users (name, color, role) VALUES (?, ?, ?)
+         RETURNING id, name, color, avatar_image, role, created_at, updated_at, last_login",
+        name,
+        color,
+        role
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO library_members (library_id, user_id) VALUES (?, ?)",
+        library_id,
+        user.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!("Created user with ID {}: '{}'", user.id, user.name);
+    Ok(user)
+}
+
+// The role of a user, or None if there's no such user
+pub async fn get_user_role_query(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let user = sqlx::query!("SELECT role FROM users WHERE id = ?", user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(user.map(|user| user.role))
+}
+
+pub async fn count_admins_query(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!: i64" FROM users WHERE role = 'admin'"#)
+        .fetch_one(pool)
+        .await
+}
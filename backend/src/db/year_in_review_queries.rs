@@ -0,0 +1,201 @@
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::db::book_queries::fetch_hidden_book_ids;
+use crate::models::year_in_review::{
+    CoverTile, FavouriteGenre, MostJournaledBook, YearInReview, YearInReviewBook, YearInReviewUser,
+};
+use crate::utils::round_to_hundredths;
+
+/// Build the year-in-review report for a user, from the books in a library.
+///
+/// A book counts as read in `year` when the user's reading status for it is one marked
+/// `is_finished` and it was finished during the year. Books restricted for `viewer_id`
+/// are left out of every part of the report. Returns `None` if the user does not exist.
+pub async fn get_year_in_review_query(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    user_id: i64,
+    viewer_id: Option<i64>,
+    year: i32,
+) -> Result<Option<YearInReview>, sqlx::Error> {
+    debug!("Building year in review {} for user {}", year, user_id);
+
+    let user = sqlx::query_as!(
+        YearInReviewUser,
+        r#"SELECT id as "id!", name, color FROM users WHERE id = ?"#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(user) = user else {
+        return Ok(None);
+    };
+
+    let year_str = year.to_string();
+    let hidden_book_ids = fetch_hidden_book_ids(pool, viewer_id).await?;
+
+    // Books finished during the year, oldest first so the montage reads chronologically
+    let mut books = sqlx::query_as!(
+        YearInReviewBook,
+        r#"
+        SELECT
+            b.id as "id!",
+            b.title,
+            b.author,
+            b.cover_image,
+            b.page_count,
+            r.rating as "rating?",
+            rs.finished_at
+        FROM reading_status rs
+        INNER JOIN status s ON rs.status_id = s.id
+        INNER JOIN books b ON rs.book_id = b.id
+        LEFT JOIN ratings r ON r.book_id = b.id AND r.user_id = rs.user_id
+        WHERE rs.user_id = ? AND b.library_id = ? AND s.is_finished = 1
+          AND strftime('%Y', rs.finished_at) = ?
+        ORDER BY rs.finished_at, b.id
+        "#,
+        user_id,
+        library_id,
+        year_str
+    )
+    .fetch_all(pool)
+    .await?;
+
+    books.retain(|b| !hidden_book_ids.contains(&b.id));
+
+    // Genres of the finished books, counted here so hidden books can be left out
+    let genre_rows = sqlx::query!(
+        r#"
+        SELECT
+            bg.book_id as "book_id!",
+            g.id as "id!",
+            g.name,
+            g.color
+        FROM reading_status rs
+        INNER JOIN status s ON rs.status_id = s.id
+        INNER JOIN books b ON rs.book_id = b.id
+        INNER JOIN book_genres bg ON bg.book_id = rs.book_id
+        INNER JOIN genres g ON g.id = bg.genre_id
+        WHERE rs.user_id = ? AND b.library_id = ? AND s.is_finished = 1
+          AND strftime('%Y', rs.finished_at) = ?
+        "#,
+        user_id,
+        library_id,
+        year_str
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut genres: HashMap<i64, FavouriteGenre> = HashMap::new();
+    for row in genre_rows {
+        if hidden_book_ids.contains(&row.book_id) {
+            continue;
+        }
+        genres
+            .entry(row.id)
+            .or_insert(FavouriteGenre {
+                id: row.id,
+                name: row.name,
+                color: row.color,
+                book_count: 0,
+            })
+            .book_count += 1;
+    }
+    // Most books wins, ties go to the genre that sorts first by name
+    let favourite_genre = genres.into_values().min_by(|a, b| {
+        b.book_count
+            .cmp(&a.book_count)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    // Journal counts per book, most journaled (then most recently journaled) first
+    let journaled_books = sqlx::query_as!(
+        MostJournaledBook,
+        r#"
+        SELECT
+            b.id as "id!",
+            b.title as "title!",
+            b.author as "author!",
+            b.cover_image,
+            COUNT(je.id) as "journal_count!: i64"
+        FROM journal_entries je
+        INNER JOIN books b ON je.book_id = b.id
+        WHERE je.user_id = ? AND b.library_id = ? AND strftime('%Y', je.created_at) = ?
+        GROUP BY b.id
+        ORDER BY COUNT(je.id) DESC, MAX(je.created_at) DESC
+        "#,
+        user_id,
+        library_id,
+        year_str
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let journaled_books: Vec<MostJournaledBook> = journaled_books
+        .into_iter()
+        .filter(|b| !hidden_book_ids.contains(&b.id))
+        .collect();
+    let journal_count = journaled_books.iter().map(|b| b.journal_count).sum();
+    let most_journaled_book = journaled_books.into_iter().next();
+
+    let ratings: Vec<f64> = books.iter().filter_map(|b| b.rating).collect();
+    let average_rating = if ratings.is_empty() {
+        None
+    } else {
+        let mean = ratings.iter().sum::<f64>() / ratings.len() as f64;
+        Some(round_to_hundredths(mean))
+    };
+
+    let rated_books = || {
+        books
+            .iter()
+            .filter_map(|b| b.rating.map(|rating| (b, rating)))
+    };
+    let highest_rated = rated_books()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(b, _)| b.clone());
+    let lowest_rated = rated_books()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(b, _)| b.clone());
+    let longest_book = books
+        .iter()
+        .filter(|b| b.page_count.is_some())
+        .max_by_key(|b| b.page_count)
+        .cloned();
+
+    let total_pages = books.iter().filter_map(|b| b.page_count).sum();
+    let covers = books
+        .iter()
+        .map(|b| CoverTile {
+            book_id: b.id,
+            title: b.title.clone(),
+            cover_image: b.cover_image.clone(),
+        })
+        .collect();
+
+    info!(
+        "Built year in review {} for user {}: {} books, {} pages",
+        year,
+        user_id,
+        books.len(),
+        total_pages
+    );
+
+    Ok(Some(YearInReview {
+        year,
+        user,
+        books_read: books.len() as i64,
+        total_pages,
+        journal_count,
+        average_rating,
+        highest_rated,
+        lowest_rated,
+        favourite_genre,
+        longest_book,
+        most_journaled_book,
+        covers,
+    }))
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+// A token a user created for a script. The token itself is only shown once, when created.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    // The first characters of the token, e.g. "hl_3fZk9"
+    pub token_prefix: String,
+    // Read-only tokens may only make GET requests
+    pub read_only: bool,
+    pub created_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub read_only: Option<bool>,
+}
+
+// Returned once when a token is created, with the token to send as a Bearer token
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NewApiToken {
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+    pub token: String,
+}
@@ -1,3 +1,5 @@
+use crate::models::journal_reactions::JournalReactionSummary;
+
 #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
 pub struct Book {
     #[serde(skip_deserializing)]
@@ -8,6 +10,7 @@ pub struct Book {
     pub title: String,
     pub author: String,
     pub series: Option<String>,
+    pub page_count: Option<i64>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -20,6 +23,7 @@ pub struct CreateBookRequest {
     pub tags: Option<Vec<i64>>,
     pub genres: Option<Vec<i64>>,
     pub series: Option<String>,
+    pub page_count: Option<i64>,
 }
 
 #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
@@ -30,9 +34,10 @@ pub struct UpdateBookRequest {
     pub tags: Option<Vec<i64>>,
     pub genres: Option<Vec<i64>>,
     pub series: Option<String>,
+    pub page_count: Option<i64>,
 }
 
-#[derive(serde_derive::Serialize)]
+#[derive(serde_derive::Serialize, Debug, Clone)]
 pub struct BookTag {
     pub id: i64,
     pub name: String,
@@ -59,7 +64,11 @@ pub struct BookJournal {
     pub id: i64,
     pub title: String,
     pub content: String,
+    pub visibility: String,
     pub user: JournalUser,
+    pub tags: Vec<BookTag>,
+    pub comment_count: i64,
+    pub reactions: Vec<JournalReactionSummary>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -78,11 +87,30 @@ pub struct BookRating {
     pub user_id: i64,
     pub book_id: i64,
     pub rating: f64,
+    pub scores: Vec<BookRatingScore>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
     pub user: RatingUser,
 }
 
+#[derive(serde_derive::Serialize)]
+pub struct BookRatingScore {
+    pub dimension_id: i64,
+    pub dimension_name: String,
+    pub score: f64,
+}
+
+// Average of every user's score for one rating dimension on a book
+#[derive(serde_derive::Serialize)]
+pub struct DimensionAverage {
+    pub dimension_id: i64,
+    pub name: String,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub average: f64,
+    pub rating_count: i64,
+}
+
 #[derive(serde_derive::Serialize)]
 pub struct StatusUser {
     pub id: i64,
@@ -120,18 +148,40 @@ pub struct BookWithDetails {
     pub tags: Vec<BookTag>,
     pub genres: Vec<BookGenre>,
     pub series: Option<String>,
+    pub page_count: Option<i64>,
     pub journals: Vec<BookJournal>,
+    pub quote_count: i64,
     pub ratings: Vec<BookRating>,
+    pub dimension_averages: Vec<DimensionAverage>,
     pub statuses: Vec<BookStatus>,
     pub current_user_status: Option<i64>,
 }
 
+// Constraints for picking a random book off one of the current user's piles
+pub struct RandomBookFilters {
+    pub status_id: i64,
+    pub genre_id: Option<i64>,
+    pub tag_id: Option<i64>,
+    pub max_pages: Option<i64>,
+    pub series_starters_only: bool,
+    pub skip_last_finished_author: bool,
+    pub exclude: Vec<i64>,
+}
+
+// A book eligible for the random picker and how long it has had its current status
+pub struct RandomBookCandidate {
+    pub id: i64,
+    pub days_on_status: f64,
+}
+
 #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
 pub struct Genre {
     pub id: i64,
     pub user_id: i64,
     pub name: String,
     pub color: String,
+    // Books in a restricted genre are hidden from children
+    pub is_restricted: bool,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -140,10 +190,13 @@ pub struct Genre {
 pub struct CreateGenreRequest {
     pub name: String,
     pub color: String,
+    pub is_restricted: Option<bool>,
 }
 
 #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
 pub struct UpdateGenreRequest {
     pub name: Option<String>,
     pub color: Option<String>,
+    // Left unchanged when omitted
+    pub is_restricted: Option<bool>,
 }
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompatibilityUser {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompatibilityBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub cover_image: Option<String>,
+}
+
+// A book both users rated, and how far apart their ratings were
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SharedRating {
+    pub book: CompatibilityBook,
+    pub user_rating: f64,
+    pub other_user_rating: f64,
+    pub difference: f64,
+}
+
+// A book one user rated highly that the other hasn't read
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LovedBook {
+    pub book: CompatibilityBook,
+    pub rating: f64,
+}
+
+// How closely two users' ratings line up.
+// `correlation` is None when they share fewer than two rated books or a user rated them all the same.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TasteCompatibility {
+    pub user: CompatibilityUser,
+    pub other_user: CompatibilityUser,
+    pub shared_count: i64,
+    pub correlation: Option<f64>,
+    pub agreements: Vec<SharedRating>,
+    pub disagreements: Vec<SharedRating>,
+    pub loved_by_user_unread_by_other: Vec<LovedBook>,
+    pub loved_by_other_unread_by_user: Vec<LovedBook>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompatibilityCell {
+    pub other_user_id: i64,
+    pub shared_count: i64,
+    pub correlation: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompatibilityRow {
+    pub user_id: i64,
+    pub scores: Vec<CompatibilityCell>,
+}
+
+// Pairwise compatibility for every user in the household
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompatibilityMatrix {
+    pub users: Vec<CompatibilityUser>,
+    pub rows: Vec<CompatibilityRow>,
+}
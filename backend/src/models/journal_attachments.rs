@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+// A file attached to a journal entry. Journal content embeds it by its `url`,
+// e.g. `<img src="/attachments/Xq3...k9.png">`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalAttachment {
+    pub id: i64,
+    pub journal_entry_id: i64,
+    pub book_id: i64,
+    pub user_id: i64,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub url: String,
+    pub created_at: Option<String>,
+    // Location of the file in upload storage, never exposed to clients
+    #[serde(skip)]
+    pub storage_key: String,
+}
+
+// Attachments are stored under `journals/` with a random file name, which doubles as
+// the unguessable part of their URL
+pub const ATTACHMENT_KEY_PREFIX: &str = "journals/";
+
+pub fn journal_attachment_url(storage_key: &str) -> String {
+    format!(
+        "/attachments/{}",
+        storage_key
+            .strip_prefix(ATTACHMENT_KEY_PREFIX)
+            .unwrap_or(storage_key)
+    )
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::books::JournalUser;
+
+// A comment on a journal entry, with its replies nested under it
+#[derive(Serialize)]
+pub struct JournalComment {
+    pub id: i64,
+    pub journal_entry_id: i64,
+    pub parent_id: Option<i64>,
+    pub content: String,
+    pub user: JournalUser,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub replies: Vec<JournalComment>,
+}
+
+// Request body for commenting on a journal entry; set `parent_id` to reply to a comment
+#[derive(Deserialize, Debug)]
+pub struct CreateJournalCommentRequest {
+    pub content: String,
+    pub parent_id: Option<i64>,
+}
@@ -1,3 +1,5 @@
+use crate::models::BookTag;
+
 #[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug)]
 pub struct JournalEntry {
     #[serde(skip_deserializing)]
@@ -8,12 +10,71 @@ pub struct JournalEntry {
     pub user_id: i64,
     pub title: String,
     pub content: String,
+    // "private" (only the author) or "household" (everyone)
+    pub visibility: String,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(skip_deserializing)]
+    pub tags: Vec<BookTag>,
+}
+
+pub const JOURNAL_VISIBILITIES: [&str; 2] = ["private", "household"];
+
+pub fn is_valid_journal_visibility(visibility: &str) -> bool {
+    JOURNAL_VISIBILITIES.contains(&visibility)
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct CreateJournalRequest {
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<i64>>,
+    // Defaults to "household"
+    pub visibility: Option<String>,
 }
 
 #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
 pub struct UpdateJournalRequest {
     pub title: Option<String>,
     pub content: Option<String>,
+    pub tags: Option<Vec<i64>>,
+    pub visibility: Option<String>,
+}
+
+// Filters and paging for the journal feed at GET /journals
+#[derive(serde_derive::Deserialize, Debug, Default)]
+pub struct JournalFeedParams {
+    pub user_id: Option<i64>,
+    pub book_id: Option<i64>,
+    pub tag_id: Option<i64>,
+    // Inclusive "YYYY-MM-DD" bounds on when entries were written
+    pub from: Option<String>,
+    pub to: Option<String>,
+    // Matches the entry's title and content
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    // `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(serde_derive::Serialize, Debug)]
+pub struct JournalFeedBook {
+    pub id: i64,
+    pub title: String,
+    pub cover_image: Option<String>,
+}
+
+// A journal entry with enough of its book to show it in a feed
+#[derive(serde_derive::Serialize, Debug)]
+pub struct JournalFeedItem {
+    #[serde(flatten)]
+    pub journal: JournalEntry,
+    pub book: JournalFeedBook,
+}
+
+#[derive(serde_derive::Serialize, Debug)]
+pub struct JournalFeedPage {
+    pub journals: Vec<JournalFeedItem>,
+    // Pass as `cursor` to get the next page; null on the last page
+    pub next_cursor: Option<String>,
 }
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+use crate::models::JournalEntry;
+use crate::models::journal_attachments::JournalAttachment;
+
+#[derive(Deserialize, Debug)]
+pub struct JournalExportParams {
+    // "markdown" (the default) or "epub"
+    pub format: Option<String>,
+}
+
+// A journal entry with everything an export writes alongside it
+#[derive(Debug)]
+pub struct JournalExportEntry {
+    pub journal: JournalEntry,
+    pub book_title: String,
+    pub book_author: String,
+    // The entry author's own rating of the book
+    pub rating: Option<f64>,
+    pub attachments: Vec<JournalAttachment>,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+// Everyone who reacted to a journal entry with one emoji
+#[derive(Serialize, Debug, Clone)]
+pub struct JournalReactionSummary {
+    pub emoji: String,
+    pub count: i64,
+    pub user_ids: Vec<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JournalReactionRequest {
+    pub emoji: String,
+}
+
+// Emoji can be several code points (skin tones, ZWJ sequences) but never words or spaces
+pub fn is_valid_reaction_emoji(emoji: &str) -> bool {
+    !emoji.is_empty()
+        && emoji.chars().count() <= 10
+        && !emoji
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_ascii_alphabetic())
+}
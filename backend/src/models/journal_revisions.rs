@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+// An earlier version of a journal entry's title and content
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalRevision {
+    pub id: i64,
+    pub journal_entry_id: i64,
+    pub title: String,
+    pub content: String,
+    // When this version was saved and when a newer one replaced it
+    pub saved_at: Option<String>,
+    pub replaced_at: Option<String>,
+}
+
+// Revisions to compare; without `to` the older revision is compared to the entry as it is now
+#[derive(Deserialize, Debug, Clone)]
+pub struct JournalRevisionDiffParams {
+    pub from: i64,
+    pub to: Option<i64>,
+}
+
+// One line of a diff: "equal", "added", or "removed"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalRevisionDiff {
+    // `to` is null when comparing against the current version of the entry
+    pub from: i64,
+    pub to: Option<i64>,
+    pub from_title: String,
+    pub to_title: String,
+    pub lines: Vec<DiffLine>,
+    pub added: i64,
+    pub removed: i64,
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+// A household's books, tags, genres and lists. Users can belong to several libraries.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Library {
+    pub id: i64,
+    pub name: String,
+    pub created_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateLibraryRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AddLibraryMemberRequest {
+    pub user_id: i64,
+}
@@ -1,5 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+pub const LIST_VISIBILITIES: [&str; 2] = ["private", "household"];
+
+pub fn is_valid_list_visibility(visibility: &str) -> bool {
+    LIST_VISIBILITIES.contains(&visibility)
+}
+
+// What a user may do with a list, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ListAccess {
+    // Household lists can be seen by everyone in the library
+    View,
+    // Collaborators can change the name, type and books
+    Edit,
+    // Only the owner can change visibility and collaborators, or delete the list
+    Own,
+}
+
 // Basic List structure from database
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct List {
@@ -9,6 +26,7 @@ pub struct List {
     pub user_id: i64,
     pub type_id: i64,
     pub name: String,
+    pub visibility: String,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -19,6 +37,9 @@ pub struct BookInList {
     pub id: i64,
     pub cover_image: Option<String>,
     pub status_name: Option<String>,
+    pub status_color: Option<String>,
+    // The user who put the book on the list, if they still exist
+    pub added_by: Option<i64>,
 }
 
 // User info to include in list responses
@@ -37,8 +58,11 @@ pub struct ListWithBooks {
     pub user_id: i64,
     pub type_id: i64,
     pub name: String,
+    // "private" or "household"
+    pub visibility: String,
     pub books: Vec<BookInList>,
     pub user: ListUser,
+    pub collaborators: Vec<ListUser>,
 }
 
 // Request body for creating a list
@@ -46,7 +70,8 @@ pub struct ListWithBooks {
 pub struct CreateListRequest {
     pub type_id: i64,
     pub name: String,
-    pub books: Vec<i64>, // Array of book IDs
+    pub visibility: Option<String>, // Defaults to "household"
+    pub books: Vec<i64>,            // Array of book IDs
 }
 
 // Request body for updating a list
@@ -54,5 +79,12 @@ pub struct CreateListRequest {
 pub struct UpdateListRequest {
     pub name: Option<String>,
     pub type_id: Option<i64>,
-    pub books: Option<Vec<i64>>, // Array of book IDs
+    pub visibility: Option<String>, // Only the owner can change it
+    pub books: Option<Vec<i64>>,    // Array of book IDs
+}
+
+// Request body for adding a collaborator to a list
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AddListCollaboratorRequest {
+    pub user_id: i64,
 }
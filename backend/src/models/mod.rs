@@ -1,11 +1,31 @@
+pub mod api_tokens;
 pub mod books;
+pub mod compatibility;
+pub mod journal_attachments;
+pub mod journal_comments;
 pub mod journal_entries;
+pub mod journal_exports;
+pub mod journal_reactions;
+pub mod journal_revisions;
+pub mod libraries;
 pub mod lists;
+pub mod obsidian;
+pub mod preferences;
+pub mod quotes;
+pub mod ratings;
+pub mod recommendations;
+pub mod reviews;
+pub mod share_links;
+pub mod statuses;
 pub mod tags;
 pub mod users;
+pub mod year_in_review;
 
 pub use books::{
     Book, BookJournal, BookTag, BookWithDetails, CreateBookRequest, UpdateBookRequest,
 };
-pub use journal_entries::{JournalEntry, UpdateJournalRequest};
+pub use journal_entries::{
+    CreateJournalRequest, JournalEntry, JournalFeedItem, JournalFeedPage, JournalFeedParams,
+    UpdateJournalRequest, is_valid_journal_visibility,
+};
 pub use users::{SelectUser, UpdateUserRequest, User};
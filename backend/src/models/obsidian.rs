@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+// What happened to one note of an imported vault
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObsidianImportNote {
+    pub file_name: String,
+    pub book_id: Option<i64>,
+    // "imported" or "skipped"
+    pub status: String,
+    // Why a note was skipped
+    pub reason: Option<String>,
+    pub journals_created: i64,
+    pub journals_updated: i64,
+    pub journals_unchanged: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObsidianImportSummary {
+    pub notes_imported: i64,
+    pub notes_skipped: i64,
+    pub journals_created: i64,
+    pub journals_updated: i64,
+    pub journals_unchanged: i64,
+    pub notes: Vec<ObsidianImportNote>,
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PREFERENCE_KEYS: [&str; 5] = [
+    "default_sort",
+    "rating_display",
+    "hidden_genres",
+    "default_list_id",
+    "theme",
+];
+pub const BOOK_SORTS: [&str; 5] = ["updated", "added", "title", "author", "series"];
+pub const RATING_DISPLAYS: [&str; 2] = ["stars", "numeric"];
+pub const THEMES: [&str; 3] = ["system", "light", "dark"];
+
+// A user's settings, with the server's defaults for anything they haven't set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserPreferences {
+    // How GET /books orders books when no `sort` is given
+    pub default_sort: String,
+    pub rating_display: String,
+    // Books in these genres are left out of GET /books
+    pub hidden_genres: Vec<i64>,
+    pub default_list_id: Option<i64>,
+    pub theme: String,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            default_sort: "updated".to_string(),
+            rating_display: "stars".to_string(),
+            hidden_genres: Vec::new(),
+            default_list_id: None,
+            theme: "system".to_string(),
+        }
+    }
+}
+
+fn one_of(value: &Value, allowed: &[&str]) -> Result<String, String> {
+    match value.as_str() {
+        Some(s) if allowed.contains(&s) => Ok(s.to_string()),
+        _ => Err(format!("must be one of: {}", allowed.join(", "))),
+    }
+}
+
+impl UserPreferences {
+    /// Set one preference from its JSON value. Unknown keys and values of the wrong
+    /// shape are an error describing what was expected.
+    pub fn set(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        match key {
+            "default_sort" => self.default_sort = one_of(value, &BOOK_SORTS)?,
+            "rating_display" => self.rating_display = one_of(value, &RATING_DISPLAYS)?,
+            "theme" => self.theme = one_of(value, &THEMES)?,
+            "hidden_genres" => {
+                self.hidden_genres = value
+                    .as_array()
+                    .and_then(|ids| ids.iter().map(Value::as_i64).collect())
+                    .ok_or("must be a list of genre ids")?;
+            }
+            "default_list_id" => {
+                self.default_list_id = match value {
+                    Value::Null => None,
+                    _ => Some(value.as_i64().ok_or("must be a list id or null")?),
+                };
+            }
+            _ => return Err("is not a known preference".to_string()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QuoteUser {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QuoteBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+}
+
+// A line copied from a book, placed by page or by location
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quote {
+    pub id: i64,
+    pub book_id: i64,
+    pub user_id: i64,
+    pub text: String,
+    pub page: Option<i64>,
+    pub location: Option<String>,
+    pub chapter: Option<String>,
+    pub note: Option<String>,
+    // "manual" or "kindle"
+    pub source: String,
+    pub clipped_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub user: QuoteUser,
+    pub book: QuoteBook,
+}
+
+// Request body for adding a quote to a book
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateQuoteRequest {
+    pub text: String,
+    pub page: Option<i64>,
+    pub location: Option<String>,
+    pub chapter: Option<String>,
+    pub note: Option<String>,
+}
+
+// Request body for updating a quote; omitted fields are left unchanged
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateQuoteRequest {
+    pub text: Option<String>,
+    pub page: Option<i64>,
+    pub location: Option<String>,
+    pub chapter: Option<String>,
+    pub note: Option<String>,
+}
+
+// Filters for searching quotes across all books
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct QuoteSearchParams {
+    pub q: Option<String>,
+    pub user_id: Option<i64>,
+    pub book_id: Option<i64>,
+}
+
+// Options for importing a Kindle "My Clippings.txt" file
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct KindleImportParams {
+    // Add books that aren't in the library yet instead of reporting them as missing
+    pub create_missing_books: Option<bool>,
+    // Report what would be imported without saving anything
+    pub dry_run: Option<bool>,
+}
+
+// What happened to the clippings of one book in an import
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KindleImportBook {
+    pub title: String,
+    pub author: Option<String>,
+    pub book_id: Option<i64>,
+    // "matched", "created", or "missing"
+    pub status: String,
+    pub highlights: i64,
+    pub notes: i64,
+    pub imported: i64,
+    pub duplicates: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KindleImportSummary {
+    pub dry_run: bool,
+    pub imported: i64,
+    pub duplicates: i64,
+    pub missing_books: i64,
+    pub bookmarks_skipped: i64,
+    pub books: Vec<KindleImportBook>,
+}
@@ -1,9 +1,41 @@
-#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
-pub struct Rating {
+use serde::{Deserialize, Serialize};
+
+// A configurable rating dimension (plot, characters, ...) and the scale it is scored on
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RatingDimension {
     pub id: i64,
-    pub user_id: i64,
-    pub book_id: i64,
-    pub rating: Option<f64>,
+    pub name: String,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub increment: f64,
+    pub position: i64,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
+
+// Request body for creating a rating dimension
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateRatingDimensionRequest {
+    pub name: String,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub increment: Option<f64>,
+    pub position: Option<i64>,
+}
+
+// Request body for updating a rating dimension
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateRatingDimensionRequest {
+    pub name: Option<String>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub increment: Option<f64>,
+    pub position: Option<i64>,
+}
+
+// A single dimension score submitted with a rating
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RatingScoreInput {
+    pub dimension_id: i64,
+    pub score: f64,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+// Book info to include in recommendation responses
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecommendedBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub cover_image: Option<String>,
+    pub series: Option<String>,
+}
+
+// One signal that contributed to a recommendation and how much it counted
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecommendationReason {
+    pub kind: String,
+    pub explanation: String,
+    pub weight: f64,
+}
+
+// A suggested book, its overall score and why it was picked
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recommendation {
+    pub book: RecommendedBook,
+    pub score: f64,
+    pub reasons: Vec<RecommendationReason>,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReviewUser {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub avatar_image: Option<String>,
+}
+
+// A user's published review of a book, with their rating for it.
+// `body` is None when the review is a spoiler and the viewer hasn't finished the book.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Review {
+    pub id: i64,
+    pub book_id: i64,
+    pub user_id: i64,
+    pub headline: String,
+    pub body: Option<String>,
+    pub is_spoiler: bool,
+    pub is_redacted: bool,
+    pub rating: Option<f64>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub user: ReviewUser,
+}
+
+// Request body for creating a review
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateReviewRequest {
+    pub headline: String,
+    pub body: String,
+    pub is_spoiler: Option<bool>,
+    pub rating: Option<f64>,
+}
+
+// Request body for updating a review
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateReviewRequest {
+    pub headline: Option<String>,
+    pub body: Option<String>,
+    pub is_spoiler: Option<bool>,
+    pub rating: Option<f64>,
+}
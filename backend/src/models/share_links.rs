@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+// A read-only link to one list or one book, opened at /shared/{token}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShareLink {
+    pub id: i64,
+    pub token: String,
+    pub user_id: i64,
+    pub library_id: i64,
+    // Exactly one of list_id and book_id is set
+    pub list_id: Option<i64>,
+    pub book_id: Option<i64>,
+    // How many times the link has been opened
+    pub open_count: i64,
+    pub last_opened_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub created_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateShareLinkRequest {
+    pub list_id: Option<i64>,
+    pub book_id: Option<i64>,
+    // Never expires when omitted
+    pub expires_in_days: Option<i64>,
+}
+
+// What someone opening a share link sees. Nothing here identifies other users beyond
+// their names, and private journal entries are never included.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SharedView {
+    pub shared_by: String,
+    pub expires_at: Option<String>,
+    pub list: Option<SharedList>,
+    pub book: Option<SharedBook>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SharedList {
+    pub name: String,
+    pub books: Vec<SharedBookSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SharedBookSummary {
+    pub title: String,
+    pub author: String,
+    pub series: Option<String>,
+    pub cover_image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SharedBook {
+    pub title: String,
+    pub author: String,
+    pub series: Option<String>,
+    pub cover_image: Option<String>,
+    pub page_count: Option<i64>,
+    pub genres: Vec<String>,
+    pub journals: Vec<SharedJournal>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SharedJournal {
+    pub title: String,
+    pub content: String,
+    pub author: String,
+    pub created_at: String,
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+// Reading status from the status table (READ, READING, TBR, ...)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Status {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub position: i64,
+    pub is_finished: bool,
+    pub is_active: bool,
+    pub is_planned: bool,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+// Request body for creating a status
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateStatusRequest {
+    pub name: String,
+    pub color: Option<String>,
+    pub position: Option<i64>,
+    pub is_finished: Option<bool>,
+    pub is_active: Option<bool>,
+    pub is_planned: Option<bool>,
+}
+
+// Request body for updating a status
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateStatusRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub position: Option<i64>,
+    pub is_finished: Option<bool>,
+    pub is_active: Option<bool>,
+    pub is_planned: Option<bool>,
+}
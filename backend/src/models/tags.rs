@@ -6,6 +6,17 @@ pub struct Tag {
     pub user_id: i64,
     pub name: String,
     pub color: String,
+    // Books with a restricted tag are hidden from children
+    #[serde(default)]
+    pub is_restricted: bool,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct UpdateTagRequest {
+    pub name: String,
+    pub color: String,
+    // Left unchanged when omitted
+    pub is_restricted: Option<bool>,
+}
@@ -3,7 +3,10 @@ pub struct User {
     pub id: i64,
     pub name: String,
     pub color: String,
+    // URL of the user's uploaded avatar, see `user_avatar_url`
     pub avatar_image: Option<String>,
+    // "admin", "member", or "child"
+    pub role: String,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
     pub last_login: Option<String>,
@@ -19,4 +22,26 @@ pub struct UpdateUserRequest {
     pub name: Option<String>,
     pub color: Option<String>,
     pub avatar_image: Option<String>,
+    // Only admins can change roles
+    pub role: Option<String>,
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct CreateUserRequest {
+    pub name: String,
+    pub color: String,
+    // Defaults to "member"
+    pub role: Option<String>,
+}
+
+pub const USER_ROLES: [&str; 3] = ["admin", "member", "child"];
+
+pub fn is_valid_user_role(role: &str) -> bool {
+    USER_ROLES.contains(&role)
+}
+
+// Where an uploaded avatar is served from. `version` changes with every upload, so each
+// URL always returns the same image.
+pub fn user_avatar_url(user_id: i64, version: &str) -> String {
+    format!("/users/{}/avatar?v={}", user_id, version)
 }
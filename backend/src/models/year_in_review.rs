@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+// A book finished during the reviewed year
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct YearInReviewBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub cover_image: Option<String>,
+    pub page_count: Option<i64>,
+    pub rating: Option<f64>,
+    pub finished_at: Option<String>,
+}
+
+// Genre the user finished the most books in
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FavouriteGenre {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub book_count: i64,
+}
+
+// Book the user wrote the most journal entries about
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MostJournaledBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub cover_image: Option<String>,
+    pub journal_count: i64,
+}
+
+// Cover tile for the montage, in the order the books were finished
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoverTile {
+    pub book_id: i64,
+    pub title: String,
+    pub cover_image: Option<String>,
+}
+
+// User info to include in the report
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct YearInReviewUser {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+// Full year-in-review report for GET /users/:id/year-in-review/:year
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct YearInReview {
+    pub year: i32,
+    pub user: YearInReviewUser,
+    pub books_read: i64,
+    pub total_pages: i64,
+    pub journal_count: i64,
+    pub average_rating: Option<f64>,
+    pub highest_rated: Option<YearInReviewBook>,
+    pub lowest_rated: Option<YearInReviewBook>,
+    pub favourite_genre: Option<FavouriteGenre>,
+    pub longest_book: Option<YearInReviewBook>,
+    pub most_journaled_book: Option<MostJournaledBook>,
+    pub covers: Vec<CoverTile>,
+}
@@ -0,0 +1,104 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{create_api_token_query, get_api_tokens_query, revoke_api_token_query};
+use crate::models::api_tokens::{ApiToken, CreateApiTokenRequest, NewApiToken};
+use crate::utils::{CurrentUser, generate_api_token};
+
+// GET /api-tokens - The current user's tokens, newest first, including revoked ones
+pub async fn get_api_tokens(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+) -> Result<Json<Vec<ApiToken>>, StatusCode> {
+    debug!("Fetching API tokens for user {}", current_user.id);
+
+    match get_api_tokens_query(&pool, current_user.id).await {
+        Ok(api_tokens) => {
+            info!(
+                "Found {} API tokens for user {}",
+                api_tokens.len(),
+                current_user.id
+            );
+            Ok(Json(api_tokens))
+        }
+        Err(e) => {
+            error!(
+                "Failed to fetch API tokens for user {}: {}",
+                current_user.id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /api-tokens - Create a token. The response is the only time the token is shown.
+// Tokens can't be used to create more tokens.
+pub async fn create_api_token(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Result<Json<NewApiToken>, StatusCode> {
+    if let Some(api_token) = &current_user.api_token {
+        warn!(
+            "API token {} tried to create another API token",
+            api_token.token_id
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        warn!("API token name is empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let read_only = request.read_only.unwrap_or(false);
+    let (token, token_hash, token_prefix) = generate_api_token();
+
+    match create_api_token_query(
+        &pool,
+        current_user.id,
+        name,
+        read_only,
+        &token_hash,
+        &token_prefix,
+    )
+    .await
+    {
+        Ok(api_token) => Ok(Json(NewApiToken { api_token, token })),
+        Err(e) => {
+            error!(
+                "Failed to create API token for user {}: {}",
+                current_user.id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /api-tokens/:id - Revoke one of the current user's tokens
+pub async fn revoke_api_token(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    Path(token_id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("User {} revoking API token {}", current_user.id, token_id);
+
+    match revoke_api_token_query(&pool, current_user.id, token_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No active API token {} found for user {}",
+                token_id, current_user.id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to revoke API token {}: {}", token_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use axum::Json;
+use axum::extract::{Extension, Multipart, Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    avatar_storage_key, get_user_avatar_key_query, remove_avatar_images, set_user_avatar_query,
+    store_avatar_images,
+};
+use crate::models::User;
+use crate::utils::{AVATAR_SIZES, CurrentUser, FileStorage, resize_avatar, sniff_image_type};
+
+// Largest avatar accepted before resizing, well under the request body limit
+const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+// Served when no size is asked for
+const DEFAULT_AVATAR_SIZE: u32 = 128;
+
+#[derive(Deserialize, Debug)]
+pub struct AvatarParams {
+    // One of AVATAR_SIZES
+    size: Option<u32>,
+    // Set by the URL in `avatar_image`, which changes with every upload
+    v: Option<String>,
+}
+
+// The user's current avatar key, checking they can change it
+async fn current_avatar_key(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    user_id: i64,
+) -> Result<Option<String>, StatusCode> {
+    if user_id != current_user.id {
+        current_user.ensure_admin()?;
+    }
+
+    match get_user_avatar_key_query(pool, user_id).await {
+        Ok(Some(avatar_key)) => Ok(avatar_key),
+        Ok(None) => {
+            warn!("No user found with ID: {}", user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch avatar of user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Upload an avatar as a multipart `file` field. Users can change their own avatar;
+/// admins can change anyone's.
+///
+/// The image is cropped to a centred square and stored at each of `AVATAR_SIZES`. The
+/// returned user's `avatar_image` is the URL to show it from.
+pub async fn upload_user_avatar(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(user_id): Path<i64>,
+    current_user: CurrentUser,
+    mut multipart: Multipart,
+) -> Result<Json<User>, StatusCode> {
+    info!(
+        "User {} uploading avatar for user {}",
+        current_user.id, user_id
+    );
+
+    let previous_key = current_avatar_key(&pool, &current_user, user_id).await?;
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        warn!("Invalid multipart upload: {}", e);
+        e.status()
+    })? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let bytes = field.bytes().await.map_err(|e| {
+            warn!("Failed to read uploaded file: {}", e);
+            e.status()
+        })?;
+        upload = Some(bytes);
+        break;
+    }
+
+    let Some(bytes) = upload else {
+        warn!("Avatar upload has no file field");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    if bytes.is_empty() {
+        warn!("Uploaded avatar is empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if bytes.len() > MAX_AVATAR_BYTES {
+        warn!(
+            "Uploaded avatar is {} bytes, over the {} byte limit",
+            bytes.len(),
+            MAX_AVATAR_BYTES
+        );
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    if sniff_image_type(&bytes).is_none() {
+        warn!("Uploaded avatar is not a supported image");
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    // Decoding and resizing is CPU-bound, so keep it off the async workers
+    let images = match tokio::task::spawn_blocking(move || resize_avatar(&bytes)).await {
+        Ok(Ok(images)) => images,
+        Ok(Err(e)) => {
+            warn!("Uploaded avatar could not be decoded: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Err(e) => {
+            error!("Avatar resizing failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let avatar_key = match store_avatar_images(&storage, &images).await {
+        Ok(avatar_key) => avatar_key,
+        Err(e) => {
+            error!("Failed to store avatar of user {}: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match set_user_avatar_query(&pool, user_id, Some(&avatar_key)).await {
+        Ok(user) => {
+            if let Some(previous_key) = previous_key {
+                remove_avatar_images(&storage, &previous_key).await;
+            }
+            info!("Stored avatar {} for user {}", avatar_key, user_id);
+            Ok(Json(user))
+        }
+        Err(e) => {
+            error!("Failed to record avatar of user {}: {}", user_id, e);
+            remove_avatar_images(&storage, &avatar_key).await;
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Serves the avatar image itself. No user header is required so the URL works as an <img> src.
+pub async fn get_user_avatar(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(user_id): Path<i64>,
+    Query(params): Query<AvatarParams>,
+) -> Result<impl IntoResponse, StatusCode> {
+    debug!(
+        "Fetching avatar of user {} with params: {:?}",
+        user_id, params
+    );
+
+    let size = params.size.unwrap_or(DEFAULT_AVATAR_SIZE);
+    if !AVATAR_SIZES.contains(&size) {
+        warn!("Invalid avatar size: {}", size);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let avatar_key = match get_user_avatar_key_query(&pool, user_id).await {
+        Ok(Some(Some(avatar_key))) => avatar_key,
+        Ok(_) => {
+            warn!("No avatar uploaded for user {}", user_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch avatar of user {}: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Versioned URLs never change, so they can be cached for good
+    let cache_control = match params.v {
+        Some(_) => "private, max-age=31536000, immutable",
+        None => "private, no-cache",
+    };
+
+    let storage_key = avatar_storage_key(&avatar_key, size);
+    match storage.read(&storage_key).await {
+        Ok(bytes) => Ok((
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+                (header::CACHE_CONTROL, cache_control),
+            ],
+            bytes,
+        )),
+        Err(e) => {
+            error!(
+                "Avatar of user {} is missing from storage ({}): {}",
+                user_id, storage_key, e
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+pub async fn delete_user_avatar(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(user_id): Path<i64>,
+    current_user: CurrentUser,
+) -> Result<StatusCode, StatusCode> {
+    info!("Removing avatar of user {}", user_id);
+
+    let previous_key = current_avatar_key(&pool, &current_user, user_id).await?;
+
+    match set_user_avatar_query(&pool, user_id, None).await {
+        Ok(_) => {
+            if let Some(previous_key) = previous_key {
+                remove_avatar_images(&storage, &previous_key).await;
+            }
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(e) => {
+            error!("Failed to remove avatar of user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
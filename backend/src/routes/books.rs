@@ -1,53 +1,123 @@
 use axum::Json;
-use axum::extract::{Path, Query, State};
+use axum::extract::{Extension, Path, Query, State};
 use axum::http::HeaderMap;
+use rand::seq::SliceRandom;
 use reqwest::StatusCode;
 use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, info, warn};
 
 use crate::db::book_queries::{
     create_book_query, create_book_tags, default_book_cover_query, delete_book_query,
-    get_all_books_with_details_query, get_book_details_query, search_books_with_details_query,
-    update_book_genres, update_book_query, update_book_tags,
+    fetch_hidden_book_ids, get_all_books_with_details_query, get_book_details_query,
+    get_random_book_candidates_query, search_books_with_details_query, update_book_genres,
+    update_book_query, update_book_tags,
 };
+use crate::db::journal_attachment_queries::{get_book_attachment_keys_query, has_inline_images};
 use crate::db::journal_queries::{create_journal_entry, get_journals_by_book_id};
+use crate::db::library_queries::book_in_library_query;
+use crate::db::preference_queries::get_user_preferences_query;
+use crate::db::rating_dimension_queries::get_all_rating_dimensions_query;
 use crate::db::rating_queries::{delete_rating_query, get_rating_query, upsert_rating_query};
 use crate::db::reading_status_queries::{
     delete_status_query, get_status_query, upsert_status_query,
 };
-use crate::models::{Book, BookWithDetails, CreateBookRequest, UpdateBookRequest};
-use crate::utils::extract_user_id_from_headers;
+use crate::db::status_queries::{get_planned_status_id_query, status_exists_query};
+use crate::models::books::RandomBookFilters;
+use crate::models::preferences::{BOOK_SORTS, UserPreferences};
+use crate::models::ratings::{RatingDimension, RatingScoreInput};
+use crate::models::{
+    Book, BookWithDetails, CreateBookRequest, CreateJournalRequest, UpdateBookRequest,
+    is_valid_journal_visibility,
+};
+use crate::utils::{
+    CurrentLibrary, CurrentUser, FileStorage, ensure_can_edit_book, extract_user_id_from_headers,
+    is_foreign_key_violation, is_on_scale,
+};
 
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub struct BookQueryParams {
     search: Option<String>,
+    // One of BOOK_SORTS; defaults to the current user's `default_sort` preference
+    sort: Option<String>,
+}
+
+// Books come from the database most recently updated first
+fn sort_books(books: &mut [BookWithDetails], sort: &str) {
+    match sort {
+        "added" => books.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        "title" => books.sort_by_key(|book| book.title.to_lowercase()),
+        "author" => {
+            books.sort_by_key(|book| (book.author.to_lowercase(), book.title.to_lowercase()))
+        }
+        // Books outside a series go last
+        "series" => books.sort_by_key(|book| {
+            (
+                book.series.is_none(),
+                book.series.as_deref().map(str::to_lowercase),
+                book.title.to_lowercase(),
+            )
+        }),
+        _ => {}
+    }
 }
 
 pub async fn get_books(
     State(pool): State<Pool<Sqlite>>,
     Query(params): Query<BookQueryParams>,
     headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<Vec<BookWithDetails>>, StatusCode> {
     debug!("Fetching books from database with params: {:?}", params);
 
     // Extract user_id from headers (optional for this endpoint)
     let current_user_id = extract_user_id_from_headers(&headers).ok();
 
+    if let Some(sort) = &params.sort
+        && !BOOK_SORTS.contains(&sort.as_str())
+    {
+        warn!("Invalid book sort: {}", sort);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let preferences = match current_user_id {
+        Some(user_id) => match get_user_preferences_query(&pool, user_id).await {
+            Ok(preferences) => preferences,
+            Err(e) => {
+                error!("Failed to fetch preferences of user {}: {}", user_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+        None => UserPreferences::default(),
+    };
+
     let books = match params.search {
         Some(search_term) => {
             debug!("Searching books with term: {}", search_term);
-            search_books_with_details_query(&pool, &search_term, current_user_id).await
+            search_books_with_details_query(&pool, library.id, &search_term, current_user_id).await
         }
         None => {
             debug!("Fetching all books");
-            get_all_books_with_details_query(&pool, current_user_id).await
+            get_all_books_with_details_query(&pool, library.id, current_user_id).await
         }
     };
 
     match books {
-        Ok(books) => {
+        Ok(mut books) => {
+            // Leave out books in genres the user has hidden
+            books.retain(|book| {
+                !book
+                    .genres
+                    .iter()
+                    .any(|genre| preferences.hidden_genres.contains(&genre.id))
+            });
+            sort_books(
+                &mut books,
+                params.sort.as_deref().unwrap_or(&preferences.default_sort),
+            );
+
             info!("Successfully retrieved {} books", books.len());
             debug!(
                 "Books retrieved: {:?}",
@@ -63,9 +133,148 @@ pub async fn get_books(
     }
 }
 
+#[derive(Deserialize, Debug)]
+pub struct RandomBookParams {
+    status_id: Option<i64>,
+    genre_id: Option<i64>,
+    tag_id: Option<i64>,
+    max_pages: Option<i64>,
+    series_starters: Option<bool>,
+    skip_last_author: Option<bool>,
+    weighted: Option<bool>,
+    // Comma-separated book ids to leave out, e.g. books already rolled
+    exclude: Option<String>,
+}
+
+/// Pick a random book from the current user's planned (TBR) status, or another status.
+/// With `weighted=true`, books that have had their status longer are more likely to be picked.
+///
+/// `series_starters=true` keeps standalones and the first book of each series. Series have
+/// no explicit ordering, so the first book is the one that was added to the library first.
+pub async fn get_random_book(
+    State(pool): State<Pool<Sqlite>>,
+    Query(params): Query<RandomBookParams>,
+    library: CurrentLibrary,
+    headers: HeaderMap,
+) -> Result<Json<BookWithDetails>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "Picking a random book for user {} with params: {:?}",
+        user_id, params
+    );
+
+    if params.max_pages.is_some_and(|pages| pages <= 0) {
+        warn!("Invalid max_pages: {:?}", params.max_pages);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let exclude = match params.exclude.as_deref() {
+        None | Some("") => Vec::new(),
+        Some(ids) => match ids
+            .split(',')
+            .map(|id| id.trim().parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(ids) => ids,
+            Err(_) => {
+                warn!("Invalid exclude list: {}", ids);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        },
+    };
+
+    // Without a status, pick from the to-be-read pile
+    let status_id = match params.status_id {
+        Some(status_id) => status_id,
+        None => match get_planned_status_id_query(&pool).await {
+            Ok(Some(status_id)) => status_id,
+            Ok(None) => {
+                warn!("No planned status to pick a random book from");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            Err(e) => {
+                error!("Failed to look up the planned status: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+    };
+
+    let filters = RandomBookFilters {
+        status_id,
+        genre_id: params.genre_id,
+        tag_id: params.tag_id,
+        max_pages: params.max_pages,
+        series_starters_only: params.series_starters.unwrap_or(false),
+        skip_last_finished_author: params.skip_last_author.unwrap_or(false),
+        exclude,
+    };
+
+    let candidates =
+        match get_random_book_candidates_query(&pool, library.id, user_id, &filters).await {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                error!("Failed to fetch random book candidates: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+    // Children are never picked a book they aren't allowed to see
+    let candidates = match fetch_hidden_book_ids(&pool, Some(user_id)).await {
+        Ok(hidden_book_ids) => candidates
+            .into_iter()
+            .filter(|candidate| !hidden_book_ids.contains(&candidate.id))
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            error!("Failed to fetch restricted books: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let picked = {
+        let mut rng = rand::thread_rng();
+        if params.weighted.unwrap_or(false) {
+            candidates
+                .choose_weighted(&mut rng, |c| c.days_on_status + 1.0)
+                .ok()
+        } else {
+            candidates.choose(&mut rng)
+        }
+    };
+
+    let Some(picked) = picked else {
+        warn!(
+            "No books match the random picker filters for user {}",
+            user_id
+        );
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    match get_book_details_query(&pool, library.id, picked.id, Some(user_id)).await {
+        Ok(Some(book)) => {
+            info!(
+                "Picked book {} out of {} candidates for user {}",
+                book.id,
+                candidates.len(),
+                user_id
+            );
+            Ok(Json(book))
+        }
+        Ok(None) => {
+            warn!("Picked book {} no longer exists", picked.id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch picked book {}: {}", picked.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn create_book(
     State(pool): State<Pool<Sqlite>>,
     headers: HeaderMap,
+    library: CurrentLibrary,
     Json(request): Json<CreateBookRequest>,
 ) -> Result<Json<Book>, StatusCode> {
     if request.title.trim().is_empty() {
@@ -74,6 +283,9 @@ pub async fn create_book(
     if request.author.trim().is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
+    if request.page_count.is_some_and(|pages| pages <= 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
     // Extract user_id from headers
     let user_id = extract_user_id_from_headers(&headers)?;
@@ -86,6 +298,7 @@ pub async fn create_book(
         title: request.title,
         author: request.author,
         series: request.series,
+        page_count: request.page_count,
         created_at: None,
         updated_at: None,
     };
@@ -93,7 +306,7 @@ pub async fn create_book(
     info!("Creating new book: {} for user: {}", book.title, user_id);
     debug!("Book details - Author: {}", book.author);
 
-    match create_book_query(&pool, book).await {
+    match create_book_query(&pool, library.id, book).await {
         Ok(created_book) => {
             info!("Successfully created book with ID: {}", created_book.id);
 
@@ -165,13 +378,14 @@ pub async fn get_book_details(
     State(pool): State<Pool<Sqlite>>,
     axum::extract::Path(id): axum::extract::Path<i64>,
     headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<BookWithDetails>, StatusCode> {
     debug!("Fetching book with details for ID: {}", id);
 
     // Extract user_id from headers (optional for this endpoint)
     let current_user_id = extract_user_id_from_headers(&headers).ok();
 
-    match get_book_details_query(&pool, id, current_user_id).await {
+    match get_book_details_query(&pool, library.id, id, current_user_id).await {
         Ok(Some(book)) => {
             info!("Found book with ID {}: '{}'", id, book.title);
             Ok(Json(book))
@@ -190,14 +404,19 @@ pub async fn get_book_details(
 pub async fn update_book(
     State(pool): State<Pool<Sqlite>>,
     Path(id): Path<i64>,
-    headers: HeaderMap,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
     Json(request): Json<UpdateBookRequest>,
 ) -> Result<Json<BookWithDetails>, StatusCode> {
-    // Extract user_id from headers
-    let user_id = extract_user_id_from_headers(&headers)?;
+    let user_id = current_user.id;
+    ensure_can_edit_book(&pool, &current_user, id).await?;
+
+    if request.page_count.is_some_and(|pages| pages <= 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
     // First, get the current book to preserve fields that aren't being updated
-    let current_book = match get_book_details_query(&pool, id, Some(user_id)).await {
+    let current_book = match get_book_details_query(&pool, library.id, id, Some(user_id)).await {
         Ok(Some(book)) => book,
         Ok(None) => {
             warn!("No book found with ID: {}", id);
@@ -217,12 +436,13 @@ pub async fn update_book(
         title: request.title.unwrap_or(current_book.title),
         author: request.author.unwrap_or(current_book.author),
         series: request.series.or(current_book.series),
+        page_count: request.page_count.or(current_book.page_count),
         created_at: current_book.created_at,
         updated_at: current_book.updated_at,
     };
 
     // Update the book in the database
-    let updated_book = match update_book_query(&pool, id, updated_book).await {
+    let updated_book = match update_book_query(&pool, library.id, id, updated_book).await {
         Ok(book) => book,
         Err(e) => {
             error!("Failed to update book: {}", e);
@@ -257,7 +477,7 @@ pub async fn update_book(
     }
 
     // Return the updated book with details
-    match get_book_details_query(&pool, id, Some(user_id)).await {
+    match get_book_details_query(&pool, library.id, id, Some(user_id)).await {
         Ok(Some(book_with_details)) => {
             info!(
                 "Successfully retrieved updated book with details for ID: {}",
@@ -278,16 +498,34 @@ pub async fn update_book(
 
 pub async fn delete_book(
     State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
     axum::extract::Path(id): axum::extract::Path<i64>,
 ) -> Result<StatusCode, StatusCode> {
     if id <= 0 {
         return Err(StatusCode::BAD_REQUEST);
     }
+    ensure_can_edit_book(&pool, &current_user, id).await?;
 
     info!("Deleting book with ID: {}", id);
 
-    match delete_book_query(&pool, id).await {
+    // Journal attachment files outlive their rows unless removed here
+    let attachment_keys = match get_book_attachment_keys_query(&pool, id).await {
+        Ok(keys) => keys,
+        Err(e) => {
+            error!("Failed to fetch journal attachments for book {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match delete_book_query(&pool, library.id, id).await {
         Ok(_) => {
+            for key in &attachment_keys {
+                if let Err(e) = storage.remove(key).await {
+                    warn!("Failed to remove stored file {}: {}", key, e);
+                }
+            }
             info!("Successfully deleted book with ID: {}", id);
             Ok(StatusCode::NO_CONTENT)
         }
@@ -305,18 +543,21 @@ pub async fn delete_book(
 pub async fn get_book_journals(
     State(pool): State<Pool<Sqlite>>,
     axum::extract::Path(id): axum::extract::Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<Vec<crate::models::JournalEntry>>, StatusCode> {
+    // Private entries are only returned to their author
+    let current_user_id = extract_user_id_from_headers(&headers).ok();
+
     debug!("Fetching journals for book with ID: {}", id);
 
     // First check if the book exists
-    let book_exists = sqlx::query!("SELECT id FROM books WHERE id = ?", id)
-        .fetch_optional(&pool)
-        .await;
+    let book_exists = book_in_library_query(&pool, id, library.id).await;
 
     match book_exists {
-        Ok(Some(_)) => {
+        Ok(true) => {
             // Book exists, now get its journals
-            match get_journals_by_book_id(&pool, id).await {
+            match get_journals_by_book_id(&pool, library.id, id, current_user_id).await {
                 Ok(journals) => {
                     info!("Found {} journals for book ID {}", journals.len(), id);
                     Ok(Json(journals))
@@ -327,7 +568,7 @@ pub async fn get_book_journals(
                 }
             }
         }
-        Ok(None) => {
+        Ok(false) => {
             warn!("Book with ID {} not found when fetching journals", id);
             Err(StatusCode::NOT_FOUND)
         }
@@ -342,23 +583,58 @@ pub async fn create_book_journal_entry(
     State(pool): State<Pool<Sqlite>>,
     axum::extract::Path(book_id): axum::extract::Path<i64>,
     headers: HeaderMap,
-    Json(mut journal): Json<crate::models::JournalEntry>,
+    Json(request): Json<CreateJournalRequest>,
 ) -> Result<Json<crate::models::JournalEntry>, StatusCode> {
     debug!("Creating journal for book ID: {}", book_id);
 
     // Extract user_id from headers
     let user_id = extract_user_id_from_headers(&headers)?;
 
+    // A book restricted for the user is treated as not found, as on its detail page
+    match fetch_hidden_book_ids(&pool, Some(user_id)).await {
+        Ok(hidden_book_ids) if hidden_book_ids.contains(&book_id) => {
+            warn!("Book {} is restricted for user {}", book_id, user_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Failed to fetch restricted books: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    let visibility = request
+        .visibility
+        .unwrap_or_else(|| "household".to_string());
+    if !is_valid_journal_visibility(&visibility) {
+        warn!("Invalid journal visibility: {}", visibility);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if has_inline_images(&request.content) {
+        warn!("Refusing inline image in new journal for book {}", book_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     // Set the book_id from the path parameter and user_id from header
-    journal.book_id = book_id;
-    journal.user_id = user_id;
+    let journal = crate::models::JournalEntry {
+        id: 0,
+        book_id,
+        user_id,
+        title: request.title,
+        content: request.content,
+        visibility,
+        created_at: None,
+        updated_at: None,
+        tags: Vec::new(),
+    };
+    let tag_ids = request.tags.unwrap_or_default();
 
     info!(
-        "Journal details - Title: '{}', Content: '{}', User ID: {}",
-        journal.title, journal.content, user_id
+        "Journal details - Title: '{}', Content: '{}', User ID: {}, Tags: {:?}",
+        journal.title, journal.content, user_id, tag_ids
     );
 
-    match create_journal_entry(&pool, journal).await {
+    match create_journal_entry(&pool, journal, &tag_ids).await {
         Ok(created_journal) => {
             info!(
                 "Successfully created journal with ID: {}",
@@ -366,6 +642,13 @@ pub async fn create_book_journal_entry(
             );
             Ok(Json(created_journal))
         }
+        Err(e) if is_foreign_key_violation(&e) => {
+            warn!(
+                "Journal for book {} references an unknown book or tag: {}",
+                book_id, e
+            );
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             error!("Failed to create journal: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -376,6 +659,7 @@ pub async fn create_book_journal_entry(
 #[derive(serde::Deserialize, Debug)]
 pub struct UpsertRatingRequest {
     rating: f64,
+    scores: Option<Vec<RatingScoreInput>>,
 }
 
 /// Upsert (create or update) a rating for a book by the current user
@@ -398,19 +682,60 @@ pub async fn upsert_rating(
         book_id, user_id, payload.rating
     );
 
-    // Validate rating is between 0 and 5
-    if payload.rating < 0.0 || payload.rating > 5.0 {
+    // Validate the overall rating is between 0 and 5 in half-star increments
+    if !is_on_scale(payload.rating, 0.0, 5.0, 0.5) {
         warn!("Invalid rating value: {}", payload.rating);
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    // Validate rating is in half-star increments (0, 0.5, 1.0, 1.5, etc.)
-    if (payload.rating * 2.0).fract() != 0.0 {
-        warn!("Rating must be in half-star increments: {}", payload.rating);
-        return Err(StatusCode::BAD_REQUEST);
+    // Validate each dimension score against that dimension's own scale
+    if let Some(scores) = &payload.scores {
+        let dimensions: HashMap<i64, RatingDimension> =
+            match get_all_rating_dimensions_query(&pool).await {
+                Ok(dimensions) => dimensions.into_iter().map(|d| (d.id, d)).collect(),
+                Err(e) => {
+                    error!("Failed to fetch rating dimensions: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+
+        let mut seen = HashSet::new();
+        for score in scores {
+            let Some(dimension) = dimensions.get(&score.dimension_id) else {
+                warn!("Unknown rating dimension: {}", score.dimension_id);
+                return Err(StatusCode::BAD_REQUEST);
+            };
+            if !seen.insert(score.dimension_id) {
+                warn!(
+                    "Duplicate score for rating dimension: {}",
+                    score.dimension_id
+                );
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            if !is_on_scale(
+                score.score,
+                dimension.min_value,
+                dimension.max_value,
+                dimension.increment,
+            ) {
+                warn!(
+                    "Score {} is not on the scale for dimension '{}'",
+                    score.score, dimension.name
+                );
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
     }
 
-    match upsert_rating_query(&pool, user_id, book_id, payload.rating).await {
+    match upsert_rating_query(
+        &pool,
+        user_id,
+        book_id,
+        payload.rating,
+        payload.scores.as_deref(),
+    )
+    .await
+    {
         Ok(_) => {
             info!(
                 "Successfully upserted rating for book {} by user {}",
@@ -507,10 +832,17 @@ pub async fn upsert_status(
         book_id, user_id, payload.status_id
     );
 
-    // Validate status_id is valid (0=UNREAD, 1=READ, 2=READING, 3=TBR, 99=DNF)
-    if ![0, 1, 2, 3, 99].contains(&payload.status_id) {
-        warn!("Invalid status_id value: {}", payload.status_id);
-        return Err(StatusCode::BAD_REQUEST);
+    // Validate status_id refers to a status in the status table
+    match status_exists_query(&pool, payload.status_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!("Invalid status_id value: {}", payload.status_id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Err(e) => {
+            error!("Failed to validate status_id {}: {}", payload.status_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
     }
 
     match upsert_status_query(&pool, user_id, book_id, payload.status_id).await {
@@ -0,0 +1,70 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{get_compatibility_matrix_query, get_taste_compatibility_query};
+use crate::models::compatibility::{CompatibilityMatrix, TasteCompatibility};
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+// GET /users/:id/compatibility/:other_id - Compare two users' ratings
+pub async fn get_taste_compatibility(
+    State(pool): State<Pool<Sqlite>>,
+    Path((user_id, other_user_id)): Path<(i64, i64)>,
+    library: CurrentLibrary,
+    headers: HeaderMap,
+) -> Result<Json<TasteCompatibility>, StatusCode> {
+    debug!(
+        "Fetching taste compatibility between users {} and {}",
+        user_id, other_user_id
+    );
+
+    if user_id == other_user_id {
+        warn!("Cannot compare user {} with themselves", user_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+    match get_taste_compatibility_query(&pool, library.id, user_id, other_user_id, viewer_id).await
+    {
+        Ok(Some(report)) => {
+            info!(
+                "Compared users {} and {} on {} shared books",
+                user_id, other_user_id, report.shared_count
+            );
+            Ok(Json(report))
+        }
+        Ok(None) => {
+            warn!(
+                "User {} or user {} not found in library for compatibility",
+                user_id, other_user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "Failed to compare users {} and {}: {}",
+                user_id, other_user_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /users/compatibility - Compatibility scores between every pair of library members
+pub async fn get_compatibility_matrix(
+    State(pool): State<Pool<Sqlite>>,
+    library: CurrentLibrary,
+) -> Result<Json<CompatibilityMatrix>, StatusCode> {
+    debug!("Fetching household compatibility matrix");
+
+    match get_compatibility_matrix_query(&pool, library.id).await {
+        Ok(matrix) => Ok(Json(matrix)),
+        Err(e) => {
+            error!("Failed to build compatibility matrix: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,6 +1,5 @@
 use axum::Json;
 use axum::extract::{Path, Query, State};
-use axum::http::HeaderMap;
 use reqwest::StatusCode;
 use serde::Deserialize;
 use sqlx::{Pool, Sqlite};
@@ -11,7 +10,7 @@ use crate::db::{
     get_genres_by_name_query, update_genre_query,
 };
 use crate::models::books::{CreateGenreRequest, Genre, UpdateGenreRequest};
-use crate::utils::extract_user_id_from_headers;
+use crate::utils::{CurrentLibrary, CurrentUser};
 
 #[derive(Deserialize, Debug)]
 pub struct GenreQueryParams {
@@ -21,17 +20,18 @@ pub struct GenreQueryParams {
 pub async fn get_genres(
     State(pool): State<Pool<Sqlite>>,
     Query(params): Query<GenreQueryParams>,
+    library: CurrentLibrary,
 ) -> Result<Json<Vec<Genre>>, StatusCode> {
     debug!("Fetching genres from database with params: {:?}", params);
 
     let genres = match params.name {
         Some(name_filter) => {
             debug!("Filtering genres by name: {}", name_filter);
-            get_genres_by_name_query(&pool, &name_filter).await
+            get_genres_by_name_query(&pool, library.id, &name_filter).await
         }
         None => {
             debug!("Fetching all genres");
-            get_all_genres_query(&pool).await
+            get_all_genres_query(&pool, library.id).await
         }
     };
 
@@ -46,17 +46,18 @@ pub async fn get_genres(
 
 pub async fn create_genre(
     State(pool): State<Pool<Sqlite>>,
-    headers: HeaderMap,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
     Json(genre): Json<CreateGenreRequest>,
 ) -> Result<Json<Genre>, StatusCode> {
     debug!("Creating new genre");
 
-    // Extract user_id from headers
-    let user_id = extract_user_id_from_headers(&headers)?;
+    current_user.ensure_admin()?;
+    let user_id = current_user.id;
 
     info!("Creating new genre: {} for user: {}", genre.name, user_id);
 
-    match create_genre_query(&pool, &genre, user_id).await {
+    match create_genre_query(&pool, library.id, &genre, user_id).await {
         Ok(genre) => Ok(Json(genre)),
         Err(e) => {
             error!("Failed to create genre: {}", e);
@@ -68,9 +69,10 @@ pub async fn create_genre(
 pub async fn get_genre_by_id(
     State(pool): State<Pool<Sqlite>>,
     Path(id): Path<i64>,
+    library: CurrentLibrary,
 ) -> Result<Json<Genre>, StatusCode> {
     debug!("Fetching genre with id: {}", id);
-    match get_genre_by_id_query(&pool, id).await {
+    match get_genre_by_id_query(&pool, library.id, id).await {
         Ok(Some(genre)) => Ok(Json(genre)),
         Ok(None) => {
             warn!("No genre found with id: {}", id);
@@ -86,17 +88,20 @@ pub async fn get_genre_by_id(
 pub async fn update_genre(
     State(pool): State<Pool<Sqlite>>,
     Path(id): Path<i64>,
-    headers: HeaderMap,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
     Json(genre): Json<UpdateGenreRequest>,
 ) -> Result<Json<Genre>, StatusCode> {
     debug!("Updating genre with id: {}", id);
 
-    // Extract user_id from headers
-    let _user_id = extract_user_id_from_headers(&headers)?;
+    current_user.ensure_admin()?;
 
-    info!("Updating genre with id: {} for user: {}", id, _user_id);
+    info!(
+        "Updating genre with id: {} for user: {}",
+        id, current_user.id
+    );
 
-    match update_genre_query(&pool, id, &genre).await {
+    match update_genre_query(&pool, library.id, id, &genre).await {
         Ok(genre) => Ok(Json(genre)),
         Err(sqlx::Error::RowNotFound) => {
             warn!("No genre found with id: {}", id);
@@ -112,13 +117,20 @@ pub async fn update_genre(
 pub async fn delete_genre(
     State(pool): State<Pool<Sqlite>>,
     Path(id): Path<i64>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
 ) -> Result<Json<()>, StatusCode> {
     debug!("Deleting genre with id: {}", id);
-    match delete_genre_query(&pool, id).await {
+    current_user.ensure_admin()?;
+    match delete_genre_query(&pool, library.id, id).await {
         Ok(()) => {
             info!("Successfully deleted genre with id: {}", id);
             Ok(Json(()))
         }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("No genre found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
         Err(e) => {
             error!("Failed to delete genre with id {}: {}", id, e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -0,0 +1,259 @@
+use axum::Json;
+use axum::extract::{Extension, Multipart, Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    create_journal_attachment_query, delete_journal_attachment_query,
+    get_journal_attachment_by_key_query, get_journal_attachments_query, get_journal_by_id,
+};
+use crate::models::journal_attachments::{ATTACHMENT_KEY_PREFIX, JournalAttachment};
+use crate::utils::{
+    CurrentLibrary, CurrentUser, FileStorage, ensure_can_edit_journal, sniff_image_type,
+};
+
+// Largest single file accepted, well under the request body limit
+const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+// Someone else's private entry, or one on a book restricted for the current user, is
+// treated as not found
+async fn ensure_journal_visible(
+    pool: &Pool<Sqlite>,
+    library: CurrentLibrary,
+    current_user: &CurrentUser,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<(), StatusCode> {
+    match get_journal_by_id(pool, library.id, journal_id, Some(current_user.id)).await {
+        Ok(Some(journal)) if journal.book_id == book_id => Ok(()),
+        Ok(_) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_journal_attachments(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, journal_id)): Path<(i64, i64)>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<JournalAttachment>>, StatusCode> {
+    debug!(
+        "Fetching attachments for journal {} in book {}",
+        journal_id, book_id
+    );
+
+    ensure_journal_visible(&pool, library, &current_user, book_id, journal_id).await?;
+
+    match get_journal_attachments_query(&pool, library.id, book_id, journal_id).await {
+        Ok(attachments) => {
+            info!(
+                "Found {} attachments for journal {}",
+                attachments.len(),
+                journal_id
+            );
+            Ok(Json(attachments))
+        }
+        Err(e) => {
+            error!(
+                "Failed to fetch attachments for journal {}: {}",
+                journal_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Upload an image as a multipart `file` field.
+///
+/// The image type is detected from the file's bytes; the declared content type is ignored.
+/// Embed the returned `url` in the journal content to show the image.
+pub async fn upload_journal_attachment(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path((book_id, journal_id)): Path<(i64, i64)>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+    mut multipart: Multipart,
+) -> Result<Json<JournalAttachment>, StatusCode> {
+    let user_id = current_user.id;
+
+    info!(
+        "User {} uploading attachment to journal {} in book {}",
+        user_id, journal_id, book_id
+    );
+
+    ensure_journal_visible(&pool, library, &current_user, book_id, journal_id).await?;
+    ensure_can_edit_journal(&pool, &current_user, journal_id).await?;
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        warn!("Invalid multipart upload: {}", e);
+        e.status()
+    })? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or("attachment").to_string();
+        let bytes = field.bytes().await.map_err(|e| {
+            warn!("Failed to read uploaded file: {}", e);
+            e.status()
+        })?;
+        upload = Some((file_name, bytes));
+        break;
+    }
+
+    let Some((file_name, bytes)) = upload else {
+        warn!("Attachment upload has no file field");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    if bytes.is_empty() {
+        warn!("Uploaded file '{}' is empty", file_name);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        warn!(
+            "Uploaded file '{}' is {} bytes, over the {} byte limit",
+            file_name,
+            bytes.len(),
+            MAX_ATTACHMENT_BYTES
+        );
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let Some((content_type, extension)) = sniff_image_type(&bytes) else {
+        warn!("Uploaded file '{}' is not a supported image", file_name);
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    };
+
+    let storage_key = FileStorage::new_key("journals", extension);
+    if let Err(e) = storage.write(&storage_key, &bytes).await {
+        error!("Failed to store attachment '{}': {}", file_name, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match create_journal_attachment_query(
+        &pool,
+        journal_id,
+        user_id,
+        &file_name,
+        content_type,
+        bytes.len() as i64,
+        &storage_key,
+    )
+    .await
+    {
+        Ok(attachment) => {
+            info!(
+                "Stored attachment {} for journal {}",
+                attachment.id, journal_id
+            );
+            Ok(Json(attachment))
+        }
+        Err(e) => {
+            error!("Failed to record attachment '{}': {}", file_name, e);
+            if let Err(e) = storage.remove(&storage_key).await {
+                warn!("Failed to clean up stored file {}: {}", storage_key, e);
+            }
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /attachments/:file - Serves the file itself. No user header is required so the URL
+// works as an <img> src; the random file name is what keeps it private.
+pub async fn download_journal_attachment(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(file): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    debug!("Downloading attachment {}", file);
+
+    if !file.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') {
+        warn!("Invalid attachment file name requested");
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let storage_key = format!("{}{}", ATTACHMENT_KEY_PREFIX, file);
+    let attachment = match get_journal_attachment_by_key_query(&pool, &storage_key).await {
+        Ok(Some(attachment)) => attachment,
+        Ok(None) => {
+            warn!("No attachment stored as {}", file);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch attachment {}: {}", file, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match storage.read(&attachment.storage_key).await {
+        Ok(bytes) => Ok((
+            [
+                (header::CONTENT_TYPE, attachment.content_type),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+                (
+                    header::CACHE_CONTROL,
+                    "private, max-age=31536000, immutable".to_string(),
+                ),
+            ],
+            bytes,
+        )),
+        Err(e) => {
+            error!(
+                "Attachment {} is missing from storage ({}): {}",
+                attachment.id, attachment.storage_key, e
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+pub async fn delete_journal_attachment(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path((book_id, journal_id, attachment_id)): Path<(i64, i64, i64)>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+) -> Result<StatusCode, StatusCode> {
+    info!(
+        "Deleting attachment {} of journal {} in book {}",
+        attachment_id, journal_id, book_id
+    );
+
+    ensure_journal_visible(&pool, library, &current_user, book_id, journal_id).await?;
+    ensure_can_edit_journal(&pool, &current_user, journal_id).await?;
+
+    match delete_journal_attachment_query(&pool, book_id, journal_id, attachment_id).await {
+        Ok(storage_key) => {
+            if let Err(e) = storage.remove(&storage_key).await {
+                warn!("Failed to remove stored file {}: {}", storage_key, e);
+            }
+            info!("Successfully deleted attachment {}", attachment_id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No attachment {} found on journal {} in book {}",
+                attachment_id, journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete attachment {}: {}", attachment_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
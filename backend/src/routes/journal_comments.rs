@@ -0,0 +1,153 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    create_journal_comment_query, delete_journal_comment_query, get_journal_by_id,
+    get_journal_comments_query, journal_comment_exists_query,
+};
+use crate::models::journal_comments::{CreateJournalCommentRequest, JournalComment};
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+// Someone else's private entry is treated as not found
+async fn ensure_journal_visible(
+    pool: &Pool<Sqlite>,
+    library: CurrentLibrary,
+    journal_id: i64,
+    current_user_id: Option<i64>,
+) -> Result<(), StatusCode> {
+    match get_journal_by_id(pool, library.id, journal_id, current_user_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            warn!("No journal entry found with ID: {}", journal_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch journal entry {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /journals/:id/comments - Comment threads on a journal entry
+pub async fn get_journal_comments(
+    State(pool): State<Pool<Sqlite>>,
+    Path(journal_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<JournalComment>>, StatusCode> {
+    let current_user_id = extract_user_id_from_headers(&headers).ok();
+
+    debug!("Fetching comments on journal {}", journal_id);
+
+    ensure_journal_visible(&pool, library, journal_id, current_user_id).await?;
+
+    match get_journal_comments_query(&pool, journal_id).await {
+        Ok(comments) => {
+            info!(
+                "Found {} comment threads on journal {}",
+                comments.len(),
+                journal_id
+            );
+            Ok(Json(comments))
+        }
+        Err(e) => {
+            error!("Failed to fetch comments on journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /journals/:id/comments - Comment on a journal entry, or reply to a comment on it.
+// Returns the updated comment threads.
+pub async fn create_journal_comment(
+    State(pool): State<Pool<Sqlite>>,
+    Path(journal_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    Json(request): Json<CreateJournalCommentRequest>,
+) -> Result<Json<Vec<JournalComment>>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "User {} commenting on journal {}: {:?}",
+        user_id, journal_id, request
+    );
+
+    let content = request.content.trim();
+    if content.is_empty() {
+        warn!("Comment content is required");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_journal_visible(&pool, library, journal_id, Some(user_id)).await?;
+
+    if let Some(parent_id) = request.parent_id {
+        match journal_comment_exists_query(&pool, journal_id, parent_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    "Comment {} to reply to is not on journal {}",
+                    parent_id, journal_id
+                );
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            Err(e) => {
+                error!("Failed to check comment {}: {}", parent_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
+    if let Err(e) =
+        create_journal_comment_query(&pool, journal_id, user_id, content, request.parent_id).await
+    {
+        error!("Failed to create comment on journal {}: {}", journal_id, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match get_journal_comments_query(&pool, journal_id).await {
+        Ok(comments) => Ok(Json(comments)),
+        Err(e) => {
+            error!("Failed to fetch comments on journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Delete a comment and its replies.
+///
+/// Allowed for the comment's author and for the author of the journal entry it's on.
+pub async fn delete_journal_comment(
+    State(pool): State<Pool<Sqlite>>,
+    Path((journal_id, comment_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "User {} deleting comment {} on journal {}",
+        user_id, comment_id, journal_id
+    );
+
+    match delete_journal_comment_query(&pool, journal_id, comment_id, user_id).await {
+        Ok(()) => {
+            info!("Successfully deleted comment with ID: {}", comment_id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No comment {} on journal {} that user {} can delete",
+                comment_id, journal_id, user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete comment {}: {}", comment_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
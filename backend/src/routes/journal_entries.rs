@@ -1,31 +1,122 @@
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::HeaderMap;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::NaiveDate;
 use reqwest::StatusCode;
 use sqlx::{Pool, Sqlite};
 use tracing::{debug, error, info, warn};
 
+use crate::db::journal_attachment_queries::{get_journal_attachment_keys_query, has_inline_images};
 use crate::db::{
-    get_all_journals, get_journal_by_id, update_journal_entry as db_update_journal_entry,
+    delete_journal_entry as db_delete_journal_entry, get_all_journals, get_journal_by_id,
+    update_journal_entry as db_update_journal_entry,
 };
-use crate::models::{JournalEntry, UpdateJournalRequest};
+use crate::models::{
+    JournalEntry, JournalFeedPage, JournalFeedParams, UpdateJournalRequest,
+    is_valid_journal_visibility,
+};
+use crate::utils::{
+    CurrentLibrary, CurrentUser, FileStorage, ensure_can_edit_journal,
+    extract_user_id_from_headers, is_foreign_key_violation,
+};
+
+const DEFAULT_JOURNAL_FEED_LIMIT: i64 = 20;
+const MAX_JOURNAL_FEED_LIMIT: i64 = 100;
+
+// Cursors are opaque to clients: the created_at and id of the last entry on a page
+fn encode_journal_cursor(created_at: &str, id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{created_at}|{id}"))
+}
+
+fn decode_journal_cursor(cursor: &str) -> Option<(String, i64)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (created_at, id) = decoded.rsplit_once('|')?;
+    Some((created_at.to_string(), id.parse().ok()?))
+}
 
+fn is_valid_date(date: &Option<String>) -> bool {
+    date.as_deref()
+        .is_none_or(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok())
+}
+
+/// The journal feed, newest first, one page at a time.
+///
+/// Filter by `user_id`, `book_id`, `tag_id`, a `from`/`to` date range, and `q` (title and
+/// content). Pass the returned `next_cursor` as `cursor` to get the following page.
 pub async fn get_journal_entries_query(
     State(pool): State<Pool<Sqlite>>,
-) -> Result<Json<Vec<JournalEntry>>, StatusCode> {
-    debug!("Fetching all journal entries from database");
-
-    match get_all_journals(&pool).await {
-        Ok(journals) => {
-            info!("Successfully retrieved {} journal entries", journals.len());
-            debug!(
-                "Journal entries retrieved: {:?}",
-                journals.iter().map(|j| &j.title).collect::<Vec<_>>()
+    Query(params): Query<JournalFeedParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<JournalFeedPage>, StatusCode> {
+    let current_user_id = extract_user_id_from_headers(&headers).ok();
+
+    debug!(
+        "Fetching journal feed with params: {:?} for user {:?}",
+        params, current_user_id
+    );
+
+    let limit = params.limit.unwrap_or(DEFAULT_JOURNAL_FEED_LIMIT);
+    if limit <= 0 || limit > MAX_JOURNAL_FEED_LIMIT {
+        warn!("Invalid journal feed limit: {}", limit);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_date(&params.from) || !is_valid_date(&params.to) {
+        warn!(
+            "Invalid journal feed date range: {:?} to {:?}",
+            params.from, params.to
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let cursor = match params.cursor.as_deref().map(decode_journal_cursor) {
+        Some(Some(cursor)) => Some(cursor),
+        Some(None) => {
+            warn!("Invalid journal feed cursor: {:?}", params.cursor);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        None => None,
+    };
+
+    // Fetch one extra entry to find out whether there's another page
+    match get_all_journals(
+        &pool,
+        library.id,
+        &params,
+        current_user_id,
+        cursor
+            .as_ref()
+            .map(|(created_at, id)| (created_at.as_str(), *id)),
+        limit + 1,
+    )
+    .await
+    {
+        Ok(mut journals) => {
+            let next_cursor = if journals.len() as i64 > limit {
+                journals.truncate(limit as usize);
+                journals.last().map(|item| {
+                    encode_journal_cursor(
+                        item.journal.created_at.as_deref().unwrap_or_default(),
+                        item.journal.id,
+                    )
+                })
+            } else {
+                None
+            };
+
+            info!(
+                "Successfully retrieved {} journal entries (more: {})",
+                journals.len(),
+                next_cursor.is_some()
             );
-            Ok(Json(journals))
+            Ok(Json(JournalFeedPage {
+                journals,
+                next_cursor,
+            }))
         }
         Err(e) => {
             error!("Failed to fetch journal entries: {}", e);
-            warn!("Returning empty journal list due to database error");
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
@@ -34,17 +125,21 @@ pub async fn get_journal_entries_query(
 pub async fn get_journal_entry_by_id_query(
     State(pool): State<Pool<Sqlite>>,
     axum::extract::Path(id): axum::extract::Path<i64>,
-) -> Result<Json<Option<JournalEntry>>, StatusCode> {
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<JournalEntry>, StatusCode> {
+    let current_user_id = extract_user_id_from_headers(&headers).ok();
+
     debug!("Fetching journal entry with ID: {}", id);
 
-    match get_journal_by_id(&pool, id).await {
+    match get_journal_by_id(&pool, library.id, id, current_user_id).await {
         Ok(Some(journal)) => {
             info!("Found journal entry with ID {}: '{}'", id, journal.title);
-            Ok(Json(Some(journal)))
+            Ok(Json(journal))
         }
         Ok(None) => {
             warn!("No journal entry found with ID: {}", id);
-            Ok(Json(None))
+            Err(StatusCode::NOT_FOUND)
         }
         Err(e) => {
             error!("Failed to fetch journal entry by ID {}: {}", id, e);
@@ -53,9 +148,36 @@ pub async fn get_journal_entry_by_id_query(
     }
 }
 
+// Someone else's private entry, or one on a book restricted for the current user, is
+// treated as not found
+async fn get_visible_journal(
+    pool: &Pool<Sqlite>,
+    library: CurrentLibrary,
+    current_user: &CurrentUser,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<JournalEntry, StatusCode> {
+    match get_journal_by_id(pool, library.id, journal_id, Some(current_user.id)).await {
+        Ok(Some(journal)) if journal.book_id == book_id => Ok(journal),
+        Ok(_) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn update_journal_entry(
     State(pool): State<Pool<Sqlite>>,
     Path((book_id, journal_id)): Path<(i64, i64)>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
     Json(request): Json<UpdateJournalRequest>,
 ) -> Result<Json<JournalEntry>, StatusCode> {
     info!(
@@ -63,7 +185,45 @@ pub async fn update_journal_entry(
         journal_id, book_id
     );
 
-    match db_update_journal_entry(&pool, journal_id, request.title, request.content).await {
+    let journal = get_visible_journal(&pool, library, &current_user, book_id, journal_id).await?;
+    ensure_can_edit_journal(&pool, &current_user, journal_id).await?;
+
+    // Only the author decides who can read an entry
+    if request
+        .visibility
+        .as_ref()
+        .is_some_and(|visibility| *visibility != journal.visibility)
+        && journal.user_id != current_user.id
+    {
+        warn!(
+            "User {} tried to change the visibility of journal entry {} by user {}",
+            current_user.id, journal_id, journal.user_id
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if let Some(visibility) = &request.visibility
+        && !is_valid_journal_visibility(visibility)
+    {
+        warn!("Invalid journal visibility: {}", visibility);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if request.content.as_deref().is_some_and(has_inline_images) {
+        warn!("Refusing inline image in journal entry {}", journal_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match db_update_journal_entry(
+        &pool,
+        book_id,
+        journal_id,
+        request.title,
+        request.content,
+        request.visibility,
+        request.tags.as_deref(),
+    )
+    .await
+    {
         Ok(updated_journal) => {
             info!(
                 "Successfully updated journal entry with ID: {}",
@@ -71,9 +231,74 @@ pub async fn update_journal_entry(
             );
             Ok(Json(updated_journal))
         }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) if is_foreign_key_violation(&e) => {
+            warn!(
+                "Journal entry {} update references an unknown tag",
+                journal_id
+            );
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             error!("Failed to update journal entry: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
+
+pub async fn delete_journal_entry(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path((book_id, journal_id)): Path<(i64, i64)>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+) -> Result<StatusCode, StatusCode> {
+    info!(
+        "Deleting journal entry with ID: {} in book: {}",
+        journal_id, book_id
+    );
+
+    get_visible_journal(&pool, library, &current_user, book_id, journal_id).await?;
+    ensure_can_edit_journal(&pool, &current_user, journal_id).await?;
+
+    // Attachment rows cascade with the entry, but their files have to be removed separately
+    let attachment_keys = match get_journal_attachment_keys_query(&pool, journal_id).await {
+        Ok(keys) => keys,
+        Err(e) => {
+            error!(
+                "Failed to fetch attachments for journal {}: {}",
+                journal_id, e
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match db_delete_journal_entry(&pool, book_id, journal_id).await {
+        Ok(()) => {
+            for key in &attachment_keys {
+                if let Err(e) = storage.remove(key).await {
+                    warn!("Failed to remove stored file {}: {}", key, e);
+                }
+            }
+            info!("Successfully deleted journal entry with ID: {}", journal_id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete journal entry: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
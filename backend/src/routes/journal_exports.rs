@@ -0,0 +1,194 @@
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderMap, header};
+use axum::response::IntoResponse;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    get_export_book_title_query, get_export_user_name_query, get_journal_export_entries_query,
+};
+use crate::models::journal_exports::{JournalExportEntry, JournalExportParams};
+use crate::utils::journal_export::{build_epub_export, build_markdown_export, slugify};
+use crate::utils::{CurrentLibrary, FileStorage, extract_user_id_from_headers};
+
+enum ExportFormat {
+    Markdown,
+    Epub,
+}
+
+fn parse_export_format(params: &JournalExportParams) -> Result<ExportFormat, StatusCode> {
+    match params.format.as_deref() {
+        None | Some("markdown") => Ok(ExportFormat::Markdown),
+        Some("epub") => Ok(ExportFormat::Epub),
+        Some(other) => {
+            warn!("Invalid journal export format: {}", other);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+// Read the attached files to bundle, keyed by attachment id. Files missing from
+// storage are left out of the export rather than failing it.
+async fn read_attachment_files(
+    storage: &FileStorage,
+    entries: &[JournalExportEntry],
+) -> HashMap<i64, Vec<u8>> {
+    let mut files = HashMap::new();
+    for attachment in entries.iter().flat_map(|entry| &entry.attachments) {
+        match storage.read(&attachment.storage_key).await {
+            Ok(bytes) => {
+                files.insert(attachment.id, bytes);
+            }
+            Err(e) => warn!(
+                "Leaving attachment {} out of the export, it is missing from storage ({}): {}",
+                attachment.id, attachment.storage_key, e
+            ),
+        }
+    }
+    files
+}
+
+async fn build_export(
+    storage: &FileStorage,
+    format: ExportFormat,
+    title: &str,
+    entries: &[JournalExportEntry],
+) -> Result<impl IntoResponse + use<>, StatusCode> {
+    let files = read_attachment_files(storage, entries).await;
+
+    let (archive, content_type, extension) = match format {
+        ExportFormat::Markdown => (
+            build_markdown_export(entries, &files),
+            "application/zip",
+            "zip",
+        ),
+        ExportFormat::Epub => (
+            build_epub_export(title, entries, &files),
+            "application/epub+zip",
+            "epub",
+        ),
+    };
+
+    match archive {
+        Ok(bytes) => Ok((
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}.{}\"", slugify(title), extension),
+                ),
+            ],
+            bytes,
+        )),
+        Err(e) => {
+            error!("Failed to build journal export: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /books/:id/journals/export - Download a book's journal entries
+// as a zip of Markdown files (`format=markdown`, the default) or an EPUB (`format=epub`)
+pub async fn export_book_journals(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(book_id): Path<i64>,
+    Query(params): Query<JournalExportParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<impl IntoResponse, StatusCode> {
+    debug!(
+        "Exporting journals for book {} with params: {:?}",
+        book_id, params
+    );
+
+    let format = parse_export_format(&params)?;
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    let book_title = match get_export_book_title_query(&pool, library.id, book_id).await {
+        Ok(Some(title)) => title,
+        Ok(None) => {
+            warn!("No book found with ID: {}", book_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch book {}: {}", book_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let entries =
+        match get_journal_export_entries_query(&pool, library.id, Some(book_id), None, viewer_id)
+            .await
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to load journals of book {}: {}", book_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+    info!(
+        "Exporting {} journal entries of book {}",
+        entries.len(),
+        book_id
+    );
+    build_export(&storage, format, &format!("{book_title} journal"), &entries).await
+}
+
+// GET /users/:id/journals/export - Download every journal entry a user has written,
+// as a zip of Markdown files or an EPUB "reading diary"
+pub async fn export_user_journals(
+    State(pool): State<Pool<Sqlite>>,
+    Extension(storage): Extension<FileStorage>,
+    Path(user_id): Path<i64>,
+    Query(params): Query<JournalExportParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<impl IntoResponse, StatusCode> {
+    debug!(
+        "Exporting journals of user {} with params: {:?}",
+        user_id, params
+    );
+
+    let format = parse_export_format(&params)?;
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    let user_name = match get_export_user_name_query(&pool, user_id).await {
+        Ok(Some(name)) => name,
+        Ok(None) => {
+            warn!("No user found with ID: {}", user_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch user {}: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let entries =
+        match get_journal_export_entries_query(&pool, library.id, None, Some(user_id), viewer_id)
+            .await
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to load journals of user {}: {}", user_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+    info!(
+        "Exporting {} journal entries of user {}",
+        entries.len(),
+        user_id
+    );
+    build_export(
+        &storage,
+        format,
+        &format!("{user_name}'s reading diary"),
+        &entries,
+    )
+    .await
+}
@@ -0,0 +1,133 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    add_journal_reaction_query, get_journal_by_id, get_journal_reactions_query,
+    remove_journal_reaction_query,
+};
+use crate::models::journal_reactions::{
+    JournalReactionRequest, JournalReactionSummary, is_valid_reaction_emoji,
+};
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+// Someone else's private entry is treated as not found
+async fn ensure_journal_visible(
+    pool: &Pool<Sqlite>,
+    library: CurrentLibrary,
+    journal_id: i64,
+    current_user_id: Option<i64>,
+) -> Result<(), StatusCode> {
+    match get_journal_by_id(pool, library.id, journal_id, current_user_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            warn!("No journal entry found with ID: {}", journal_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch journal entry {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn reaction_summaries(
+    pool: &Pool<Sqlite>,
+    journal_id: i64,
+) -> Result<Json<Vec<JournalReactionSummary>>, StatusCode> {
+    match get_journal_reactions_query(pool, journal_id).await {
+        Ok(reactions) => Ok(Json(reactions)),
+        Err(e) => {
+            error!("Failed to fetch reactions on journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /journals/:id/reactions - Reactions on a journal entry, grouped by emoji
+pub async fn get_journal_reactions(
+    State(pool): State<Pool<Sqlite>>,
+    Path(journal_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<JournalReactionSummary>>, StatusCode> {
+    let current_user_id = extract_user_id_from_headers(&headers).ok();
+
+    debug!("Fetching reactions on journal {}", journal_id);
+
+    ensure_journal_visible(&pool, library, journal_id, current_user_id).await?;
+    reaction_summaries(&pool, journal_id).await
+}
+
+// POST /journals/:id/reactions - React to a journal entry as the current user
+pub async fn add_journal_reaction(
+    State(pool): State<Pool<Sqlite>>,
+    Path(journal_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    Json(request): Json<JournalReactionRequest>,
+) -> Result<Json<Vec<JournalReactionSummary>>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "User {} reacting {} to journal {}",
+        user_id, request.emoji, journal_id
+    );
+
+    let emoji = request.emoji.trim();
+    if !is_valid_reaction_emoji(emoji) {
+        warn!("Invalid reaction emoji: {:?}", request.emoji);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_journal_visible(&pool, library, journal_id, Some(user_id)).await?;
+
+    if let Err(e) = add_journal_reaction_query(&pool, journal_id, user_id, emoji).await {
+        error!("Failed to add reaction to journal {}: {}", journal_id, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    info!("User {} reacted to journal {}", user_id, journal_id);
+    reaction_summaries(&pool, journal_id).await
+}
+
+// DELETE /journals/:id/reactions/:emoji - Take back the current user's reaction
+pub async fn remove_journal_reaction(
+    State(pool): State<Pool<Sqlite>>,
+    Path((journal_id, emoji)): Path<(i64, String)>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<JournalReactionSummary>>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "User {} removing reaction {} from journal {}",
+        user_id, emoji, journal_id
+    );
+
+    match remove_journal_reaction_query(&pool, journal_id, user_id, &emoji).await {
+        Ok(()) => {
+            info!(
+                "User {} removed reaction from journal {}",
+                user_id, journal_id
+            );
+            reaction_summaries(&pool, journal_id).await
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "User {} has no {} reaction on journal {}",
+                user_id, emoji, journal_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "Failed to remove reaction from journal {}: {}",
+                journal_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    get_journal_by_id, get_journal_revision_query, get_journal_revisions_query,
+    update_journal_entry as db_update_journal_entry,
+};
+use crate::models::JournalEntry;
+use crate::models::journal_revisions::{
+    JournalRevision, JournalRevisionDiff, JournalRevisionDiffParams,
+};
+use crate::utils::{
+    CurrentLibrary, CurrentUser, diff_lines, ensure_can_edit_journal, extract_user_id_from_headers,
+};
+
+// Finds the entry as the current user sees it, so revisions of someone else's
+// private entry are treated as not found
+async fn fetch_visible_journal(
+    pool: &Pool<Sqlite>,
+    headers: &HeaderMap,
+    library: CurrentLibrary,
+    book_id: i64,
+    journal_id: i64,
+) -> Result<JournalEntry, StatusCode> {
+    let current_user_id = extract_user_id_from_headers(headers).ok();
+
+    match get_journal_by_id(pool, library.id, journal_id, current_user_id).await {
+        Ok(Some(journal)) if journal.book_id == book_id => Ok(journal),
+        Ok(_) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn fetch_revision(
+    pool: &Pool<Sqlite>,
+    library: CurrentLibrary,
+    book_id: i64,
+    journal_id: i64,
+    revision_id: i64,
+) -> Result<JournalRevision, StatusCode> {
+    match get_journal_revision_query(pool, library.id, book_id, journal_id, revision_id).await {
+        Ok(Some(revision)) => Ok(revision),
+        Ok(None) => {
+            warn!(
+                "No revision {} of journal {} found in book {}",
+                revision_id, journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch revision {}: {}", revision_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /books/:book_id/journals/:journal_id/revisions - Earlier versions, newest first
+pub async fn get_journal_revisions(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, journal_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<JournalRevision>>, StatusCode> {
+    debug!(
+        "Fetching revisions of journal {} in book {}",
+        journal_id, book_id
+    );
+
+    fetch_visible_journal(&pool, &headers, library, book_id, journal_id).await?;
+
+    match get_journal_revisions_query(&pool, library.id, book_id, journal_id).await {
+        Ok(revisions) => {
+            info!(
+                "Found {} revisions of journal {}",
+                revisions.len(),
+                journal_id
+            );
+            Ok(Json(revisions))
+        }
+        Err(e) => {
+            error!("Failed to fetch revisions of journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /books/:book_id/journals/:journal_id/revisions/:revision_id
+pub async fn get_journal_revision(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, journal_id, revision_id)): Path<(i64, i64, i64)>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<JournalRevision>, StatusCode> {
+    debug!(
+        "Fetching revision {} of journal {} in book {}",
+        revision_id, journal_id, book_id
+    );
+
+    fetch_visible_journal(&pool, &headers, library, book_id, journal_id).await?;
+
+    fetch_revision(&pool, library, book_id, journal_id, revision_id)
+        .await
+        .map(Json)
+}
+
+/// Compare two versions of a journal entry line by line.
+///
+/// `from` is a revision id; `to` is another revision id, or the current entry when omitted.
+pub async fn get_journal_revision_diff(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, journal_id)): Path<(i64, i64)>,
+    Query(params): Query<JournalRevisionDiffParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<JournalRevisionDiff>, StatusCode> {
+    debug!(
+        "Comparing revisions of journal {} in book {}: {:?}",
+        journal_id, book_id, params
+    );
+
+    let journal = fetch_visible_journal(&pool, &headers, library, book_id, journal_id).await?;
+    let from = fetch_revision(&pool, library, book_id, journal_id, params.from).await?;
+    let (to_title, to_content) = match params.to {
+        Some(to) => {
+            let revision = fetch_revision(&pool, library, book_id, journal_id, to).await?;
+            (revision.title, revision.content)
+        }
+        None => (journal.title, journal.content),
+    };
+
+    let lines = diff_lines(&from.content, &to_content);
+    let diff = JournalRevisionDiff {
+        from: params.from,
+        to: params.to,
+        from_title: from.title,
+        to_title,
+        added: lines.iter().filter(|l| l.kind == "added").count() as i64,
+        removed: lines.iter().filter(|l| l.kind == "removed").count() as i64,
+        lines,
+    };
+
+    info!(
+        "Journal {} diff: {} lines added, {} removed",
+        journal_id, diff.added, diff.removed
+    );
+    Ok(Json(diff))
+}
+
+/// Bring back an earlier version of a journal entry.
+///
+/// The version being replaced is kept as a new revision, so a restore can be undone too.
+pub async fn restore_journal_revision(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, journal_id, revision_id)): Path<(i64, i64, i64)>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    current_user: CurrentUser,
+) -> Result<Json<JournalEntry>, StatusCode> {
+    info!(
+        "Restoring revision {} of journal {} in book {}",
+        revision_id, journal_id, book_id
+    );
+
+    fetch_visible_journal(&pool, &headers, library, book_id, journal_id).await?;
+    ensure_can_edit_journal(&pool, &current_user, journal_id).await?;
+    let revision = fetch_revision(&pool, library, book_id, journal_id, revision_id).await?;
+
+    match db_update_journal_entry(
+        &pool,
+        book_id,
+        journal_id,
+        Some(revision.title),
+        Some(revision.content),
+        None,
+        None,
+    )
+    .await
+    {
+        Ok(journal) => {
+            info!(
+                "Restored journal {} to revision {}",
+                journal_id, revision_id
+            );
+            Ok(Json(journal))
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No journal entry with ID {} found in book {}",
+                journal_id, book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to restore journal {}: {}", journal_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
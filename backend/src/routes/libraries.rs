@@ -0,0 +1,120 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    add_library_member_query, create_library_query, get_libraries_for_user_query,
+    is_library_member_query, remove_library_member_query,
+};
+use crate::models::libraries::{AddLibraryMemberRequest, CreateLibraryRequest, Library};
+use crate::utils::{CurrentUser, is_foreign_key_violation};
+
+// GET /libraries - The libraries the current user belongs to
+pub async fn get_libraries(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+) -> Result<Json<Vec<Library>>, StatusCode> {
+    debug!("Fetching libraries for user {}", current_user.id);
+
+    match get_libraries_for_user_query(&pool, current_user.id).await {
+        Ok(libraries) => Ok(Json(libraries)),
+        Err(e) => {
+            error!("Failed to fetch libraries: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /libraries - Admins can start a new library, which they join straight away
+pub async fn create_library(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    Json(request): Json<CreateLibraryRequest>,
+) -> Result<Json<Library>, StatusCode> {
+    current_user.ensure_admin()?;
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        warn!("Library name is empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match create_library_query(&pool, name, current_user.id).await {
+        Ok(library) => Ok(Json(library)),
+        Err(e) => {
+            error!("Failed to create library: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Only admins who are members of a library can change who else belongs to it
+async fn ensure_library_admin(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    library_id: i64,
+) -> Result<(), StatusCode> {
+    current_user.ensure_admin()?;
+
+    match is_library_member_query(pool, library_id, current_user.id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!(
+                "User {} is not a member of library {}",
+                current_user.id, library_id
+            );
+            Err(StatusCode::FORBIDDEN)
+        }
+        Err(e) => {
+            error!("Failed to check library membership: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /libraries/:id/members
+pub async fn add_library_member(
+    State(pool): State<Pool<Sqlite>>,
+    Path(library_id): Path<i64>,
+    current_user: CurrentUser,
+    Json(request): Json<AddLibraryMemberRequest>,
+) -> Result<StatusCode, StatusCode> {
+    ensure_library_admin(&pool, &current_user, library_id).await?;
+
+    info!("Adding user {} to library {}", request.user_id, library_id);
+
+    match add_library_member_query(&pool, library_id, request.user_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) if is_foreign_key_violation(&e) => {
+            warn!("User {} not found", request.user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to add library member: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /libraries/:id/members/:user_id
+pub async fn remove_library_member(
+    State(pool): State<Pool<Sqlite>>,
+    Path((library_id, user_id)): Path<(i64, i64)>,
+    current_user: CurrentUser,
+) -> Result<StatusCode, StatusCode> {
+    ensure_library_admin(&pool, &current_user, library_id).await?;
+
+    match remove_library_member_query(&pool, library_id, user_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("User {} is not a member of library {}", user_id, library_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to remove library member: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
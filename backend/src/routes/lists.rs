@@ -6,19 +6,67 @@ use sqlx::{Pool, Sqlite};
 use tracing::{debug, error, info, warn};
 
 use crate::db::{
-    create_list_query, delete_list_query, get_all_lists_query, get_list_by_id_query,
-    update_list_query,
+    add_list_collaborator_query, create_list_query, delete_list_query, get_all_lists_query,
+    get_list_access_query, get_list_by_id_query, is_library_member_query,
+    remove_list_collaborator_query, update_list_query,
 };
-use crate::models::lists::{CreateListRequest, ListWithBooks, UpdateListRequest};
-use crate::utils::extract_user_id_from_headers;
+use crate::models::lists::{
+    AddListCollaboratorRequest, CreateListRequest, ListAccess, ListWithBooks, UpdateListRequest,
+    is_valid_list_visibility,
+};
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+// 404 if the user can't see the list, 403 if they can but need more access than they have
+async fn ensure_list_access(
+    pool: &Pool<Sqlite>,
+    library_id: i64,
+    list_id: i64,
+    user_id: i64,
+    required: ListAccess,
+) -> Result<(), StatusCode> {
+    match get_list_access_query(pool, library_id, list_id, user_id).await {
+        Ok(Some(access)) if access >= required => Ok(()),
+        Ok(Some(access)) => {
+            warn!(
+                "User {} has {:?} access to list {} but needs {:?}",
+                user_id, access, list_id, required
+            );
+            Err(StatusCode::FORBIDDEN)
+        }
+        Ok(None) => {
+            warn!("No list found with id: {} for user: {}", list_id, user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check access to list {}: {}", list_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn ensure_valid_visibility(visibility: Option<&str>) -> Result<(), StatusCode> {
+    match visibility {
+        Some(visibility) if !is_valid_list_visibility(visibility) => {
+            warn!("Invalid list visibility: {}", visibility);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        _ => Ok(()),
+    }
+}
 
-// GET /lists - Get all lists
+// GET /lists - Get the lists the user can see: household lists, and private lists
+// they own or collaborate on
 pub async fn get_lists(
     State(pool): State<Pool<Sqlite>>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<Vec<ListWithBooks>>, StatusCode> {
     debug!("Fetching all lists");
 
-    match get_all_lists_query(&pool).await {
+    // Optional, so restricted books can be left out for children
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    match get_all_lists_query(&pool, library.id, viewer_id).await {
         Ok(lists) => {
             info!("Successfully fetched {} lists", lists.len());
             Ok(Json(lists))
@@ -30,18 +78,19 @@ pub async fn get_lists(
     }
 }
 
-// GET /lists/:id - Get a single list by ID
+// GET /lists/:id - Get a single list by ID, following the same rules as GET /lists
 pub async fn get_list(
     State(pool): State<Pool<Sqlite>>,
     Path(list_id): Path<i64>,
     headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<ListWithBooks>, StatusCode> {
     debug!("Fetching list with id: {}", list_id);
 
     // Extract user_id from headers
     let user_id = extract_user_id_from_headers(&headers)?;
 
-    match get_list_by_id_query(&pool, list_id, user_id).await {
+    match get_list_by_id_query(&pool, library.id, list_id, user_id).await {
         Ok(Some(list)) => {
             info!("Successfully fetched list {}", list_id);
             Ok(Json(list))
@@ -61,12 +110,14 @@ pub async fn get_list(
 pub async fn create_list(
     State(pool): State<Pool<Sqlite>>,
     headers: HeaderMap,
+    library: CurrentLibrary,
     Json(request): Json<CreateListRequest>,
 ) -> Result<Json<ListWithBooks>, StatusCode> {
     debug!("Creating new list");
 
     // Extract user_id from headers
     let user_id = extract_user_id_from_headers(&headers)?;
+    ensure_valid_visibility(request.visibility.as_deref())?;
 
     info!(
         "Creating new list '{}' for user {} with {} books",
@@ -75,7 +126,7 @@ pub async fn create_list(
         request.books.len()
     );
 
-    match create_list_query(&pool, user_id, request).await {
+    match create_list_query(&pool, library.id, user_id, request).await {
         Ok(list) => {
             info!("Successfully created list with id: {}", list.id);
             Ok(Json(list))
@@ -87,11 +138,13 @@ pub async fn create_list(
     }
 }
 
-// PUT /lists/:id - Update an existing list
+// PUT /lists/:id - Update an existing list. Collaborators can change everything but
+// the visibility, which only the owner can change.
 pub async fn update_list(
     State(pool): State<Pool<Sqlite>>,
     Path(list_id): Path<i64>,
     headers: HeaderMap,
+    library: CurrentLibrary,
     Json(request): Json<UpdateListRequest>,
 ) -> Result<Json<ListWithBooks>, StatusCode> {
     debug!("Updating list with id: {}", list_id);
@@ -101,7 +154,14 @@ pub async fn update_list(
 
     info!("Updating list {} for user {}", list_id, user_id);
 
-    match update_list_query(&pool, list_id, user_id, request).await {
+    ensure_valid_visibility(request.visibility.as_deref())?;
+    let required = match request.visibility {
+        Some(_) => ListAccess::Own,
+        None => ListAccess::Edit,
+    };
+    ensure_list_access(&pool, library.id, list_id, user_id, required).await?;
+
+    match update_list_query(&pool, library.id, list_id, user_id, request).await {
         Ok(list) => {
             info!("Successfully updated list {}", list_id);
             Ok(Json(list))
@@ -117,11 +177,12 @@ pub async fn update_list(
     }
 }
 
-// DELETE /lists/:id - Delete a list
+// DELETE /lists/:id - Delete a list. Only its owner can.
 pub async fn delete_list(
     State(pool): State<Pool<Sqlite>>,
     Path(list_id): Path<i64>,
     headers: HeaderMap,
+    library: CurrentLibrary,
 ) -> Result<Json<()>, StatusCode> {
     debug!("Deleting list with id: {}", list_id);
 
@@ -130,7 +191,9 @@ pub async fn delete_list(
 
     info!("Deleting list {} for user {}", list_id, user_id);
 
-    match delete_list_query(&pool, list_id, user_id).await {
+    ensure_list_access(&pool, library.id, list_id, user_id, ListAccess::Own).await?;
+
+    match delete_list_query(&pool, library.id, list_id, user_id).await {
         Ok(()) => {
             info!("Successfully deleted list {}", list_id);
             Ok(Json(()))
@@ -145,3 +208,92 @@ pub async fn delete_list(
         }
     }
 }
+
+// POST /lists/:id/collaborators - The owner lets another library member edit the list
+pub async fn add_list_collaborator(
+    State(pool): State<Pool<Sqlite>>,
+    Path(list_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    Json(request): Json<AddListCollaboratorRequest>,
+) -> Result<Json<ListWithBooks>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    info!(
+        "User {} adding collaborator {} to list {}",
+        user_id, request.user_id, list_id
+    );
+
+    ensure_list_access(&pool, library.id, list_id, user_id, ListAccess::Own).await?;
+
+    if request.user_id == user_id {
+        warn!("List owner {} can't be a collaborator", user_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match is_library_member_query(&pool, library.id, request.user_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!(
+                "User {} is not a member of library {}",
+                request.user_id, library.id
+            );
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to check library membership: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    if let Err(e) = add_list_collaborator_query(&pool, list_id, request.user_id).await {
+        error!("Failed to add collaborator to list {}: {}", list_id, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match get_list_by_id_query(&pool, library.id, list_id, user_id).await {
+        Ok(Some(list)) => Ok(Json(list)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to fetch list {}: {}", list_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /lists/:id/collaborators/:user_id - The owner removes a collaborator, or a
+// collaborator leaves the list
+pub async fn remove_list_collaborator(
+    State(pool): State<Pool<Sqlite>>,
+    Path((list_id, collaborator_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    info!(
+        "User {} removing collaborator {} from list {}",
+        user_id, collaborator_id, list_id
+    );
+
+    let required = if collaborator_id == user_id {
+        ListAccess::Edit
+    } else {
+        ListAccess::Own
+    };
+    ensure_list_access(&pool, library.id, list_id, user_id, required).await?;
+
+    match remove_list_collaborator_query(&pool, list_id, collaborator_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "User {} is not a collaborator on list {}",
+                collaborator_id, list_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to remove collaborator from list {}: {}", list_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,13 +1,49 @@
+pub mod api_tokens;
+pub mod avatars;
 pub mod books;
+pub mod compatibility;
 pub mod genres;
+pub mod journal_attachments;
+pub mod journal_comments;
 pub mod journal_entries;
+pub mod journal_exports;
+pub mod journal_reactions;
+pub mod journal_revisions;
+pub mod libraries;
 pub mod lists;
+pub mod obsidian;
+pub mod preferences;
+pub mod quotes;
+pub mod rating_dimensions;
+pub mod recommendations;
+pub mod reviews;
+pub mod share_links;
+pub mod statuses;
 pub mod tags;
 pub mod users;
+pub mod year_in_review;
 
+pub use api_tokens::*;
+pub use avatars::*;
 pub use books::*;
+pub use compatibility::*;
 pub use genres::*;
+pub use journal_attachments::*;
+pub use journal_comments::*;
 pub use journal_entries::*;
+pub use journal_exports::*;
+pub use journal_reactions::*;
+pub use journal_revisions::*;
+pub use libraries::*;
 pub use lists::*;
+pub use obsidian::*;
+pub use preferences::*;
+pub use quotes::*;
+pub use rating_dimensions::*;
+pub use recommendations::*;
+pub use reviews::*;
+pub use share_links::*;
+pub use statuses::*;
 pub use tags::*;
 pub use users::*;
+pub use year_in_review::*;
@@ -0,0 +1,142 @@
+use axum::Json;
+use axum::extract::{Multipart, State};
+use axum::http::{HeaderMap, header};
+use axum::response::IntoResponse;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+use zip::result::ZipError;
+
+use crate::db::book_queries::get_all_books_with_details_query;
+use crate::db::import_obsidian_notes_query;
+use crate::models::obsidian::ObsidianImportSummary;
+use crate::utils::obsidian::{
+    VAULT_TOO_LARGE, build_obsidian_vault, parse_obsidian_note, read_obsidian_vault,
+};
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+// Every zip file starts with a local file header
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// GET /books/export/obsidian - Download the library as a zipped Obsidian vault:
+// a note per book with the current user's journal entries as sections
+pub async fn export_obsidian_vault(
+    State(pool): State<Pool<Sqlite>>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<impl IntoResponse, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    info!("Exporting Obsidian vault for user {}", user_id);
+
+    let books = match get_all_books_with_details_query(&pool, library.id, Some(user_id)).await {
+        Ok(books) => books,
+        Err(e) => {
+            error!("Failed to load books for Obsidian export: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match build_obsidian_vault(&books, user_id) {
+        Ok(bytes) => {
+            info!("Exported {} book notes for user {}", books.len(), user_id);
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "application/zip"),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        "attachment; filename=\"library-vault.zip\"",
+                    ),
+                ],
+                bytes,
+            ))
+        }
+        Err(e) => {
+            error!("Failed to build Obsidian vault: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Import notes from an Obsidian vault, uploaded as a multipart `file` field holding
+/// either a zipped vault or a single note.
+///
+/// Notes are matched to books by the `home_library_book_id` in their front matter. Each `##` section
+/// updates the journal entry named by its `%% journal: 12 %%` marker, or becomes a new
+/// entry when it has none.
+pub async fn import_obsidian_vault(
+    State(pool): State<Pool<Sqlite>>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    mut multipart: Multipart,
+) -> Result<Json<ObsidianImportSummary>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    info!("User {} importing Obsidian notes", user_id);
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        warn!("Invalid multipart upload: {}", e);
+        e.status()
+    })? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or("note.md").to_string();
+        let bytes = field.bytes().await.map_err(|e| {
+            warn!("Failed to read uploaded file: {}", e);
+            e.status()
+        })?;
+        upload = Some((file_name, bytes));
+        break;
+    }
+
+    let Some((file_name, bytes)) = upload else {
+        warn!("Obsidian import has no file field");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let files = if bytes.starts_with(ZIP_MAGIC) {
+        read_obsidian_vault(&bytes).map_err(|e| match e {
+            ZipError::UnsupportedArchive(VAULT_TOO_LARGE) => {
+                warn!("Uploaded vault is too large to import");
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            e => {
+                warn!("Uploaded vault is not a valid zip: {}", e);
+                StatusCode::BAD_REQUEST
+            }
+        })?
+    } else {
+        let Ok(contents) = String::from_utf8(bytes.to_vec()) else {
+            warn!("Uploaded note is not valid UTF-8");
+            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        };
+        vec![(file_name, contents)]
+    };
+
+    if files.is_empty() {
+        warn!("Uploaded vault contains no Markdown notes");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    debug!("Read {} notes from upload", files.len());
+
+    let notes: Vec<_> = files
+        .iter()
+        .map(|(file_name, contents)| parse_obsidian_note(file_name, contents))
+        .collect();
+
+    match import_obsidian_notes_query(&pool, library.id, user_id, &notes).await {
+        Ok(summary) => {
+            info!(
+                "Imported {} Obsidian notes for user {} ({} skipped)",
+                summary.notes_imported, user_id, summary.notes_skipped
+            );
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            error!("Failed to import Obsidian notes: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
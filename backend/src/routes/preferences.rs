@@ -0,0 +1,88 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use serde_json::{Map, Value};
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{get_user_preferences_query, get_user_role_query, update_user_preferences_query};
+use crate::models::preferences::{PREFERENCE_KEYS, UserPreferences};
+use crate::utils::CurrentUser;
+
+// Users can see and change their own preferences; admins can for anyone
+async fn ensure_can_manage_preferences(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    user_id: i64,
+) -> Result<(), StatusCode> {
+    if user_id != current_user.id {
+        current_user.ensure_admin()?;
+    }
+
+    match get_user_role_query(pool, user_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            warn!("No user found with ID: {}", user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to look up user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /users/:id/preferences - Every preference, with defaults for those not set
+pub async fn get_user_preferences(
+    State(pool): State<Pool<Sqlite>>,
+    Path(user_id): Path<i64>,
+    current_user: CurrentUser,
+) -> Result<Json<UserPreferences>, StatusCode> {
+    debug!("Fetching preferences of user {}", user_id);
+
+    ensure_can_manage_preferences(&pool, &current_user, user_id).await?;
+
+    match get_user_preferences_query(&pool, user_id).await {
+        Ok(preferences) => Ok(Json(preferences)),
+        Err(e) => {
+            error!("Failed to fetch preferences of user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /users/:id/preferences - Change the given preferences; null resets one to its default.
+// Unknown keys and invalid values are rejected and nothing is saved.
+pub async fn update_user_preferences(
+    State(pool): State<Pool<Sqlite>>,
+    Path(user_id): Path<i64>,
+    current_user: CurrentUser,
+    Json(changes): Json<Map<String, Value>>,
+) -> Result<Json<UserPreferences>, StatusCode> {
+    info!("Updating preferences of user {}", user_id);
+
+    ensure_can_manage_preferences(&pool, &current_user, user_id).await?;
+
+    let mut validated = UserPreferences::default();
+    for (key, value) in &changes {
+        if !PREFERENCE_KEYS.contains(&key.as_str()) {
+            warn!("Unknown preference: {}", key);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        if value.is_null() {
+            continue;
+        }
+        if let Err(e) = validated.set(key, value) {
+            warn!("Invalid preference {}: {}", key, e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    match update_user_preferences_query(&pool, user_id, &changes).await {
+        Ok(preferences) => Ok(Json(preferences)),
+        Err(e) => {
+            error!("Failed to update preferences of user {}: {}", user_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
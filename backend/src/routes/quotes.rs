@@ -0,0 +1,310 @@
+use axum::Json;
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::HeaderMap;
+use chrono::{Datelike, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::book_queries::book_exists_query;
+use crate::db::{
+    create_quote_query, delete_quote_query, get_quotes_by_book_id_query, get_random_quote_query,
+    import_kindle_clippings_query, search_quotes_query, update_quote_query,
+};
+use crate::models::quotes::{
+    CreateQuoteRequest, KindleImportParams, KindleImportSummary, Quote, QuoteSearchParams,
+    UpdateQuoteRequest,
+};
+use crate::utils::kindle::parse_kindle_clippings;
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+#[derive(Deserialize, Debug)]
+pub struct RandomQuoteParams {
+    user_id: Option<i64>,
+    // Return the quote of the day instead of a fresh pick on every request
+    daily: Option<bool>,
+}
+
+fn is_valid_page(page: Option<i64>) -> bool {
+    page.is_none_or(|p| p > 0)
+}
+
+async fn ensure_book_exists(pool: &Pool<Sqlite>, book_id: i64) -> Result<(), StatusCode> {
+    match book_exists_query(pool, book_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!("Book with ID {} not found", book_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check if book exists for ID {}: {}", book_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /quotes - Search quotes across all books in the library
+pub async fn get_quotes(
+    State(pool): State<Pool<Sqlite>>,
+    Query(params): Query<QuoteSearchParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<Quote>>, StatusCode> {
+    debug!("Searching quotes with params: {:?}", params);
+
+    // Quotes from books restricted for the current user are left out
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    match search_quotes_query(&pool, library.id, &params, viewer_id).await {
+        Ok(quotes) => {
+            info!("Found {} quotes", quotes.len());
+            Ok(Json(quotes))
+        }
+        Err(e) => {
+            error!("Failed to search quotes: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /quotes/random - A random quote, or with ?daily=true the same quote all day
+pub async fn get_random_quote(
+    State(pool): State<Pool<Sqlite>>,
+    Query(params): Query<RandomQuoteParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Quote>, StatusCode> {
+    debug!("Picking a random quote with params: {:?}", params);
+
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    let seed = params
+        .daily
+        .unwrap_or(false)
+        .then(|| Utc::now().date_naive().num_days_from_ce() as u64);
+
+    match get_random_quote_query(&pool, library.id, params.user_id, viewer_id, seed).await {
+        Ok(Some(quote)) => {
+            info!("Picked quote {} from book {}", quote.id, quote.book_id);
+            Ok(Json(quote))
+        }
+        Ok(None) => {
+            warn!("No quotes to pick from");
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to pick a random quote: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /books/:id/quotes - Get all quotes from a book, in page order
+pub async fn get_book_quotes(
+    State(pool): State<Pool<Sqlite>>,
+    Path(book_id): Path<i64>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<Quote>>, StatusCode> {
+    debug!("Fetching quotes for book {}", book_id);
+
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    ensure_book_exists(&pool, book_id).await?;
+
+    match get_quotes_by_book_id_query(&pool, library.id, book_id, viewer_id).await {
+        Ok(quotes) => {
+            info!("Found {} quotes for book {}", quotes.len(), book_id);
+            Ok(Json(quotes))
+        }
+        Err(e) => {
+            error!("Failed to fetch quotes for book {}: {}", book_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /books/:id/quotes - Save a quote from a book for the current user
+pub async fn create_book_quote(
+    State(pool): State<Pool<Sqlite>>,
+    Path(book_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<CreateQuoteRequest>,
+) -> Result<Json<Quote>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!("Creating quote from book {} by user {}", book_id, user_id);
+
+    if request.text.trim().is_empty() {
+        warn!("Quote text is required");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_page(request.page) {
+        warn!("Invalid quote page: {:?}", request.page);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_book_exists(&pool, book_id).await?;
+
+    match create_quote_query(&pool, book_id, user_id, &request).await {
+        Ok(quote) => {
+            info!("Successfully created quote with ID: {}", quote.id);
+            Ok(Json(quote))
+        }
+        Err(e) => {
+            error!("Failed to create quote: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /books/:book_id/quotes/:quote_id - Update one of the current user's quotes
+pub async fn update_book_quote(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, quote_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateQuoteRequest>,
+) -> Result<Json<Quote>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "Updating quote {} from book {} by user {}",
+        quote_id, book_id, user_id
+    );
+
+    if request.text.as_ref().is_some_and(|t| t.trim().is_empty()) {
+        warn!("Quote text cannot be empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_page(request.page) {
+        warn!("Invalid quote page: {:?}", request.page);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match update_quote_query(&pool, book_id, quote_id, user_id, &request).await {
+        Ok(quote) => {
+            info!("Successfully updated quote with ID: {}", quote.id);
+            Ok(Json(quote))
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No quote {} from book {} found for user {}",
+                quote_id, book_id, user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to update quote {}: {}", quote_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /books/:book_id/quotes/:quote_id - Delete one of the current user's quotes
+pub async fn delete_book_quote(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, quote_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "Deleting quote {} from book {} by user {}",
+        quote_id, book_id, user_id
+    );
+
+    match delete_quote_query(&pool, book_id, quote_id, user_id).await {
+        Ok(()) => {
+            info!("Successfully deleted quote with ID: {}", quote_id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No quote {} from book {} found for user {}",
+                quote_id, book_id, user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete quote {}: {}", quote_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Import a Kindle "My Clippings.txt" file uploaded as a multipart `file` field.
+///
+/// Clippings for books that aren't in the library are reported as missing; send the
+/// file again with `?create_missing_books=true` to add them. Use `?dry_run=true` to
+/// preview the import. Clippings imported before are skipped.
+pub async fn import_kindle_clippings(
+    State(pool): State<Pool<Sqlite>>,
+    Query(params): Query<KindleImportParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+    mut multipart: Multipart,
+) -> Result<Json<KindleImportSummary>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    info!(
+        "User {} importing Kindle clippings with params: {:?}",
+        user_id, params
+    );
+
+    let mut contents = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        warn!("Invalid multipart upload: {}", e);
+        e.status()
+    })? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let bytes = field.bytes().await.map_err(|e| {
+            warn!("Failed to read uploaded file: {}", e);
+            e.status()
+        })?;
+        contents = Some(bytes);
+        break;
+    }
+
+    let Some(contents) = contents else {
+        warn!("Kindle import has no file field");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let Ok(contents) = std::str::from_utf8(&contents) else {
+        warn!("Uploaded clippings file is not valid UTF-8");
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    };
+
+    let clippings = parse_kindle_clippings(contents);
+    if clippings.is_empty() {
+        warn!("Uploaded file contains no Kindle clippings");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    debug!("Parsed {} Kindle clippings", clippings.len());
+
+    match import_kindle_clippings_query(
+        &pool,
+        library.id,
+        user_id,
+        &clippings,
+        params.create_missing_books.unwrap_or(false),
+        params.dry_run.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(summary) => {
+            info!(
+                "Imported {} Kindle clippings for user {} ({} duplicates)",
+                summary.imported, user_id, summary.duplicates
+            );
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            error!("Failed to import Kindle clippings: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
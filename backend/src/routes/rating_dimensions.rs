@@ -0,0 +1,166 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    create_rating_dimension_query, delete_rating_dimension_by_id_query,
+    get_all_rating_dimensions_query, get_rating_dimension_by_id_query,
+    update_rating_dimension_query,
+};
+use crate::models::ratings::{
+    CreateRatingDimensionRequest, RatingDimension, UpdateRatingDimensionRequest,
+};
+use crate::utils::{is_on_scale, is_unique_violation};
+
+// A scale needs a positive increment that steps evenly from min to max
+fn is_valid_scale(min_value: f64, max_value: f64, increment: f64) -> bool {
+    min_value.is_finite()
+        && max_value > min_value
+        && increment > 0.0
+        && is_on_scale(max_value, min_value, max_value, increment)
+}
+
+// GET /rating-dimensions - Get all rating dimensions in display order
+pub async fn get_rating_dimensions(
+    State(pool): State<Pool<Sqlite>>,
+) -> Result<Json<Vec<RatingDimension>>, StatusCode> {
+    debug!("Fetching all rating dimensions");
+
+    match get_all_rating_dimensions_query(&pool).await {
+        Ok(dimensions) => Ok(Json(dimensions)),
+        Err(e) => {
+            error!("Failed to fetch rating dimensions: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /rating-dimensions/:id - Get a single rating dimension
+pub async fn get_rating_dimension_by_id(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RatingDimension>, StatusCode> {
+    debug!("Fetching rating dimension with id: {}", id);
+
+    match get_rating_dimension_by_id_query(&pool, id).await {
+        Ok(Some(dimension)) => Ok(Json(dimension)),
+        Ok(None) => {
+            warn!("No rating dimension found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch rating dimension by id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /rating-dimensions - Create a new rating dimension
+pub async fn create_rating_dimension(
+    State(pool): State<Pool<Sqlite>>,
+    Json(request): Json<CreateRatingDimensionRequest>,
+) -> Result<Json<RatingDimension>, StatusCode> {
+    debug!("Creating new rating dimension");
+
+    if request.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_scale(
+        request.min_value.unwrap_or(0.0),
+        request.max_value.unwrap_or(5.0),
+        request.increment.unwrap_or(0.5),
+    ) {
+        warn!("Invalid scale for rating dimension '{}'", request.name);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    info!("Creating new rating dimension: {}", request.name);
+
+    match create_rating_dimension_query(&pool, &request).await {
+        Ok(dimension) => Ok(Json(dimension)),
+        Err(e) if is_unique_violation(&e) => {
+            warn!("A rating dimension named '{}' already exists", request.name);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            error!("Failed to create rating dimension: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /rating-dimensions/:id - Update an existing rating dimension
+// Existing scores are kept as-is if the scale changes
+pub async fn update_rating_dimension(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateRatingDimensionRequest>,
+) -> Result<Json<RatingDimension>, StatusCode> {
+    debug!("Updating rating dimension with id: {}", id);
+
+    if request.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Check the scale against the stored values for anything not being updated
+    let current = match get_rating_dimension_by_id_query(&pool, id).await {
+        Ok(Some(dimension)) => dimension,
+        Ok(None) => {
+            warn!("No rating dimension found with id: {}", id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch rating dimension by id {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if !is_valid_scale(
+        request.min_value.unwrap_or(current.min_value),
+        request.max_value.unwrap_or(current.max_value),
+        request.increment.unwrap_or(current.increment),
+    ) {
+        warn!("Invalid scale for rating dimension {}", id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match update_rating_dimension_query(&pool, id, &request).await {
+        Ok(dimension) => Ok(Json(dimension)),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("No rating dimension found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) if is_unique_violation(&e) => {
+            warn!("A rating dimension with the requested name already exists");
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            error!("Failed to update rating dimension with id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /rating-dimensions/:id - Delete a rating dimension and its scores
+pub async fn delete_rating_dimension(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    debug!("Deleting rating dimension with id: {}", id);
+
+    match delete_rating_dimension_by_id_query(&pool, id).await {
+        Ok(()) => {
+            info!("Successfully deleted rating dimension with id: {}", id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("No rating dimension found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete rating dimension with id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::get_recommendations_query;
+use crate::models::recommendations::Recommendation;
+use crate::utils::{CurrentLibrary, extract_user_id_from_headers};
+
+const DEFAULT_RECOMMENDATION_LIMIT: usize = 10;
+const MAX_RECOMMENDATION_LIMIT: usize = 100;
+
+#[derive(Deserialize, Debug)]
+pub struct RecommendationParams {
+    limit: Option<usize>,
+}
+
+// GET /users/:id/recommendations - Suggest what a user should read next
+// Each suggestion lists the reasons it was picked, strongest first
+pub async fn get_recommendations(
+    State(pool): State<Pool<Sqlite>>,
+    Path(user_id): Path<i64>,
+    Query(params): Query<RecommendationParams>,
+    library: CurrentLibrary,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Recommendation>>, StatusCode> {
+    debug!(
+        "Fetching recommendations for user {} with params: {:?}",
+        user_id, params
+    );
+
+    let limit = params.limit.unwrap_or(DEFAULT_RECOMMENDATION_LIMIT);
+    if limit == 0 || limit > MAX_RECOMMENDATION_LIMIT {
+        warn!("Invalid recommendation limit: {}", limit);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+    match get_recommendations_query(&pool, library.id, user_id, viewer_id, limit).await {
+        Ok(Some(recommendations)) => {
+            info!(
+                "Found {} recommendations for user {}",
+                recommendations.len(),
+                user_id
+            );
+            Ok(Json(recommendations))
+        }
+        Ok(None) => {
+            warn!("No user found with ID: {}", user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "Failed to build recommendations for user {}: {}",
+                user_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::book_queries::book_exists_query;
+use crate::db::reading_status_queries::has_finished_book_query;
+use crate::db::{
+    create_review_query, delete_review_query, get_reviews_by_book_id_query, update_review_query,
+};
+use crate::models::reviews::{CreateReviewRequest, Review, UpdateReviewRequest};
+use crate::utils::{extract_user_id_from_headers, is_on_scale, is_unique_violation};
+
+// Ratings embedded in reviews follow the same 0-5 half-star scale as POST /books/:id/ratings
+fn is_valid_rating(rating: Option<f64>) -> bool {
+    rating.is_none_or(|r| is_on_scale(r, 0.0, 5.0, 0.5))
+}
+
+async fn ensure_book_exists(pool: &Pool<Sqlite>, book_id: i64) -> Result<(), StatusCode> {
+    match book_exists_query(pool, book_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!("Book with ID {} not found", book_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to check if book exists for ID {}: {}", book_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /books/:id/reviews - Get all reviews for a book
+// Spoiler reviews have their body removed unless the viewer wrote them or has finished the book
+pub async fn get_book_reviews(
+    State(pool): State<Pool<Sqlite>>,
+    Path(book_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Review>>, StatusCode> {
+    debug!("Fetching reviews for book {}", book_id);
+
+    ensure_book_exists(&pool, book_id).await?;
+
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    let mut reviews = match get_reviews_by_book_id_query(&pool, book_id).await {
+        Ok(reviews) => reviews,
+        Err(e) => {
+            error!("Failed to fetch reviews for book {}: {}", book_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let viewer_has_finished = match viewer_id {
+        Some(user_id) => match has_finished_book_query(&pool, user_id, book_id).await {
+            Ok(finished) => finished,
+            Err(e) => {
+                error!(
+                    "Failed to check reading status of book {} for user {}: {}",
+                    book_id, user_id, e
+                );
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+        None => false,
+    };
+
+    if !viewer_has_finished {
+        for review in reviews
+            .iter_mut()
+            .filter(|r| r.is_spoiler && Some(r.user_id) != viewer_id)
+        {
+            review.body = None;
+            review.is_redacted = true;
+        }
+    }
+
+    info!("Found {} reviews for book {}", reviews.len(), book_id);
+    Ok(Json(reviews))
+}
+
+// POST /books/:id/reviews - Publish the current user's review of a book
+pub async fn create_book_review(
+    State(pool): State<Pool<Sqlite>>,
+    Path(book_id): Path<i64>,
+    headers: HeaderMap,
+    Json(request): Json<CreateReviewRequest>,
+) -> Result<Json<Review>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!("Creating review of book {} by user {}", book_id, user_id);
+
+    if request.headline.trim().is_empty() || request.body.trim().is_empty() {
+        warn!("Review headline and body are required");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_rating(request.rating) {
+        warn!("Invalid review rating value: {:?}", request.rating);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_book_exists(&pool, book_id).await?;
+
+    match create_review_query(&pool, book_id, user_id, &request).await {
+        Ok(review) => {
+            info!("Successfully created review with ID: {}", review.id);
+            Ok(Json(review))
+        }
+        Err(e) if is_unique_violation(&e) => {
+            warn!("User {} has already reviewed book {}", user_id, book_id);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            error!("Failed to create review: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /books/:book_id/reviews/:review_id - Update the current user's review
+pub async fn update_book_review(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, review_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateReviewRequest>,
+) -> Result<Json<Review>, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "Updating review {} of book {} by user {}",
+        review_id, book_id, user_id
+    );
+
+    let is_blank = |value: &Option<String>| value.as_ref().is_some_and(|v| v.trim().is_empty());
+    if is_blank(&request.headline) || is_blank(&request.body) {
+        warn!("Review headline and body cannot be empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if !is_valid_rating(request.rating) {
+        warn!("Invalid review rating value: {:?}", request.rating);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match update_review_query(&pool, book_id, review_id, user_id, &request).await {
+        Ok(review) => {
+            info!("Successfully updated review with ID: {}", review.id);
+            Ok(Json(review))
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No review {} of book {} found for user {}",
+                review_id, book_id, user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to update review {}: {}", review_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /books/:book_id/reviews/:review_id - Delete the current user's review
+pub async fn delete_book_review(
+    State(pool): State<Pool<Sqlite>>,
+    Path((book_id, review_id)): Path<(i64, i64)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = extract_user_id_from_headers(&headers)?;
+
+    debug!(
+        "Deleting review {} of book {} by user {}",
+        review_id, book_id, user_id
+    );
+
+    match delete_review_query(&pool, book_id, review_id, user_id).await {
+        Ok(()) => {
+            info!("Successfully deleted review with ID: {}", review_id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No review {} of book {} found for user {}",
+                review_id, book_id, user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete review {}: {}", review_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
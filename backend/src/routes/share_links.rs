@@ -0,0 +1,200 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::book_queries::fetch_hidden_book_ids;
+use crate::db::{
+    book_in_library_query, create_share_link_query, get_list_access_query,
+    get_live_share_link_query, get_share_links_query, get_shared_view_query,
+    record_share_link_open_query, revoke_share_link_query,
+};
+use crate::models::lists::ListAccess;
+use crate::models::share_links::{CreateShareLinkRequest, ShareLink, SharedView};
+use crate::utils::{CurrentLibrary, CurrentUser, generate_share_token};
+
+// Longest a link can be set to last; links without an expiry last until revoked
+const MAX_SHARE_LINK_DAYS: i64 = 3650;
+
+// 404 unless the current user can edit the list or see the book they're sharing
+async fn ensure_can_share(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    library: &CurrentLibrary,
+    request: &CreateShareLinkRequest,
+) -> Result<(), StatusCode> {
+    let visible = match (request.list_id, request.book_id) {
+        // Seeing a household list isn't enough to share it outside the household
+        (Some(list_id), None) => get_list_access_query(pool, library.id, list_id, current_user.id)
+            .await
+            .map(|access| access >= Some(ListAccess::Edit)),
+        (None, Some(book_id)) => match book_in_library_query(pool, book_id, library.id).await {
+            Ok(true) => fetch_hidden_book_ids(pool, Some(current_user.id))
+                .await
+                .map(|hidden_book_ids| !hidden_book_ids.contains(&book_id)),
+            other => other,
+        },
+        _ => {
+            warn!("Share link must be for exactly one of a list or a book");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    match visible {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!(
+                "User {} can't share list {:?} / book {:?}",
+                current_user.id, request.list_id, request.book_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to look up what is being shared: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /share-links - The current user's share links in the library, with how often
+// each was opened
+pub async fn get_share_links(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+) -> Result<Json<Vec<ShareLink>>, StatusCode> {
+    debug!("Fetching share links for user {}", current_user.id);
+
+    match get_share_links_query(&pool, library.id, current_user.id).await {
+        Ok(share_links) => {
+            info!(
+                "Found {} share links for user {}",
+                share_links.len(),
+                current_user.id
+            );
+            Ok(Json(share_links))
+        }
+        Err(e) => {
+            error!(
+                "Failed to fetch share links for user {}: {}",
+                current_user.id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /share-links - Share a list the current user owns or collaborates on, or a book
+// in the library
+pub async fn create_share_link(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> Result<Json<ShareLink>, StatusCode> {
+    if let Some(days) = request.expires_in_days
+        && !(1..=MAX_SHARE_LINK_DAYS).contains(&days)
+    {
+        warn!("Invalid share link expiry: {} days", days);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_can_share(&pool, &current_user, &library, &request).await?;
+
+    match create_share_link_query(
+        &pool,
+        library.id,
+        current_user.id,
+        &generate_share_token(),
+        request.list_id,
+        request.book_id,
+        request.expires_in_days,
+    )
+    .await
+    {
+        Ok(share_link) => Ok(Json(share_link)),
+        Err(e) => {
+            error!(
+                "Failed to create share link for user {}: {}",
+                current_user.id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /share-links/:id - Revoke one of the current user's share links
+pub async fn revoke_share_link(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    Path(share_link_id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!(
+        "User {} revoking share link {}",
+        current_user.id, share_link_id
+    );
+
+    match revoke_share_link_query(&pool, current_user.id, share_link_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!(
+                "No active share link {} found for user {}",
+                share_link_id, current_user.id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to revoke share link {}: {}", share_link_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /shared/:token - The shared list or book. No user header is needed; unknown,
+// revoked and expired links are all 404. Only views that succeed count as opens.
+pub async fn get_shared(
+    State(pool): State<Pool<Sqlite>>,
+    Path(token): Path<String>,
+) -> Result<Json<SharedView>, StatusCode> {
+    let share_link = match get_live_share_link_query(&pool, &token).await {
+        Ok(Some(share_link)) => share_link,
+        Ok(None) => {
+            warn!("Unknown, revoked or expired share link opened");
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to open share link: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let view = match get_shared_view_query(&pool, &share_link).await {
+        Ok(Some(view)) => view,
+        Ok(None) => {
+            warn!(
+                "What share link {} points at is no longer available",
+                share_link.id
+            );
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to load share link {}: {}", share_link.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match record_share_link_open_query(&pool, share_link.id).await {
+        Ok(open_count) => {
+            info!(
+                "Share link {} opened ({} times so far)",
+                share_link.id, open_count
+            );
+            Ok(Json(view))
+        }
+        Err(e) => {
+            error!("Failed to count share link {} open: {}", share_link.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
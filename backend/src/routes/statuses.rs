@@ -0,0 +1,184 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use reqwest::StatusCode;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{
+    create_status_query, delete_status_by_id_query, get_all_statuses_query, get_status_by_id_query,
+    get_status_usage_count_query, update_status_query,
+};
+use crate::models::statuses::{CreateStatusRequest, Status, UpdateStatusRequest};
+use crate::utils::is_unique_violation;
+
+// A status means at most one of "finished", "currently reading" and "to be read"
+fn has_conflicting_flags(
+    is_finished: Option<bool>,
+    is_active: Option<bool>,
+    is_planned: Option<bool>,
+) -> bool {
+    [is_finished, is_active, is_planned]
+        .iter()
+        .filter(|flag| **flag == Some(true))
+        .count()
+        > 1
+}
+
+// GET /statuses - Get all statuses in display order
+pub async fn get_statuses(
+    State(pool): State<Pool<Sqlite>>,
+) -> Result<Json<Vec<Status>>, StatusCode> {
+    debug!("Fetching all statuses");
+
+    match get_all_statuses_query(&pool).await {
+        Ok(statuses) => Ok(Json(statuses)),
+        Err(e) => {
+            error!("Failed to fetch statuses: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// GET /statuses/:id - Get a single status
+pub async fn get_status_by_id(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Status>, StatusCode> {
+    debug!("Fetching status with id: {}", id);
+
+    match get_status_by_id_query(&pool, id).await {
+        Ok(Some(status)) => Ok(Json(status)),
+        Ok(None) => {
+            warn!("No status found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to fetch status by id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// POST /statuses - Create a new status
+pub async fn create_status(
+    State(pool): State<Pool<Sqlite>>,
+    Json(request): Json<CreateStatusRequest>,
+) -> Result<Json<Status>, StatusCode> {
+    debug!("Creating new status");
+
+    if request.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if has_conflicting_flags(request.is_finished, request.is_active, request.is_planned) {
+        warn!(
+            "Status '{}' can only be one of finished, active or planned",
+            request.name
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    info!("Creating new status: {}", request.name);
+
+    match create_status_query(&pool, &request).await {
+        Ok(status) => Ok(Json(status)),
+        Err(e) if is_unique_violation(&e) => {
+            warn!("A status named '{}' already exists", request.name);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            error!("Failed to create status: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /statuses/:id - Update an existing status
+pub async fn update_status(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateStatusRequest>,
+) -> Result<Json<Status>, StatusCode> {
+    debug!("Updating status with id: {}", id);
+
+    if request.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Check the flags against the stored values for anything not being updated
+    let current = match get_status_by_id_query(&pool, id).await {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            warn!("No status found with id: {}", id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            error!("Failed to fetch status by id {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if has_conflicting_flags(
+        Some(request.is_finished.unwrap_or(current.is_finished)),
+        Some(request.is_active.unwrap_or(current.is_active)),
+        Some(request.is_planned.unwrap_or(current.is_planned)),
+    ) {
+        warn!(
+            "Status {} can only be one of finished, active or planned",
+            id
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match update_status_query(&pool, id, &request).await {
+        Ok(status) => Ok(Json(status)),
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("No status found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) if is_unique_violation(&e) => {
+            warn!("A status with the requested name already exists");
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            error!("Failed to update status with id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// DELETE /statuses/:id - Delete a status that no book is using
+pub async fn delete_status_by_id(
+    State(pool): State<Pool<Sqlite>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    debug!("Deleting status with id: {}", id);
+
+    match get_status_usage_count_query(&pool, id).await {
+        Ok(0) => {}
+        Ok(count) => {
+            warn!(
+                "Refusing to delete status {} which is used by {} reading statuses",
+                id, count
+            );
+            return Err(StatusCode::CONFLICT);
+        }
+        Err(e) => {
+            error!("Failed to check usage of status {}: {}", id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    match delete_status_by_id_query(&pool, id).await {
+        Ok(()) => {
+            info!("Successfully deleted status with id: {}", id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            warn!("No status found with id: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!("Failed to delete status with id {}: {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
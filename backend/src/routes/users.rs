@@ -1,16 +1,39 @@
 use axum::Json;
 use axum::extract::{Path, State};
+use axum::http::HeaderMap;
 use reqwest::StatusCode;
 use sqlx::{Pool, Sqlite};
 use tracing::{debug, error, info, warn};
 
-use crate::db::{get_all_users, select_user as db_select_user, update_user as db_update_user};
+use crate::db::{
+    count_admins_query, create_user_query, get_all_users, get_user_role_query,
+    select_user as db_select_user, update_user as db_update_user,
+};
+use crate::models::users::{CreateUserRequest, is_valid_user_role};
 use crate::models::{SelectUser, UpdateUserRequest, User};
+use crate::utils::{CurrentLibrary, CurrentUser, extract_library_id_from_headers};
 
-pub async fn get_users(State(pool): State<Pool<Sqlite>>) -> Result<Json<Vec<User>>, StatusCode> {
-    debug!("Fetching all users from database");
+// GET /users - The members of the active library. The login screen's user picker asks
+// before anyone is selected, so it names the library with the `currentLibraryId` header.
+pub async fn get_users(
+    State(pool): State<Pool<Sqlite>>,
+    headers: HeaderMap,
+    library: Option<CurrentLibrary>,
+) -> Result<Json<Vec<User>>, StatusCode> {
+    debug!("Fetching users of library {:?}", library);
+
+    let library_id = match library {
+        Some(library) => library.id,
+        None => match extract_library_id_from_headers(&headers)? {
+            Some(library_id) => library_id,
+            None => {
+                warn!("GET /users says neither who it's from nor which library");
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+        },
+    };
 
-    match get_all_users(&pool).await {
+    match get_all_users(&pool, library_id).await {
         Ok(users) => {
             info!("Successfully retrieved {} users", users.len());
             Ok(Json(users))
@@ -41,13 +64,92 @@ pub async fn select_user(
     }
 }
 
+// POST /users - Add a member of the household. Admins only.
+pub async fn create_user(
+    State(pool): State<Pool<Sqlite>>,
+    current_user: CurrentUser,
+    library: CurrentLibrary,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<Json<User>, StatusCode> {
+    current_user.ensure_admin()?;
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        warn!("User name is empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let role = request.role.as_deref().unwrap_or("member");
+    if !is_valid_user_role(role) {
+        warn!("Invalid user role: {}", role);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match create_user_query(&pool, library.id, name, &request.color, role).await {
+        Ok(user) => Ok(Json(user)),
+        Err(e) => {
+            error!("Failed to create user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// PUT /users/:id - Users can edit themselves; admins can edit anyone and change roles.
+// The last admin can't be demoted.
 pub async fn update_user(
     State(pool): State<Pool<Sqlite>>,
     Path(user_id): Path<i64>,
+    current_user: CurrentUser,
     Json(request): Json<UpdateUserRequest>,
 ) -> Result<Json<User>, StatusCode> {
     info!("Updating user with ID: {}", user_id);
 
+    if user_id != current_user.id {
+        current_user.ensure_admin()?;
+    }
+
+    if let Some(role) = &request.role {
+        current_user.ensure_admin()?;
+        if !is_valid_user_role(role) {
+            warn!("Invalid user role: {}", role);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let current_role = match get_user_role_query(&pool, user_id).await {
+            Ok(Some(current_role)) => current_role,
+            Ok(None) => {
+                warn!("No user found with ID: {}", user_id);
+                return Err(StatusCode::NOT_FOUND);
+            }
+            Err(e) => {
+                error!("Failed to fetch role of user {}: {}", user_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+        if current_role == "admin" && role != "admin" {
+            match count_admins_query(&pool).await {
+                Ok(1) => {
+                    warn!("Refusing to demote user {}, the last admin", user_id);
+                    return Err(StatusCode::CONFLICT);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Failed to count admins: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            }
+        }
+    }
+
+    // Images are uploaded to /users/:id/avatar rather than embedded
+    if request
+        .avatar_image
+        .as_deref()
+        .is_some_and(|avatar| avatar.starts_with("data:"))
+    {
+        warn!("Refusing inline avatar image for user {}", user_id);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     // Convert Option<String> to Option<Option<String>> for the avatar_image
     // If provided, wrap it in Some, otherwise None means "don't change"
     let avatar_image_update = request.avatar_image.map(Some);
@@ -58,6 +160,7 @@ pub async fn update_user(
         request.name,
         request.color,
         avatar_image_update,
+        request.role,
     )
     .await
     {
@@ -0,0 +1,227 @@
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderMap;
+use axum::response::{Html, IntoResponse, Response};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, info, warn};
+
+use crate::db::get_year_in_review_query;
+use crate::models::year_in_review::{YearInReview, YearInReviewBook};
+use crate::utils::{CurrentLibrary, escape_html, extract_user_id_from_headers};
+
+// Used in the page for user and genre colours that aren't plain `#rrggbb` values
+const FALLBACK_COLOR: &str = "#a1a1aa";
+
+#[derive(Deserialize, Debug)]
+pub struct YearInReviewParams {
+    format: Option<String>,
+}
+
+// GET /users/:id/year-in-review/:year - Get a user's reading summary for a year
+// Returns JSON by default, or a self-contained HTML page with ?format=html
+// Books restricted for the viewer are left out
+pub async fn get_year_in_review(
+    State(pool): State<Pool<Sqlite>>,
+    Path((user_id, year)): Path<(i64, i32)>,
+    Query(params): Query<YearInReviewParams>,
+    headers: HeaderMap,
+    library: CurrentLibrary,
+) -> Result<Response, StatusCode> {
+    let viewer_id = extract_user_id_from_headers(&headers).ok();
+
+    debug!(
+        "Fetching year in review {} for user {} with params: {:?}",
+        year, user_id, params
+    );
+
+    if !(1000..=9999).contains(&year) {
+        warn!("Invalid year for year in review: {}", year);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let as_html = match params.format.as_deref() {
+        None | Some("json") => false,
+        Some("html") => true,
+        Some(other) => {
+            warn!("Unsupported year in review format: {}", other);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    match get_year_in_review_query(&pool, library.id, user_id, viewer_id, year).await {
+        Ok(Some(report)) => {
+            info!(
+                "Built year in review {} for user {} ({} books)",
+                year, user_id, report.books_read
+            );
+            if as_html {
+                Ok(Html(render_year_in_review_html(&report)).into_response())
+            } else {
+                Ok(Json(report).into_response())
+            }
+        }
+        Ok(None) => {
+            warn!("No user found with ID: {}", user_id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            error!(
+                "Failed to build year in review {} for user {}: {}",
+                year, user_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Colours go into CSS, where HTML escaping doesn't help, so only `#rrggbb` is let through
+fn css_color(color: &str) -> &str {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => color,
+        _ => FALLBACK_COLOR,
+    }
+}
+
+fn render_highlight(label: &str, book: Option<&YearInReviewBook>, detail: String) -> String {
+    match book {
+        Some(book) => format!(
+            r#"<div class="highlight"><h3>{}</h3><p class="title">{}</p><p class="author">{}</p><p class="detail">{}</p></div>"#,
+            escape_html(label),
+            escape_html(&book.title),
+            escape_html(&book.author),
+            escape_html(&detail)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Render the report as a standalone HTML page with inline styles, suitable for sharing
+pub fn render_year_in_review_html(report: &YearInReview) -> String {
+    let mut highlights = String::new();
+    highlights.push_str(&render_highlight(
+        "Highest rated",
+        report.highest_rated.as_ref(),
+        format!(
+            "{} stars",
+            report
+                .highest_rated
+                .as_ref()
+                .and_then(|b| b.rating)
+                .unwrap_or_default()
+        ),
+    ));
+    highlights.push_str(&render_highlight(
+        "Lowest rated",
+        report.lowest_rated.as_ref(),
+        format!(
+            "{} stars",
+            report
+                .lowest_rated
+                .as_ref()
+                .and_then(|b| b.rating)
+                .unwrap_or_default()
+        ),
+    ));
+    highlights.push_str(&render_highlight(
+        "Longest book",
+        report.longest_book.as_ref(),
+        format!(
+            "{} pages",
+            report
+                .longest_book
+                .as_ref()
+                .and_then(|b| b.page_count)
+                .unwrap_or_default()
+        ),
+    ));
+    if let Some(book) = &report.most_journaled_book {
+        highlights.push_str(&format!(
+            r#"<div class="highlight"><h3>Most journaled</h3><p class="title">{}</p><p class="author">{}</p><p class="detail">{} entries</p></div>"#,
+            escape_html(&book.title),
+            escape_html(&book.author),
+            book.journal_count
+        ));
+    }
+    if let Some(genre) = &report.favourite_genre {
+        highlights.push_str(&format!(
+            r#"<div class="highlight" style="border-color: {}"><h3>Favourite genre</h3><p class="title">{}</p><p class="detail">{} books</p></div>"#,
+            css_color(&genre.color),
+            escape_html(&genre.name),
+            genre.book_count
+        ));
+    }
+
+    let covers: String = report
+        .covers
+        .iter()
+        .map(|cover| match &cover.cover_image {
+            Some(src) if !src.is_empty() => format!(
+                r#"<img class="cover" src="{}" alt="{}" title="{}">"#,
+                escape_html(src),
+                escape_html(&cover.title),
+                escape_html(&cover.title)
+            ),
+            _ => format!(
+                r#"<div class="cover placeholder">{}</div>"#,
+                escape_html(&cover.title)
+            ),
+        })
+        .collect();
+
+    let average = report
+        .average_rating
+        .map(|r| format!("{r:.2}"))
+        .unwrap_or_else(|| "-".to_string());
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{name}'s {year} in books</title>
+<style>
+body {{ margin: 0; padding: 2rem; background: #18181b; color: #f4f4f5; font-family: system-ui, sans-serif; }}
+h1 {{ margin-top: 0; color: {color}; }}
+.stats {{ display: flex; flex-wrap: wrap; gap: 1rem; margin-bottom: 2rem; }}
+.stat {{ background: #27272a; border-radius: 0.5rem; padding: 1rem 1.5rem; min-width: 8rem; }}
+.stat .value {{ font-size: 2rem; font-weight: bold; }}
+.stat .label {{ color: #a1a1aa; }}
+.highlights {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(14rem, 1fr)); gap: 1rem; margin-bottom: 2rem; }}
+.highlight {{ background: #27272a; border-left: 4px solid {color}; border-radius: 0.5rem; padding: 1rem; }}
+.highlight h3 {{ margin: 0 0 0.5rem; color: #a1a1aa; font-size: 0.9rem; text-transform: uppercase; }}
+.highlight p {{ margin: 0.25rem 0; }}
+.highlight .title {{ font-weight: bold; }}
+.highlight .author, .highlight .detail {{ color: #d4d4d8; }}
+.covers {{ display: flex; flex-wrap: wrap; gap: 0.5rem; }}
+.cover {{ width: 6rem; height: 9rem; object-fit: cover; border-radius: 0.25rem; }}
+.placeholder {{ display: flex; align-items: center; justify-content: center; text-align: center; background: #3f3f46; font-size: 0.75rem; padding: 0.25rem; box-sizing: border-box; }}
+</style>
+</head>
+<body>
+<h1>{name}'s {year} in books</h1>
+<div class="stats">
+<div class="stat"><div class="value">{books_read}</div><div class="label">books read</div></div>
+<div class="stat"><div class="value">{total_pages}</div><div class="label">pages</div></div>
+<div class="stat"><div class="value">{average}</div><div class="label">average rating</div></div>
+<div class="stat"><div class="value">{journal_count}</div><div class="label">journal entries</div></div>
+</div>
+<div class="highlights">{highlights}</div>
+<h2>Covers</h2>
+<div class="covers">{covers}</div>
+</body>
+</html>
+"#,
+        name = escape_html(&report.user.name),
+        color = css_color(&report.user.color),
+        year = report.year,
+        books_read = report.books_read,
+        total_pages = report.total_pages,
+        average = average,
+        journal_count = report.journal_count,
+        highlights = highlights,
+        covers = covers,
+    )
+}
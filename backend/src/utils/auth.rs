@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use axum::extract::{
+    FromRequestParts, MatchedPath, OptionalFromRequestParts, Path, Request, State,
+};
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, Method, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, error, warn};
+
+use crate::db::book_queries::get_book_owner_id_query;
+use crate::db::{
+    book_in_library_query, get_default_library_id_query, get_journal_owner_id_query,
+    get_user_role_query, is_library_member_query, journal_in_library_query, use_api_token_query,
+};
+use crate::utils::{extract_library_id_from_headers, extract_user_id_from_headers};
+
+const API_TOKEN_PREFIX: &str = "hl_";
+// How much of a token is kept in the clear so people can tell their tokens apart
+const API_TOKEN_DISPLAY_LENGTH: usize = 8;
+
+/// Generates a new API token, returning (token, hash to store, prefix to display)
+pub fn generate_api_token() -> (String, String, String) {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    let token = format!("{API_TOKEN_PREFIX}{secret}");
+    let prefix = token[..API_TOKEN_DISPLAY_LENGTH].to_string();
+    (token.clone(), hash_api_token(&token), prefix)
+}
+
+/// Generates the token for a new share link, the only secret in its URL
+pub fn generate_share_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+/// Hex-encoded SHA-256 of a token, as stored in api_tokens.token_hash
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Added to requests authenticated with an API token
+#[derive(Clone, Debug)]
+pub struct ApiTokenAuth {
+    pub token_id: i64,
+    pub user_id: i64,
+}
+
+/// Accepts `Authorization: Bearer <token>` alongside the `currentUserId` header.
+///
+/// A valid token sets `currentUserId` to its owner, replacing any the client sent, so
+/// handlers see token requests like any other. Unknown or revoked tokens get 401 and
+/// read-only tokens get 403 for anything but GET. Requests without an Authorization
+/// header pass through unchanged.
+pub async fn api_token_auth(
+    State(pool): State<Pool<Sqlite>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(authorization) = request.headers().get(header::AUTHORIZATION) else {
+        return Ok(next.run(request).await);
+    };
+
+    let Some(token) = authorization
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+    else {
+        warn!("Authorization header is not a Bearer token");
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let api_token = match use_api_token_query(&pool, &hash_api_token(token)).await {
+        Ok(Some(api_token)) => api_token,
+        Ok(None) => {
+            warn!("Unknown or revoked API token");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Err(e) => {
+            error!("Failed to check API token: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if api_token.read_only && !matches!(*request.method(), Method::GET | Method::HEAD) {
+        warn!(
+            "Read-only API token {} used for {} {}",
+            api_token.id,
+            request.method(),
+            request.uri()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    debug!(
+        "Request authenticated with API token {} of user {}",
+        api_token.id, api_token.user_id
+    );
+    request
+        .headers_mut()
+        .insert("currentUserId", HeaderValue::from(api_token.user_id));
+    request.extensions_mut().insert(ApiTokenAuth {
+        token_id: api_token.id,
+        user_id: api_token.user_id,
+    });
+
+    Ok(next.run(request).await)
+}
+
+/// The user a request acts as, from an API token or the `currentUserId` header
+#[derive(Clone, Debug)]
+pub struct CurrentUser {
+    pub id: i64,
+    // "admin", "member", or "child"
+    pub role: String,
+    // Set when the request was authenticated with an API token
+    pub api_token: Option<ApiTokenAuth>,
+}
+
+impl CurrentUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+
+    pub fn is_child(&self) -> bool {
+        self.role == "child"
+    }
+
+    /// 403 for anyone but admins, who manage users, tags and genres
+    pub fn ensure_admin(&self) -> Result<(), StatusCode> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            warn!("User {} ({}) is not an admin", self.id, self.role);
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+
+    /// Children can only change their own data; everyone else can edit shared books
+    /// and other people's entries as before
+    pub fn can_edit(&self, owner_id: i64) -> bool {
+        !self.is_child() || self.id == owner_id
+    }
+}
+
+impl FromRequestParts<Pool<Sqlite>> for CurrentUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        pool: &Pool<Sqlite>,
+    ) -> Result<Self, Self::Rejection> {
+        let api_token = parts.extensions.get::<ApiTokenAuth>().cloned();
+        let id = match &api_token {
+            Some(api_token) => api_token.user_id,
+            None => extract_user_id_from_headers(&parts.headers)?,
+        };
+
+        match get_user_role_query(pool, id).await {
+            Ok(Some(role)) => Ok(CurrentUser {
+                id,
+                role,
+                api_token,
+            }),
+            Ok(None) => {
+                warn!("Request made as unknown user {}", id);
+                Err(StatusCode::UNAUTHORIZED)
+            }
+            Err(e) => {
+                error!("Failed to load role of user {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+/// 403 unless the current user may edit the book. Books belong to whoever added them.
+pub async fn ensure_can_edit_book(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    book_id: i64,
+) -> Result<(), StatusCode> {
+    if !current_user.is_child() {
+        return Ok(());
+    }
+    match get_book_owner_id_query(pool, book_id).await {
+        Ok(Some(owner_id)) if current_user.can_edit(owner_id) => Ok(()),
+        Ok(Some(_)) => {
+            warn!(
+                "Child user {} tried to edit book {} added by someone else",
+                current_user.id, book_id
+            );
+            Err(StatusCode::FORBIDDEN)
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to look up owner of book {}: {}", book_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// 403 unless the current user may edit the journal entry
+pub async fn ensure_can_edit_journal(
+    pool: &Pool<Sqlite>,
+    current_user: &CurrentUser,
+    journal_id: i64,
+) -> Result<(), StatusCode> {
+    if !current_user.is_child() {
+        return Ok(());
+    }
+    match get_journal_owner_id_query(pool, journal_id).await {
+        Ok(Some(owner_id)) if current_user.can_edit(owner_id) => Ok(()),
+        Ok(Some(_)) => {
+            warn!(
+                "Child user {} tried to edit journal entry {} of another user",
+                current_user.id, journal_id
+            );
+            Err(StatusCode::FORBIDDEN)
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(
+                "Failed to look up owner of journal entry {}: {}",
+                journal_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The library a request works in, from the `currentLibraryId` header. Without the
+/// header, the first library the user joined is used.
+#[derive(Clone, Copy, Debug)]
+pub struct CurrentLibrary {
+    pub id: i64,
+}
+
+// The user a request acts as, if it says
+fn request_user_id(parts: &Parts) -> Option<i64> {
+    match parts.extensions.get::<ApiTokenAuth>() {
+        Some(api_token) => Some(api_token.user_id),
+        None => extract_user_id_from_headers(&parts.headers).ok(),
+    }
+}
+
+async fn resolve_library(
+    pool: &Pool<Sqlite>,
+    parts: &Parts,
+    user_id: i64,
+) -> Result<CurrentLibrary, StatusCode> {
+    let library_id = match extract_library_id_from_headers(&parts.headers)? {
+        Some(library_id) => match is_library_member_query(pool, library_id, user_id).await {
+            Ok(true) => library_id,
+            Ok(false) => {
+                warn!("User {} is not a member of library {}", user_id, library_id);
+                return Err(StatusCode::FORBIDDEN);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to check membership of library {}: {}",
+                    library_id, e
+                );
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+        None => match get_default_library_id_query(pool, user_id).await {
+            Ok(Some(library_id)) => library_id,
+            Ok(None) => {
+                warn!("User {} doesn't belong to any library", user_id);
+                return Err(StatusCode::FORBIDDEN);
+            }
+            Err(e) => {
+                error!("Failed to find default library of user {}: {}", user_id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        },
+    };
+
+    Ok(CurrentLibrary { id: library_id })
+}
+
+impl FromRequestParts<Pool<Sqlite>> for CurrentLibrary {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        pool: &Pool<Sqlite>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(library) = parts.extensions.get::<CurrentLibrary>() {
+            return Ok(*library);
+        }
+
+        let user_id = match parts.extensions.get::<ApiTokenAuth>() {
+            Some(api_token) => api_token.user_id,
+            None => extract_user_id_from_headers(&parts.headers)?,
+        };
+        resolve_library(pool, parts, user_id).await
+    }
+}
+
+/// None for requests that don't say who they're from, like the login screen's user picker
+impl OptionalFromRequestParts<Pool<Sqlite>> for CurrentLibrary {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        pool: &Pool<Sqlite>,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if request_user_id(parts).is_none() {
+            return Ok(None);
+        }
+        <Self as FromRequestParts<_>>::from_request_parts(parts, pool)
+            .await
+            .map(Some)
+    }
+}
+
+// Routes that work without saying who's asking: the user picker on the login screen,
+// and URLs loaded by the browser on its own or handed to people outside the household
+const PUBLIC_ROUTES: &[(Method, &str)] = &[
+    (Method::GET, "/health"),
+    (Method::GET, "/users"),
+    (Method::POST, "/users/select"),
+    (Method::GET, "/users/{id}/avatar"),
+    (Method::GET, "/attachments/{file}"),
+    (Method::GET, "/shared/{token}"),
+];
+
+/// Rejects requests that don't say who they're from with 401, except on `PUBLIC_ROUTES`.
+///
+/// Also keeps `/books/{id}/...`, `/journals/{id}/...` and `/users/{id}/...` requests
+/// inside the active library: a book, journal entry or user from another library is a 404.
+pub async fn library_scope(
+    State(pool): State<Pool<Sqlite>>,
+    Path(params): Path<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let is_public = request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|matched| {
+            PUBLIC_ROUTES
+                .iter()
+                .any(|(method, route)| method == request.method() && *route == matched.as_str())
+        });
+    if is_public {
+        return Ok(next.run(request).await);
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let Some(user_id) = request_user_id(&parts) else {
+        warn!(
+            "{} {} doesn't say who it's from",
+            parts.method,
+            parts.uri.path()
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    // Books and journals are named by the first id in their path; every user id is checked
+    let path = parts.uri.path();
+    let scoped: Vec<(&str, &String)> = if path.starts_with("/books/") {
+        params
+            .get("book_id")
+            .or_else(|| params.get("id"))
+            .map(|id| ("book", id))
+            .into_iter()
+            .collect()
+    } else if path.starts_with("/journals/") {
+        params
+            .get("journal_id")
+            .or_else(|| params.get("id"))
+            .map(|id| ("journal", id))
+            .into_iter()
+            .collect()
+    } else if path.starts_with("/users/") {
+        ["id", "other_id"]
+            .iter()
+            .filter_map(|key| params.get(*key))
+            .map(|id| ("user", id))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    // Malformed ids are left for the handler's own Path extractor to reject
+    let ids: Vec<(&str, i64)> = scoped
+        .into_iter()
+        .filter_map(|(kind, id)| Some((kind, id.parse().ok()?)))
+        .collect();
+    if ids.is_empty() {
+        return Ok(next.run(Request::from_parts(parts, body)).await);
+    }
+
+    let library = resolve_library(&pool, &parts, user_id).await?;
+    for (kind, id) in ids {
+        let in_library = match kind {
+            "book" => book_in_library_query(&pool, id, library.id).await,
+            "journal" => journal_in_library_query(&pool, id, library.id).await,
+            _ => is_library_member_query(&pool, library.id, id).await,
+        };
+        match in_library {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!("No {} {} in library {}", kind, id, library.id);
+                return Err(StatusCode::NOT_FOUND);
+            }
+            Err(e) => {
+                error!("Failed to check library of {} {}: {}", kind, id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
+    parts.extensions.insert(library);
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
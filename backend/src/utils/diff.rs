@@ -0,0 +1,31 @@
+use similar::{Algorithm, ChangeTag, capture_diff_slices};
+
+use crate::models::journal_revisions::DiffLine;
+
+fn diff_line(kind: &str, text: &str) -> DiffLine {
+    DiffLine {
+        kind: kind.to_string(),
+        text: text.to_string(),
+    }
+}
+
+/// Line-by-line diff of two texts, as the lines that stayed, were removed from `old`,
+/// and were added in `new`, in reading order.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    // Myers' algorithm runs in linear space, so long entries can't blow up memory
+    capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines)
+        .iter()
+        .flat_map(|op| op.iter_changes(&old_lines, &new_lines))
+        .map(|change| {
+            let kind = match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Delete => "removed",
+                ChangeTag::Insert => "added",
+            };
+            diff_line(kind, change.value())
+        })
+        .collect()
+}
@@ -0,0 +1,13 @@
+/// Returns true if the database rejected a write because of a UNIQUE constraint
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|db_error| db_error.is_unique_violation())
+}
+
+/// Returns true if the database rejected a write because it referenced a row that doesn't exist
+pub fn is_foreign_key_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|db_error| db_error.is_foreign_key_violation())
+}
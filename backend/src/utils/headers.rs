@@ -33,3 +33,23 @@ pub fn extract_user_id_from_headers(headers: &HeaderMap) -> Result<i64, StatusCo
 
     Ok(user_id)
 }
+
+/// Extracts the active library from the optional "currentLibraryId" header
+///
+/// # Returns
+/// * `Ok(Some(i64))` - The parsed library ID
+/// * `Ok(None)` - The header is missing, so the user's default library applies
+/// * `Err(StatusCode)` - BAD_REQUEST if the header is invalid UTF-8 or not a valid i64
+pub fn extract_library_id_from_headers(headers: &HeaderMap) -> Result<Option<i64>, StatusCode> {
+    let Some(header_value) = headers.get("currentLibraryId") else {
+        return Ok(None);
+    };
+
+    match header_value.to_str().map(str::parse::<i64>) {
+        Ok(Ok(id)) => Ok(Some(id)),
+        _ => {
+            error!("Invalid currentLibraryId header: {:?}", header_value);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
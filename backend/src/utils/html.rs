@@ -0,0 +1,322 @@
+/// Escapes text for safe inclusion in HTML element content and attribute values
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// A piece of an HTML document: a run of text or a single tag
+enum HtmlToken<'a> {
+    Text(&'a str),
+    Tag {
+        name: String,
+        closing: bool,
+        attributes: &'a str,
+    },
+}
+
+// Splits HTML into text and tags. Comments and doctypes are dropped. This is only meant
+// for the well-formed HTML the journal editor produces, not arbitrary web pages.
+fn tokenize_html(html: &str) -> Vec<HtmlToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        if start > 0 {
+            tokens.push(HtmlToken::Text(&rest[..start]));
+        }
+        let after = &rest[start + 1..];
+
+        if after.starts_with("!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+
+        // Find the closing '>' that isn't inside a quoted attribute value
+        let mut quote = None;
+        let end = after.char_indices().find_map(|(i, c)| match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                None
+            }
+            (Some(q), _) if c == q => {
+                quote = None;
+                None
+            }
+            (None, '>') => Some(i),
+            _ => None,
+        });
+        let Some(end) = end else {
+            tokens.push(HtmlToken::Text(&rest[start..]));
+            rest = "";
+            break;
+        };
+
+        let inner = after[..end].trim();
+        rest = &after[end + 1..];
+        if inner.starts_with('!') || inner.starts_with('?') {
+            continue;
+        }
+
+        let closing = inner.starts_with('/');
+        let inner = inner.trim_start_matches('/');
+        let name_end = inner
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(inner.len());
+        tokens.push(HtmlToken::Tag {
+            name: inner[..name_end].to_ascii_lowercase(),
+            closing,
+            attributes: inner[name_end..].trim().trim_end_matches('/').trim_end(),
+        });
+    }
+
+    if !rest.is_empty() {
+        tokens.push(HtmlToken::Text(rest));
+    }
+    tokens
+}
+
+// The value of one attribute from a tag's attribute text, e.g. `src` from `src="a.png" alt=""`
+fn html_attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(index) = rest.find(name) {
+        let preceded_by_space = index == 0 || rest[..index].ends_with(char::is_whitespace);
+        let after = rest[index + name.len()..].trim_start();
+        if preceded_by_space && let Some(value) = after.strip_prefix('=') {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let end = value[1..].find(quote)?;
+                return Some(decode_html_entities(&value[1..1 + end]));
+            }
+            let end = value.find(char::is_whitespace).unwrap_or(value.len());
+            return Some(decode_html_entities(&value[..end]));
+        }
+        rest = &rest[index + name.len()..];
+    }
+    None
+}
+
+/// Turns HTML entities like `&amp;`, `&nbsp;` and `&#8217;` back into the characters they stand for
+pub fn decode_html_entities(input: &str) -> String {
+    let mut decoded = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let entity = after
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| (&after[..end], end));
+
+        let character = entity.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#').and_then(|n| n.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+
+        match (character, entity) {
+            (Some(c), Some((_, end))) => {
+                decoded.push(c);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Converts journal editor HTML to Markdown.
+///
+/// Paragraphs, headings, emphasis, links, images, lists, quotes, code and rules are
+/// converted; any other tags are dropped and their text kept.
+pub fn html_to_markdown(html: &str) -> String {
+    // Output is written to the innermost buffer; blockquotes get their own so their
+    // lines can be prefixed with "> " once they're complete
+    let mut buffers = vec![String::new()];
+    let mut lists: Vec<Option<usize>> = Vec::new();
+    let mut links: Vec<String> = Vec::new();
+    let mut in_pre = false;
+
+    for token in tokenize_html(html) {
+        let out = buffers.last_mut().expect("there is always a buffer");
+        match token {
+            HtmlToken::Text(text) => {
+                let text = decode_html_entities(text);
+                if in_pre {
+                    out.push_str(&text);
+                } else {
+                    let collapsed = text.replace(['\n', '\r', '\t'], " ");
+                    if !(collapsed.trim().is_empty() && (out.is_empty() || out.ends_with('\n'))) {
+                        out.push_str(&collapsed);
+                    }
+                }
+            }
+            HtmlToken::Tag {
+                name,
+                closing,
+                attributes,
+            } => match (name.as_str(), closing) {
+                ("p" | "div", false) => {}
+                ("p" | "div", true) if lists.is_empty() => out.push_str("\n\n"),
+                ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                    let level = name[1..].parse().unwrap_or(1);
+                    out.push_str(&"#".repeat(level));
+                    out.push(' ');
+                }
+                ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => out.push_str("\n\n"),
+                ("br", _) => out.push_str(if in_pre { "\n" } else { "  \n" }),
+                ("hr", _) => out.push_str("\n---\n\n"),
+                ("strong" | "b", _) => out.push_str("**"),
+                ("em" | "i", _) => out.push('*'),
+                ("s" | "del" | "strike", _) => out.push_str("~~"),
+                ("code", _) if !in_pre => out.push('`'),
+                ("pre", false) => {
+                    in_pre = true;
+                    out.push_str("```\n");
+                }
+                ("pre", true) => {
+                    in_pre = false;
+                    if !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str("```\n\n");
+                }
+                ("a", false) => {
+                    links.push(html_attribute(attributes, "href").unwrap_or_default());
+                    out.push('[');
+                }
+                ("a", true) => {
+                    let href = links.pop().unwrap_or_default();
+                    out.push_str(&format!("]({href})"));
+                }
+                ("img", _) => {
+                    let src = html_attribute(attributes, "src").unwrap_or_default();
+                    let alt = html_attribute(attributes, "alt").unwrap_or_default();
+                    out.push_str(&format!("![{alt}]({src})"));
+                }
+                ("ul", false) => lists.push(None),
+                ("ol", false) => lists.push(Some(1)),
+                ("ul" | "ol", true) => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        out.push_str("\n\n");
+                    }
+                }
+                ("li", false) => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                    match lists.last_mut() {
+                        Some(Some(number)) => {
+                            out.push_str(&format!("{number}. "));
+                            *number += 1;
+                        }
+                        _ => out.push_str("- "),
+                    }
+                }
+                ("blockquote", false) => buffers.push(String::new()),
+                ("blockquote", true) if buffers.len() > 1 => {
+                    let quoted = buffers.pop().unwrap_or_default();
+                    let out = buffers.last_mut().expect("there is always a buffer");
+                    for line in quoted.trim().lines() {
+                        out.push_str("> ");
+                        out.push_str(line);
+                        out.push('\n');
+                    }
+                    out.push('\n');
+                }
+                _ => {}
+            },
+        }
+    }
+
+    let mut markdown = String::new();
+    for buffer in buffers {
+        markdown.push_str(&buffer);
+    }
+
+    // Tidy up the blank lines left between blocks
+    let mut tidied = String::with_capacity(markdown.len());
+    let mut blank_lines = 0;
+    for line in markdown.trim().lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        tidied.push_str(line);
+        tidied.push('\n');
+    }
+    tidied
+}
+
+// Elements that never have content, which XHTML requires to be written as `<br/>`
+const VOID_ELEMENTS: [&str; 10] = [
+    "area", "br", "col", "hr", "img", "input", "link", "meta", "source", "wbr",
+];
+
+/// Rewrites journal editor HTML as XHTML, e.g. for an EPUB: void elements are
+/// self-closed and only XML's own named entities are used
+pub fn html_to_xhtml(html: &str) -> String {
+    let mut xhtml = String::with_capacity(html.len());
+
+    for token in tokenize_html(html) {
+        match token {
+            HtmlToken::Text(text) => xhtml.push_str(&escape_html(&decode_html_entities(text))),
+            HtmlToken::Tag {
+                name,
+                closing,
+                attributes,
+            } => {
+                if VOID_ELEMENTS.contains(&name.as_str()) {
+                    if !closing {
+                        xhtml.push_str(&format!("<{name}"));
+                        if !attributes.is_empty() {
+                            xhtml.push(' ');
+                            xhtml.push_str(attributes);
+                        }
+                        xhtml.push_str("/>");
+                    }
+                } else if closing {
+                    xhtml.push_str(&format!("</{name}>"));
+                } else if attributes.is_empty() {
+                    xhtml.push_str(&format!("<{name}>"));
+                } else {
+                    xhtml.push_str(&format!("<{name} {attributes}>"));
+                }
+            }
+        }
+    }
+
+    xhtml
+}
@@ -0,0 +1,48 @@
+/// Identifies an image from its leading bytes rather than trusting the declared content type
+///
+/// # Returns
+/// * `Some((content_type, extension))` - for PNG, JPEG, GIF and WebP images
+/// * `None` - for anything else
+pub fn sniff_image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("image/png", "png"))
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(("image/jpeg", "jpg"))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(("image/gif", "gif"))
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some(("image/webp", "webp"))
+    } else {
+        None
+    }
+}
+
+/// Square sizes, in pixels, that uploaded avatars are stored at
+pub const AVATAR_SIZES: [u32; 3] = [64, 128, 256];
+
+/// Crops an image to a centred square and scales it to each of `AVATAR_SIZES`.
+///
+/// # Returns
+/// * `Ok(Vec<(size, png_bytes)>)` - one PNG per size
+/// * `Err(ImageError)` - if the image can't be decoded
+pub fn resize_avatar(bytes: &[u8]) -> Result<Vec<(u32, Vec<u8>)>, image::ImageError> {
+    let decoded = image::load_from_memory(bytes)?;
+
+    let side = decoded.width().min(decoded.height());
+    let square = decoded.crop_imm(
+        (decoded.width() - side) / 2,
+        (decoded.height() - side) / 2,
+        side,
+        side,
+    );
+
+    AVATAR_SIZES
+        .iter()
+        .map(|&size| {
+            let resized = square.resize_exact(size, size, image::imageops::FilterType::Lanczos3);
+            let mut png = std::io::Cursor::new(Vec::new());
+            resized.write_to(&mut png, image::ImageFormat::Png)?;
+            Ok((size, png.into_inner()))
+        })
+        .collect()
+}
@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Write};
+
+use chrono::Utc;
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::models::journal_attachments::JournalAttachment;
+use crate::models::journal_exports::JournalExportEntry;
+use crate::utils::html::{escape_html, html_to_markdown, html_to_xhtml};
+
+/// Lowercase ASCII words joined by dashes, for file names: "The Left Hand of Darkness!" -> "the-left-hand-of-darkness"
+pub fn slugify(value: &str) -> String {
+    let slug = value
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+// Bundled images are named after the attachment so links stay stable between exports
+fn attachment_file_name(attachment: &JournalAttachment) -> String {
+    let extension = attachment
+        .storage_key
+        .rsplit_once('.')
+        .map_or("bin", |(_, extension)| extension);
+    format!("attachment-{}.{}", attachment.id, extension)
+}
+
+// Point embedded attachments at their bundled copies. Attachment URLs are matched
+// with their quotes so `/attachments/3` doesn't also rewrite `/attachments/30`.
+fn rewrite_attachment_urls(
+    content: &str,
+    attachments: &[JournalAttachment],
+    prefix: &str,
+) -> String {
+    attachments
+        .iter()
+        .fold(content.to_string(), |content, attachment| {
+            content.replace(
+                &format!("\"{}\"", attachment.url),
+                &format!("\"{}{}\"", prefix, attachment_file_name(attachment)),
+            )
+        })
+}
+
+// "2026-03-05 10:14:23" -> "2026-03-05"
+fn entry_date(entry: &JournalExportEntry) -> &str {
+    entry
+        .journal
+        .created_at
+        .as_deref()
+        .and_then(|created_at| created_at.get(..10))
+        .unwrap_or("undated")
+}
+
+// Claim `name`, or `name-2`, `name-3`... if it has been used already
+pub fn unique_name(used: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut suffix = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{name}-{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+/// A double-quoted YAML string, safe for any title or name
+pub fn yaml_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+fn front_matter(entry: &JournalExportEntry) -> String {
+    let mut yaml = String::from("---\n");
+    yaml.push_str(&format!("title: {}\n", yaml_string(&entry.journal.title)));
+    yaml.push_str(&format!("book: {}\n", yaml_string(&entry.book_title)));
+    yaml.push_str(&format!("author: {}\n", yaml_string(&entry.book_author)));
+    if let Some(created_at) = &entry.journal.created_at {
+        yaml.push_str(&format!("date: {}\n", yaml_string(created_at)));
+    }
+    if entry.journal.tags.is_empty() {
+        yaml.push_str("tags: []\n");
+    } else {
+        yaml.push_str("tags:\n");
+        for tag in &entry.journal.tags {
+            yaml.push_str(&format!("  - {}\n", yaml_string(&tag.name)));
+        }
+    }
+    match entry.rating {
+        Some(rating) => yaml.push_str(&format!("rating: {rating}\n")),
+        None => yaml.push_str("rating: null\n"),
+    }
+    yaml.push_str("---\n\n");
+    yaml
+}
+
+/// Builds a zip with a folder per book holding one Markdown file per journal entry,
+/// each with YAML front matter, and an `images` folder with the attached files.
+///
+/// `files` holds the contents of the attachments, keyed by attachment id; attachments
+/// without contents are left out.
+pub fn build_markdown_export(
+    entries: &[JournalExportEntry],
+    files: &HashMap<i64, Vec<u8>>,
+) -> ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut book_folders: HashMap<i64, String> = HashMap::new();
+    let mut used_folders = HashSet::new();
+    let mut used_files = HashSet::new();
+
+    for entry in entries {
+        let folder = book_folders
+            .entry(entry.journal.book_id)
+            .or_insert_with(|| unique_name(&mut used_folders, slugify(&entry.book_title)))
+            .clone();
+
+        let name = unique_name(
+            &mut used_files,
+            format!(
+                "{folder}/{}-{}",
+                entry_date(entry),
+                slugify(&entry.journal.title)
+            ),
+        );
+        let content =
+            rewrite_attachment_urls(&entry.journal.content, &entry.attachments, "images/");
+
+        zip.start_file(format!("{name}.md"), options)?;
+        zip.write_all(front_matter(entry).as_bytes())?;
+        zip.write_all(format!("# {}\n\n", entry.journal.title).as_bytes())?;
+        zip.write_all(html_to_markdown(&content).as_bytes())?;
+
+        for attachment in &entry.attachments {
+            if let Some(bytes) = files.get(&attachment.id) {
+                zip.start_file(
+                    format!("{folder}/images/{}", attachment_file_name(attachment)),
+                    options,
+                )?;
+                zip.write_all(bytes)?;
+            }
+        }
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+const EPUB_CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const EPUB_STYLESHEET: &str = "body { font-family: serif; line-height: 1.5; margin: 1em; }
+h1 { margin-bottom: 0.2em; }
+.meta { color: #555; font-size: 0.9em; margin-top: 0; }
+blockquote { border-left: 3px solid #ccc; margin-left: 0; padding-left: 1em; }
+img { max-width: 100%; }
+";
+
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="en">
+<head>
+  <meta charset="UTF-8"/>
+  <title>{}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        escape_html(title),
+        body
+    )
+}
+
+fn epub_chapter(entry: &JournalExportEntry) -> String {
+    let mut meta = vec![
+        format!(
+            "{} by {}",
+            escape_html(&entry.book_title),
+            escape_html(&entry.book_author)
+        ),
+        escape_html(entry_date(entry)),
+    ];
+    if let Some(rating) = entry.rating {
+        meta.push(format!("Rated {rating}"));
+    }
+    if !entry.journal.tags.is_empty() {
+        let tags: Vec<String> = entry
+            .journal
+            .tags
+            .iter()
+            .map(|tag| escape_html(&tag.name))
+            .collect();
+        meta.push(tags.join(", "));
+    }
+
+    let content = rewrite_attachment_urls(&entry.journal.content, &entry.attachments, "images/");
+    let body = format!(
+        "<h1>{}</h1>\n<p class=\"meta\">{}</p>\n{}",
+        escape_html(&entry.journal.title),
+        meta.join(" · "),
+        html_to_xhtml(&content)
+    );
+    xhtml_document(&entry.journal.title, &body)
+}
+
+/// Builds an EPUB 3 "reading diary" with a chapter per journal entry, grouped by book
+/// in the table of contents, and the attached images bundled.
+///
+/// `files` holds the contents of the attachments, keyed by attachment id; attachments
+/// without contents are left out.
+pub fn build_epub_export(
+    title: &str,
+    entries: &[JournalExportEntry],
+    files: &HashMap<i64, Vec<u8>>,
+) -> ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // Readers identify EPUBs by an uncompressed `mimetype` file at the very start
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(EPUB_CONTAINER.as_bytes())?;
+
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(EPUB_STYLESHEET.as_bytes())?;
+
+    let mut manifest = vec![
+        r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#
+            .to_string(),
+        r#"<item id="style" href="style.css" media-type="text/css"/>"#.to_string(),
+    ];
+    let mut spine = Vec::new();
+    let mut toc = String::new();
+    let mut current_book = None;
+
+    for entry in entries {
+        let chapter = format!("entry-{}.xhtml", entry.journal.id);
+        zip.start_file(format!("OEBPS/{chapter}"), deflated)?;
+        zip.write_all(epub_chapter(entry).as_bytes())?;
+
+        manifest.push(format!(
+            r#"<item id="entry-{}" href="{chapter}" media-type="application/xhtml+xml"/>"#,
+            entry.journal.id
+        ));
+        spine.push(format!(r#"<itemref idref="entry-{}"/>"#, entry.journal.id));
+
+        if current_book != Some(entry.journal.book_id) {
+            if current_book.is_some() {
+                toc.push_str("</ol></li>\n");
+            }
+            toc.push_str(&format!(
+                "<li><a href=\"{chapter}\">{}</a><ol>\n",
+                escape_html(&entry.book_title)
+            ));
+            current_book = Some(entry.journal.book_id);
+        }
+        toc.push_str(&format!(
+            "<li><a href=\"{chapter}\">{}</a></li>\n",
+            escape_html(&entry.journal.title)
+        ));
+
+        for attachment in &entry.attachments {
+            if let Some(bytes) = files.get(&attachment.id) {
+                let file_name = attachment_file_name(attachment);
+                zip.start_file(format!("OEBPS/images/{file_name}"), deflated)?;
+                zip.write_all(bytes)?;
+                manifest.push(format!(
+                    r#"<item id="attachment-{}" href="images/{file_name}" media-type="{}"/>"#,
+                    attachment.id,
+                    escape_html(&attachment.content_type)
+                ));
+            }
+        }
+    }
+    if current_book.is_some() {
+        toc.push_str("</ol></li>\n");
+    }
+
+    let nav = format!(
+        "<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>",
+        escape_html(title),
+        toc
+    );
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(xhtml_document(title, &nav).as_bytes())?;
+
+    let modified = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let package = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">urn:home-library:journals:{}</dc:identifier>
+    <dc:title>{}</dc:title>
+    <dc:language>en</dc:language>
+    <dc:creator>Home Library</dc:creator>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    {}
+  </manifest>
+  <spine>
+    <itemref idref="nav"/>
+    {}
+  </spine>
+</package>
+"#,
+        slugify(title),
+        escape_html(title),
+        manifest.join("\n    "),
+        spine.join("\n    ")
+    );
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(package.as_bytes())?;
+
+    Ok(zip.finish()?.into_inner())
+}
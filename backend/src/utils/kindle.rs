@@ -0,0 +1,176 @@
+use chrono::NaiveDateTime;
+
+// Separates clippings in a Kindle "My Clippings.txt" file
+const CLIPPING_SEPARATOR: &str = "==========";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClippingKind {
+    Highlight,
+    Note,
+    Bookmark,
+}
+
+/// One entry from a Kindle "My Clippings.txt" file
+#[derive(Debug, Clone)]
+pub struct Clipping {
+    pub title: String,
+    pub author: Option<String>,
+    pub kind: ClippingKind,
+    pub page: Option<i64>,
+    // As printed by the device, e.g. "1520-1522" or "172"
+    pub location: Option<String>,
+    // "YYYY-MM-DD HH:MM:SS", matching the format SQLite's datetime() produces
+    pub clipped_at: Option<String>,
+    pub text: String,
+}
+
+impl Clipping {
+    /// The first and last location covered, expanding abbreviated ranges like "170-72"
+    pub fn location_range(&self) -> Option<(i64, i64)> {
+        let location = self.location.as_deref()?;
+        let (start, end) = location.split_once('-').unwrap_or((location, location));
+        let start_num: i64 = start.trim().parse().ok()?;
+
+        let end = end.trim();
+        let end_num: i64 = if end.len() < start.trim().len() {
+            let prefix = &start.trim()[..start.trim().len() - end.len()];
+            format!("{prefix}{end}").parse().ok()?
+        } else {
+            end.parse().ok()?
+        };
+
+        Some((start_num, end_num))
+    }
+}
+
+/// Parse the contents of a Kindle "My Clippings.txt" file.
+///
+/// Entries that don't look like a clipping are skipped rather than failing the whole file.
+pub fn parse_kindle_clippings(contents: &str) -> Vec<Clipping> {
+    contents
+        .trim_start_matches('\u{feff}')
+        .split(CLIPPING_SEPARATOR)
+        .filter_map(parse_clipping)
+        .collect()
+}
+
+fn parse_clipping(entry: &str) -> Option<Clipping> {
+    let mut lines = entry
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim_end());
+    let title_line = lines.by_ref().find(|line| !line.trim().is_empty())?;
+    let meta_line = lines.next()?;
+    let text = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    let (title, author) = split_title_and_author(title_line.trim());
+    let meta = meta_line.trim().trim_start_matches('-').trim();
+
+    // "Your Highlight on page 12 | Location 170-172 | Added on Sunday, 3 March 2019 10:14:23"
+    let mut sections = meta.split('|').map(str::trim);
+    let description = sections.next()?;
+    let kind = if description.contains("Highlight") {
+        ClippingKind::Highlight
+    } else if description.contains("Note") {
+        ClippingKind::Note
+    } else if description.contains("Bookmark") {
+        ClippingKind::Bookmark
+    } else {
+        return None;
+    };
+
+    let mut page = None;
+    let mut location = None;
+    let mut clipped_at = None;
+    for section in std::iter::once(description).chain(sections) {
+        let lower = section.to_lowercase();
+        if let Some(index) = lower.find("page ") {
+            page = section[index + "page ".len()..]
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|n| n.parse().ok());
+        }
+        if let Some(index) = lower.find("location ").or_else(|| lower.find("loc. ")) {
+            let value = section[index..].split_once(' ').map(|(_, v)| v.trim());
+            location = value.filter(|v| !v.is_empty()).map(str::to_string);
+        }
+        if let Some(added) = section.strip_prefix("Added on ") {
+            clipped_at = parse_clipping_date(added);
+        }
+    }
+
+    Some(Clipping {
+        title,
+        author,
+        kind,
+        page,
+        location,
+        clipped_at,
+        text,
+    })
+}
+
+// "Dune (Dune Chronicles Book 1) (Herbert, Frank)" -> ("Dune (Dune Chronicles Book 1)", "Herbert, Frank")
+fn split_title_and_author(line: &str) -> (String, Option<String>) {
+    if line.ends_with(')')
+        && let Some(open) = line.rfind('(')
+    {
+        let author = line[open + 1..line.len() - 1].trim();
+        let title = line[..open].trim();
+        if !title.is_empty() && !author.is_empty() {
+            return (title.to_string(), Some(author.to_string()));
+        }
+    }
+    (line.to_string(), None)
+}
+
+// Kindles write dates in the device's locale, e.g. "Sunday, 3 March 2019 10:14:23"
+// or "Tuesday, March 5, 2019 10:14:23 PM"
+fn parse_clipping_date(value: &str) -> Option<String> {
+    let without_weekday = value
+        .split_once(", ")
+        .map_or(value, |(_, rest)| rest)
+        .trim();
+
+    [
+        "%d %B %Y %H:%M:%S",
+        "%B %d, %Y %I:%M:%S %p",
+        "%B %d, %Y %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(without_weekday, format).ok())
+    .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Lowercase a title without its subtitle, series notes, and punctuation so that
+/// "Dune (Dune Chronicles, Book 1)" and "Dune" compare equal
+pub fn normalize_title(title: &str) -> String {
+    let main = title.split(['(', '[', ':']).next().unwrap_or(title);
+    normalize_words(main)
+}
+
+/// Lowercase an author name, turning "Herbert, Frank" into "frank herbert"
+pub fn normalize_author(author: &str) -> String {
+    normalize_words(&display_author(author))
+}
+
+/// An author as written on a book: "Herbert, Frank" becomes "Frank Herbert".
+/// Multiple authors separated by ";" are left as they are.
+pub fn display_author(author: &str) -> String {
+    match author.split_once(',') {
+        Some((last, first)) if !author.contains(';') && !first.contains(',') => {
+            format!("{} {}", first.trim(), last.trim())
+        }
+        _ => author.trim().to_string(),
+    }
+}
+
+fn normalize_words(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,3 +1,22 @@
+pub mod auth;
+pub mod diff;
+pub mod errors;
 pub mod headers;
+pub mod html;
+pub mod images;
+pub mod journal_export;
+pub mod kindle;
+pub mod obsidian;
+pub mod ratings;
+pub mod stats;
+pub mod storage;
 
+pub use auth::*;
+pub use diff::*;
+pub use errors::*;
 pub use headers::*;
+pub use html::*;
+pub use images::*;
+pub use ratings::*;
+pub use stats::*;
+pub use storage::*;
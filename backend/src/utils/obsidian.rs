@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+
+use pulldown_cmark::{Options, Parser, html};
+use zip::result::{ZipError, ZipResult};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::models::BookWithDetails;
+use crate::utils::html::html_to_markdown;
+use crate::utils::journal_export::{unique_name, yaml_string};
+
+// Front matter key holding the book's id, which is how imported notes find their book
+const BOOK_ID_KEY: &str = "home_library_book_id";
+
+// Limits on what a zipped vault may unpack to, so a small upload can't expand without end
+const MAX_VAULT_ENTRIES: usize = 10_000;
+const MAX_NOTE_BYTES: u64 = 1024 * 1024;
+const MAX_VAULT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Why `read_obsidian_vault` refused a vault that goes over the limits above
+pub const VAULT_TOO_LARGE: &str = "vault is too large to import";
+
+// Journal entries are `##` sections of the note, so headings inside an entry are moved
+// down two levels on export (h1 becomes `###`) and back up on import
+const HEADING_SHIFT: i32 = 2;
+
+/// One book's note read from an Obsidian vault
+#[derive(Debug, Clone)]
+pub struct ObsidianNote {
+    pub file_name: String,
+    pub book_id: Option<i64>,
+    pub sections: Vec<ObsidianSection>,
+}
+
+/// A `##` section of a note, which holds one journal entry
+#[derive(Debug, Clone)]
+pub struct ObsidianSection {
+    // Set for entries that came from the library, from the `%% journal: 12 %%` marker
+    pub journal_id: Option<i64>,
+    pub title: String,
+    pub markdown: String,
+}
+
+// Rewrite <h1>..<h6> tags `shift` levels lower (or higher, if negative), staying within h1..h6
+fn shift_headings(html: &str, shift: i32) -> String {
+    let levels: Vec<i32> = if shift > 0 {
+        (1..=6).rev().collect()
+    } else {
+        (1..=6).collect()
+    };
+
+    levels.into_iter().fold(html.to_string(), |html, level| {
+        let new_level = (level + shift).clamp(1, 6);
+        html.replace(&format!("<h{level}"), &format!("<h{new_level}"))
+            .replace(&format!("</h{level}>"), &format!("</h{new_level}>"))
+    })
+}
+
+/// A journal entry's HTML as the Markdown written in its note section
+pub fn journal_html_to_note_markdown(content: &str) -> String {
+    html_to_markdown(&shift_headings(content, HEADING_SHIFT))
+        .trim()
+        .to_string()
+}
+
+/// The Markdown of a note section as journal entry HTML
+pub fn note_markdown_to_journal_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH);
+    let mut content = String::new();
+    html::push_html(&mut content, parser);
+    shift_headings(content.trim(), -HEADING_SHIFT)
+}
+
+// Characters Obsidian doesn't allow in note names
+fn note_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']'
+            )
+        })
+        .collect();
+    let name = name.trim();
+    if name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn yaml_list(key: &str, values: &[&str]) -> String {
+    if values.is_empty() {
+        return format!("{key}: []\n");
+    }
+    let items: String = values
+        .iter()
+        .map(|value| format!("  - {}\n", yaml_string(value)))
+        .collect();
+    format!("{key}:\n{items}")
+}
+
+/// Writes a book's note: front matter with the book's details and `user_id`'s rating
+/// and status, then a `##` section for each of `user_id`'s journal entries
+pub fn write_obsidian_note(book: &BookWithDetails, user_id: i64) -> String {
+    let rating = book
+        .ratings
+        .iter()
+        .find(|rating| rating.user_id == user_id)
+        .map(|rating| rating.rating);
+    let status = book
+        .statuses
+        .iter()
+        .find(|status| status.user_id == user_id)
+        .map(|status| status.status_name.as_str());
+    let genres: Vec<&str> = book
+        .genres
+        .iter()
+        .map(|genre| genre.name.as_str())
+        .collect();
+    let tags: Vec<&str> = book.tags.iter().map(|tag| tag.name.as_str()).collect();
+
+    let mut note = String::from("---\n");
+    note.push_str(&format!("{BOOK_ID_KEY}: {}\n", book.id));
+    note.push_str(&format!("title: {}\n", yaml_string(&book.title)));
+    note.push_str(&format!("author: {}\n", yaml_string(&book.author)));
+    match &book.series {
+        Some(series) => note.push_str(&format!("series: {}\n", yaml_string(series))),
+        None => note.push_str("series: null\n"),
+    }
+    note.push_str(&yaml_list("genres", &genres));
+    note.push_str(&yaml_list("tags", &tags));
+    match rating {
+        Some(rating) => note.push_str(&format!("rating: {rating}\n")),
+        None => note.push_str("rating: null\n"),
+    }
+    match status {
+        Some(status) => note.push_str(&format!("status: {}\n", yaml_string(status))),
+        None => note.push_str("status: null\n"),
+    }
+    note.push_str("---\n\n");
+    note.push_str(&format!("# {}\n", book.title));
+
+    for journal in book
+        .journals
+        .iter()
+        .filter(|journal| journal.user.id == user_id)
+    {
+        note.push_str(&format!("\n## {}\n", journal.title));
+        note.push_str(&format!("%% journal: {} %%\n\n", journal.id));
+        let markdown = journal_html_to_note_markdown(&journal.content);
+        if !markdown.is_empty() {
+            note.push_str(&markdown);
+            note.push('\n');
+        }
+    }
+
+    note
+}
+
+/// Builds a zip of a vault folder with one note per book
+pub fn build_obsidian_vault(books: &[BookWithDetails], user_id: i64) -> ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut used_names = HashSet::new();
+
+    for book in books {
+        let name = unique_name(&mut used_names, note_name(&book.title));
+        zip.start_file(format!("Library/{name}.md"), options)?;
+        zip.write_all(write_obsidian_note(book, user_id).as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Reads the Markdown notes out of a zipped vault as (file name, contents).
+///
+/// Obsidian's own settings folder and any files that aren't UTF-8 Markdown are skipped.
+/// Vaults with too many files, or notes that unpack to too much text, are refused with
+/// `ZipError::UnsupportedArchive(VAULT_TOO_LARGE)`.
+pub fn read_obsidian_vault(bytes: &[u8]) -> ZipResult<Vec<(String, String)>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    if archive.len() > MAX_VAULT_ENTRIES {
+        return Err(ZipError::UnsupportedArchive(VAULT_TOO_LARGE));
+    }
+
+    let mut notes = Vec::new();
+    let mut total_bytes = 0;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let name = file.name().to_string();
+        if !file.is_file()
+            || !name.ends_with(".md")
+            || name.split('/').any(|part| part == ".obsidian")
+        {
+            continue;
+        }
+
+        if file.size() > MAX_NOTE_BYTES {
+            return Err(ZipError::UnsupportedArchive(VAULT_TOO_LARGE));
+        }
+
+        // The size in the header can't be trusted, so the read itself is capped too
+        let mut contents = Vec::new();
+        file.by_ref()
+            .take(MAX_NOTE_BYTES + 1)
+            .read_to_end(&mut contents)?;
+        total_bytes += contents.len() as u64;
+        if contents.len() as u64 > MAX_NOTE_BYTES || total_bytes > MAX_VAULT_BYTES {
+            return Err(ZipError::UnsupportedArchive(VAULT_TOO_LARGE));
+        }
+
+        if let Ok(contents) = String::from_utf8(contents) {
+            notes.push((name, contents));
+        }
+    }
+
+    Ok(notes)
+}
+
+// Splits "---\nkey: value\n---\nbody" into its front matter lines and body
+fn split_front_matter(contents: &str) -> (Vec<&str>, &str) {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let Some(rest) = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), contents);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let front_matter = rest[..offset].lines().collect();
+            return (front_matter, &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (Vec::new(), contents)
+}
+
+// "%% journal: 12 %%" -> 12
+fn journal_marker(line: &str) -> Option<i64> {
+    line.trim()
+        .strip_prefix("%%")?
+        .strip_suffix("%%")?
+        .trim()
+        .strip_prefix("journal:")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn finish_section(title: &str, lines: &[&str]) -> ObsidianSection {
+    let mut lines = lines.iter().skip_while(|line| line.trim().is_empty());
+    let mut body: Vec<&str> = Vec::new();
+    let journal_id = match lines.next() {
+        Some(line) => match journal_marker(line) {
+            Some(id) => Some(id),
+            None => {
+                body.push(line);
+                None
+            }
+        },
+        None => None,
+    };
+    body.extend(lines);
+
+    let title = title.trim();
+    ObsidianSection {
+        journal_id,
+        title: if title.is_empty() {
+            "Untitled".to_string()
+        } else {
+            title.to_string()
+        },
+        markdown: body.join("\n").trim().to_string(),
+    }
+}
+
+/// Parses a note written by `write_obsidian_note`, or one written by hand in the same
+/// shape. Text before the first `##` heading is ignored.
+pub fn parse_obsidian_note(file_name: &str, contents: &str) -> ObsidianNote {
+    let (front_matter, body) = split_front_matter(contents);
+    let book_id = front_matter.iter().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() != BOOK_ID_KEY {
+            return None;
+        }
+        value.trim().trim_matches(['"', '\'']).parse().ok()
+    });
+
+    let mut sections = Vec::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+    let mut in_code_block = false;
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let heading = line
+            .strip_prefix("## ")
+            .filter(|_| !in_code_block)
+            .or_else(|| (line.trim_end() == "##" && !in_code_block).then_some(""));
+
+        match (heading, current.as_mut()) {
+            (Some(title), _) => {
+                if let Some((previous_title, lines)) = current.take() {
+                    sections.push(finish_section(previous_title, &lines));
+                }
+                current = Some((title, Vec::new()));
+            }
+            (None, Some((_, lines))) => lines.push(line),
+            (None, None) => {}
+        }
+    }
+    if let Some((title, lines)) = current {
+        sections.push(finish_section(title, &lines));
+    }
+
+    ObsidianNote {
+        file_name: file_name.to_string(),
+        book_id,
+        sections,
+    }
+}
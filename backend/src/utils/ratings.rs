@@ -0,0 +1,12 @@
+/// Ratings at or above this count as a book the user loved
+pub const HIGH_RATING: f64 = 4.0;
+
+/// Returns true if `value` lies within `min..=max` and lands on a step of `increment` from `min`
+pub fn is_on_scale(value: f64, min: f64, max: f64, increment: f64) -> bool {
+    if !value.is_finite() || value < min || value > max || increment <= 0.0 {
+        return false;
+    }
+
+    let steps = (value - min) / increment;
+    (steps - steps.round()).abs() < 1e-9
+}
@@ -0,0 +1,35 @@
+/// Pearson correlation coefficient of paired samples.
+///
+/// Returns `None` when there are fewer than two pairs or either side has no variance,
+/// since the correlation is undefined in those cases.
+pub fn pearson_correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (x, y) in pairs {
+        let dx = x - mean_x;
+        let dy = y - mean_y;
+        covariance += dx * dy;
+        variance_x += dx * dx;
+        variance_y += dy * dy;
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+
+    Some(covariance / (variance_x.sqrt() * variance_y.sqrt()))
+}
+
+/// Round to two decimal places for display
+pub fn round_to_hundredths(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,70 @@
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use sqlx::{Pool, Row, Sqlite};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Files uploaded to the library, stored on disk next to the database file
+/// in an `uploads` directory so they're backed up and mounted along with it.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Storage rooted beside the main database file of the pool
+    pub async fn for_pool(pool: &Pool<Sqlite>) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_one(pool)
+            .await?;
+        let database_file: String = row.get("file");
+
+        let root = Path::new(&database_file)
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("uploads");
+        info!("📁 Using uploads directory: {}", root.display());
+
+        Ok(Self::new(root))
+    }
+
+    /// Generates a new unguessable key under `prefix`, e.g. `journals/3fZ...k9.png`
+    pub fn new_key(prefix: &str, extension: &str) -> String {
+        let name: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        format!("{}/{}.{}", prefix, name, extension)
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    pub async fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        debug!("Writing {} bytes to {}", bytes.len(), path.display());
+        tokio::fs::write(path, bytes).await
+    }
+
+    pub async fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        tokio::fs::read(self.path_for(key)).await
+    }
+
+    /// Removes a stored file. Files that are already gone are not an error.
+    pub async fn remove(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
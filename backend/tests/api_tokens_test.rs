@@ -0,0 +1,137 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::{make_request, make_token_request};
+
+async fn create_token(
+    test_app: &TestApp,
+    user_id: i64,
+    name: &str,
+    read_only: bool,
+) -> (i64, String) {
+    let (status, body) = make_request(
+        test_app,
+        "POST",
+        "/api-tokens",
+        user_id,
+        Some(json!({ "name": name, "read_only": read_only })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    (
+        body["id"].as_i64().unwrap(),
+        body["token"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_create_and_use_api_token() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (token_id, token) = create_token(&test_app, user_id, "NAS script", false).await;
+    assert!(token.starts_with("hl_"));
+
+    // The token itself is never listed again, and only its hash is stored
+    let (status, tokens) = make_request(&test_app, "GET", "/api-tokens", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tokens.as_array().unwrap().len(), 1);
+    assert_eq!(tokens[0]["id"], token_id);
+    assert_eq!(tokens[0]["name"], "NAS script");
+    assert_eq!(tokens[0]["read_only"], false);
+    assert_eq!(tokens[0]["token_prefix"], &token[..8]);
+    assert!(tokens[0]["last_used_at"].is_null());
+    assert!(tokens[0].get("token").is_none());
+    let stored: (String,) = sqlx::query_as("SELECT token_hash FROM api_tokens WHERE id = ?")
+        .bind(token_id)
+        .fetch_one(&test_app.pool)
+        .await
+        .unwrap();
+    assert_ne!(stored.0, token);
+
+    let (status, book) = make_token_request(
+        &test_app,
+        "POST",
+        "/books",
+        &token,
+        Some(json!({ "title": "Added by script", "author": "Cron" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book["user_id"], user_id);
+
+    let (_, tokens) = make_request(&test_app, "GET", "/api-tokens", user_id, None).await;
+    assert!(tokens[0]["last_used_at"].is_string());
+
+    // Tokens can't mint more tokens
+    let (status, _) = make_token_request(
+        &test_app,
+        "POST",
+        "/api-tokens",
+        &token,
+        Some(json!({ "name": "Sneaky" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_token_request(&test_app, "GET", "/books", "hl_not-a-token", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_read_only_api_token() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let (_, token) = create_token(&test_app, user_id, "Dashboard", true).await;
+
+    let (status, _) = make_token_request(&test_app, "GET", "/books", &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_token_request(
+        &test_app,
+        "POST",
+        "/books",
+        &token,
+        Some(json!({ "title": "Nope", "author": "Nobody" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_revoke_api_token() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let (token_id, token) = create_token(&test_app, user_id, "Old script", false).await;
+
+    // Only the owner can revoke a token
+    let uri = format!("/api-tokens/{}", token_id);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = make_request(&test_app, "DELETE", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = make_token_request(&test_app, "GET", "/books", &token, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (_, tokens) = make_request(&test_app, "GET", "/api-tokens", user_id, None).await;
+    assert!(tokens[0]["revoked_at"].is_string());
+
+    let (status, _) = make_request(&test_app, "DELETE", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        "/api-tokens",
+        user_id,
+        Some(json!({ "name": "  " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,183 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use backend::db::migrate_inline_avatars;
+use backend::utils::FileStorage;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::TestApp;
+use image::{GenericImageView, ImageFormat, RgbImage};
+use serde_json::json;
+use std::io::Cursor;
+use utils::{get_raw, make_multipart_request, make_request};
+
+// A 300x200 PNG, so uploads have to be cropped as well as scaled
+fn landscape_png() -> Vec<u8> {
+    let mut png = Cursor::new(Vec::new());
+    RgbImage::from_pixel(300, 200, image::Rgb([200, 40, 40]))
+        .write_to(&mut png, ImageFormat::Png)
+        .unwrap();
+    png.into_inner()
+}
+
+fn stored_avatar_count(test_app: &TestApp) -> usize {
+    std::fs::read_dir(test_app.uploads_dir().join("avatars"))
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_upload_and_serve_avatar() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let uri = format!("/users/{}/avatar", user_id);
+
+    let (status, user) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "me.png", &landscape_png()).await;
+    assert_eq!(status, StatusCode::OK);
+    let avatar_url = user["avatar_image"].as_str().unwrap();
+    assert!(avatar_url.starts_with(&format!("/users/{}/avatar?v=", user_id)));
+    assert_eq!(stored_avatar_count(&test_app), 3);
+
+    let (status, headers, bytes) = get_raw(&test_app, avatar_url).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/png");
+    assert_eq!(
+        image::load_from_memory(&bytes).unwrap().dimensions(),
+        (128, 128)
+    );
+
+    let (status, _, bytes) = get_raw(&test_app, &format!("{}?size=64", uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        image::load_from_memory(&bytes).unwrap().dimensions(),
+        (64, 64)
+    );
+    let (status, _, _) = get_raw(&test_app, &format!("{}?size=65", uri)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Other responses embed the URL rather than the image
+    let (_, users) = make_request(&test_app, "GET", "/users", user_id, None).await;
+    let listed = users
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|user| user["id"] == user_id)
+        .unwrap();
+    assert_eq!(listed["avatar_image"], avatar_url);
+
+    // A new upload replaces the old files
+    let (status, user) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "me.png", &landscape_png()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(user["avatar_image"], avatar_url);
+    assert_eq!(stored_avatar_count(&test_app), 3);
+
+    let (status, _) = make_request(&test_app, "DELETE", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(stored_avatar_count(&test_app), 0);
+    let (status, _, _) = get_raw(&test_app, &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_avatar_uploads_are_rejected() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let member_id = test_app.create_test_user_with_role("member").await;
+    let uri = format!("/users/{}/avatar", user_id);
+
+    let (status, _) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "me.txt", b"not an image").await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    // Looks like a PNG but can't be decoded
+    let (status, _) = make_multipart_request(
+        &test_app,
+        &uri,
+        user_id,
+        "file",
+        "me.png",
+        b"\x89PNG\r\n\x1a\nfake-png-data",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = make_multipart_request(
+        &test_app,
+        &uri,
+        member_id,
+        "file",
+        "me.png",
+        &landscape_png(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(stored_avatar_count(&test_app), 0);
+
+    // Images can't be embedded through the user update any more
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/users/{}", user_id),
+        user_id,
+        Some(json!({ "avatar_image": "data:image/png;base64,iVBORw0KGgo=" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_migrate_inline_avatars() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let broken_user_id = test_app.create_test_user().await;
+
+    let inline = format!("data:image/png;base64,{}", STANDARD.encode(landscape_png()));
+    for (id, avatar) in [
+        (user_id, inline.as_str()),
+        (broken_user_id, "data:image/png;base64,!!"),
+    ] {
+        sqlx::query("UPDATE users SET avatar_image = ? WHERE id = ?")
+            .bind(avatar)
+            .bind(id)
+            .execute(&test_app.pool)
+            .await
+            .unwrap();
+    }
+
+    let storage = FileStorage::new(test_app.uploads_dir());
+    let migrated = migrate_inline_avatars(&test_app.pool, &storage)
+        .await
+        .unwrap();
+    assert_eq!(migrated, 1);
+
+    let (_, users) = make_request(&test_app, "GET", "/users", user_id, None).await;
+    let avatar_of = |id: i64| {
+        users
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|user| user["id"] == id)
+            .unwrap()["avatar_image"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    // The decodable avatar is served from storage; the broken one is left alone
+    let avatar_url = avatar_of(user_id);
+    let (status, _, bytes) = get_raw(&test_app, &avatar_url).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        image::load_from_memory(&bytes).unwrap().dimensions(),
+        (128, 128)
+    );
+    assert_eq!(avatar_of(broken_user_id), "data:image/png;base64,!!");
+
+    let migrated = migrate_inline_avatars(&test_app.pool, &storage)
+        .await
+        .unwrap();
+    assert_eq!(migrated, 0);
+}
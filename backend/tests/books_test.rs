@@ -766,6 +766,7 @@ async fn test_delete_book_with_journals() {
     assert_eq!(status, StatusCode::NOT_FOUND);
 
     // Verify journals are also deleted (cascade delete)
+    #[allow(unused_variables)]
     let (status, body) = make_request(
         &test_app,
         "GET",
@@ -909,3 +910,157 @@ async fn test_delete_book_invalid_id() {
 
     assert_eq!(status, StatusCode::BAD_REQUEST);
 }
+
+#[tokio::test]
+async fn test_random_book_picks_from_tbr() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let tbr_book = test_app
+        .create_test_book(user_id, "On The Pile", "Author")
+        .await;
+    let read_book = test_app
+        .create_test_book(user_id, "Already Read", "Author")
+        .await;
+    test_app
+        .set_reading_status(user_id, tbr_book, 3, "2025-01-01 10:00:00")
+        .await;
+    test_app
+        .set_reading_status(user_id, read_book, 1, "2025-01-01 10:00:00")
+        .await;
+
+    for _ in 0..5 {
+        let (status, body) = make_request(&test_app, "GET", "/books/random", user_id, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], tbr_book);
+        assert_eq!(body["title"], "On The Pile");
+    }
+
+    // Any status can be picked from
+    let (status, body) =
+        make_request(&test_app, "GET", "/books/random?status_id=1", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], read_book);
+}
+
+#[tokio::test]
+async fn test_random_book_filters() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let genre_id = test_app
+        .create_test_genre(user_id, "Horror", "#EF4444")
+        .await;
+
+    let short_horror = test_app
+        .create_test_book(user_id, "Short Horror", "A")
+        .await;
+    let long_horror = test_app.create_test_book(user_id, "Long Horror", "B").await;
+    let short_other = test_app.create_test_book(user_id, "Short Other", "C").await;
+    test_app.add_genre_to_book(short_horror, genre_id).await;
+    test_app.add_genre_to_book(long_horror, genre_id).await;
+    test_app.set_page_count(short_horror, 200).await;
+    test_app.set_page_count(long_horror, 800).await;
+    test_app.set_page_count(short_other, 150).await;
+    for book_id in [short_horror, long_horror, short_other] {
+        test_app
+            .set_reading_status(user_id, book_id, 3, "2025-01-01 10:00:00")
+            .await;
+    }
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/random?genre_id={}&max_pages=300", genre_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], short_horror);
+
+    // Rolling again with the previous pick excluded leaves nothing that matches
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!(
+            "/books/random?genre_id={}&max_pages=300&exclude={}",
+            genre_id, short_horror
+        ),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_random_book_series_starters_and_last_author() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let finished = test_app
+        .create_test_book(user_id, "Finished", "Same Author")
+        .await;
+    let same_author = test_app
+        .create_test_book(user_id, "Another", "same author")
+        .await;
+    let series_one = test_app.create_test_book(user_id, "Saga 1", "Other").await;
+    let series_two = test_app.create_test_book(user_id, "Saga 2", "Other").await;
+    test_app.set_series(series_one, "Saga").await;
+    test_app.set_series(series_two, "Saga").await;
+
+    test_app
+        .set_reading_status(user_id, finished, 1, "2025-03-01 10:00:00")
+        .await;
+    for book_id in [same_author, series_one, series_two] {
+        test_app
+            .set_reading_status(user_id, book_id, 3, "2025-01-01 10:00:00")
+            .await;
+    }
+
+    for _ in 0..5 {
+        let (status, body) = make_request(
+            &test_app,
+            "GET",
+            "/books/random?series_starters=true&skip_last_author=true",
+            user_id,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], series_one);
+    }
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!(
+            "/books/random?weighted=true&exclude={},{}",
+            series_one, series_two
+        ),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], same_author);
+}
+
+#[tokio::test]
+async fn test_random_book_bad_request() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        "/books/random?exclude=1,abc",
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) =
+        make_request(&test_app, "GET", "/books/random?max_pages=0", user_id, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -1,3 +1,6 @@
+// Each test binary only uses some of these helpers
+#![allow(dead_code)]
+
 use axum::Router;
 use sqlx::{Sqlite, SqlitePool, migrate::MigrateDatabase};
 use tempfile::TempDir;
@@ -41,19 +44,46 @@ impl TestApp {
         }
     }
 
+    // Admin, so tests can manage tags and genres without caring about roles
     pub async fn create_test_user(&self) -> i64 {
+        self.create_test_user_with_role("admin").await
+    }
+
+    pub async fn create_test_user_with_role(&self, role: &str) -> i64 {
         let result = sqlx::query_as::<_, (i64,)>(
-            "INSERT INTO users (name, color) VALUES (?, ?) RETURNING id",
+            "INSERT INTO users (name, color, role) VALUES (?, ?, ?) RETURNING id",
         )
         .bind("Test User")
         .bind("bg-blue-500")
+        .bind(role)
         .fetch_one(&self.pool)
         .await
         .unwrap();
 
+        self.add_library_member(1, result.0).await;
+        result.0
+    }
+
+    pub async fn create_test_library(&self, name: &str) -> i64 {
+        let result =
+            sqlx::query_as::<_, (i64,)>("INSERT INTO libraries (name) VALUES (?) RETURNING id")
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+                .unwrap();
+
         result.0
     }
 
+    pub async fn add_library_member(&self, library_id: i64, user_id: i64) {
+        sqlx::query("INSERT OR IGNORE INTO library_members (library_id, user_id) VALUES (?, ?)")
+            .bind(library_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
     pub async fn create_test_tag(&self, user_id: i64, name: &str, color: &str) -> i64 {
         let result = sqlx::query_as::<_, (i64,)>(
             "INSERT INTO tags (user_id, name, color) VALUES (?, ?, ?) RETURNING id",
@@ -114,4 +144,83 @@ impl TestApp {
             .await
             .unwrap();
     }
+
+    pub async fn set_reading_status(
+        &self,
+        user_id: i64,
+        book_id: i64,
+        status_id: i64,
+        updated_at: &str,
+    ) {
+        sqlx::query(
+            "INSERT INTO reading_status (user_id, book_id, status_id, created_at, updated_at, finished_at)
+             VALUES (?, ?, ?, ?, ?, CASE WHEN (SELECT is_finished FROM status WHERE id = ?) = 1 THEN ? END)
+             ON CONFLICT(user_id, book_id) DO UPDATE SET status_id = excluded.status_id, updated_at = excluded.updated_at,
+             finished_at = excluded.finished_at",
+        )
+        .bind(user_id)
+        .bind(book_id)
+        .bind(status_id)
+        .bind(updated_at)
+        .bind(updated_at)
+        .bind(status_id)
+        .bind(updated_at)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+    }
+
+    pub async fn set_rating(&self, user_id: i64, book_id: i64, rating: f64) {
+        sqlx::query("INSERT INTO ratings (user_id, book_id, rating) VALUES (?, ?, ?)")
+            .bind(user_id)
+            .bind(book_id)
+            .bind(rating)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    pub async fn set_series(&self, book_id: i64, series: &str) {
+        sqlx::query("UPDATE books SET series = ? WHERE id = ?")
+            .bind(series)
+            .bind(book_id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    pub async fn set_page_count(&self, book_id: i64, page_count: i64) {
+        sqlx::query("UPDATE books SET page_count = ? WHERE id = ?")
+            .bind(page_count)
+            .bind(book_id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    pub async fn create_test_journal(
+        &self,
+        user_id: i64,
+        book_id: i64,
+        title: &str,
+        content: &str,
+    ) -> i64 {
+        let result = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO journal_entries (book_id, user_id, title, content) VALUES (?, ?, ?, ?) RETURNING id",
+        )
+        .bind(book_id)
+        .bind(user_id)
+        .bind(title)
+        .bind(content)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap();
+
+        result.0
+    }
+
+    // Where uploaded files for this app end up: beside the test database
+    pub fn uploads_dir(&self) -> std::path::PathBuf {
+        self._temp_dir.path().join("uploads")
+    }
 }
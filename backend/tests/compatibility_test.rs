@@ -0,0 +1,147 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+#[tokio::test]
+async fn test_taste_compatibility_between_two_users() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+
+    let agreed = test_app.create_test_book(user_id, "Agreed", "Author").await;
+    let close = test_app.create_test_book(user_id, "Close", "Author").await;
+    let argued = test_app.create_test_book(user_id, "Argued", "Author").await;
+    let mine = test_app.create_test_book(user_id, "Mine", "Author").await;
+    let theirs_read = test_app
+        .create_test_book(user_id, "Theirs Read", "Author")
+        .await;
+
+    test_app.set_rating(user_id, agreed, 5.0).await;
+    test_app.set_rating(other_id, agreed, 5.0).await;
+    test_app.set_rating(user_id, close, 3.0).await;
+    test_app.set_rating(other_id, close, 3.5).await;
+    test_app.set_rating(user_id, argued, 1.0).await;
+    test_app.set_rating(other_id, argued, 4.0).await;
+
+    // Loved by the user: one the other hasn't touched, one they've already finished
+    test_app.set_rating(user_id, mine, 4.5).await;
+    test_app.set_rating(user_id, theirs_read, 5.0).await;
+    test_app
+        .set_reading_status(other_id, theirs_read, 1, "2025-01-01 10:00:00")
+        .await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/compatibility/{}", user_id, other_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["id"], user_id);
+    assert_eq!(body["other_user"]["id"], other_id);
+    assert_eq!(body["shared_count"], 3);
+    assert!(body["correlation"].as_f64().is_some());
+
+    assert_eq!(body["agreements"][0]["book"]["id"], agreed);
+    assert_eq!(body["agreements"][0]["difference"], 0.0);
+    assert_eq!(body["disagreements"][0]["book"]["id"], argued);
+    assert_eq!(body["disagreements"][0]["difference"], 3.0);
+    // Identical ratings are never listed as disagreements
+    assert_eq!(body["disagreements"].as_array().unwrap().len(), 2);
+
+    let loved = body["loved_by_user_unread_by_other"].as_array().unwrap();
+    assert_eq!(loved.len(), 1);
+    assert_eq!(loved[0]["book"]["id"], mine);
+    assert_eq!(body["loved_by_other_unread_by_user"], json!([]));
+}
+
+#[tokio::test]
+async fn test_taste_compatibility_without_shared_books() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/compatibility/{}", user_id, other_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["shared_count"], 0);
+    assert_eq!(body["correlation"], json!(null));
+    assert_eq!(body["agreements"], json!([]));
+}
+
+#[tokio::test]
+async fn test_taste_compatibility_errors() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/compatibility/{}", user_id, user_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/compatibility/99999", user_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_compatibility_matrix() {
+    let test_app = TestApp::new().await;
+    let first = test_app.create_test_user().await;
+    let second = test_app.create_test_user().await;
+    let third = test_app.create_test_user().await;
+
+    for (a, b) in [(5.0, 4.5), (2.0, 1.5), (3.5, 4.0)] {
+        let book_id = test_app.create_test_book(first, "Shared", "Author").await;
+        test_app.set_rating(first, book_id, a).await;
+        test_app.set_rating(second, book_id, b).await;
+    }
+
+    let (status, body) = make_request(&test_app, "GET", "/users/compatibility", first, None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let users = body["users"].as_array().unwrap();
+    let rows = body["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), users.len());
+
+    let row = rows.iter().find(|r| r["user_id"] == first).unwrap();
+    let scores = row["scores"].as_array().unwrap();
+    // Everyone except the user themselves
+    assert_eq!(scores.len(), users.len() - 1);
+
+    let with_second = scores
+        .iter()
+        .find(|c| c["other_user_id"] == second)
+        .unwrap();
+    assert_eq!(with_second["shared_count"], 3);
+    assert!(with_second["correlation"].as_f64().unwrap() > 0.8);
+
+    let with_third = scores.iter().find(|c| c["other_user_id"] == third).unwrap();
+    assert_eq!(with_third["shared_count"], 0);
+    assert_eq!(with_third["correlation"], json!(null));
+}
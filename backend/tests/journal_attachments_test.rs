@@ -0,0 +1,284 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use backend::db::extract_inline_journal_images;
+use backend::utils::FileStorage;
+use common::TestApp;
+use serde_json::json;
+use utils::{get_raw, make_multipart_request, make_request};
+
+const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\nfake-png-data";
+// PNG_BYTES encoded as base64
+const PNG_BASE64: &str = "iVBORw0KGgpmYWtlLXBuZy1kYXRh";
+
+fn stored_file_count(test_app: &TestApp) -> usize {
+    std::fs::read_dir(test_app.uploads_dir().join("journals"))
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_upload_and_download_attachment() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", "<p>Look at this</p>")
+        .await;
+
+    let uri = format!("/books/{}/journals/{}/attachments", book_id, journal_id);
+    let (status, attachment) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "map.png", PNG_BYTES).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(attachment["file_name"], "map.png");
+    assert_eq!(attachment["content_type"], "image/png");
+    assert_eq!(attachment["size_bytes"], PNG_BYTES.len());
+    assert_eq!(attachment["user_id"], user_id);
+    assert!(attachment.get("storage_key").is_none());
+    // The URL names the file by its random storage name, not by sequential ids
+    let url = attachment["url"].as_str().unwrap();
+    assert!(url.starts_with("/attachments/"));
+    assert!(url.len() > "/attachments/".len() + 30);
+    assert_eq!(stored_file_count(&test_app), 1);
+
+    let (status, list) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // The URL works without a user header so it can be embedded as an image
+    let (status, headers, bytes) = get_raw(&test_app, url).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/png");
+    assert_eq!(bytes, PNG_BYTES);
+
+    let (status, _headers, _bytes) = get_raw(&test_app, "/attachments/not-a-file.png").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let attachment_uri = format!("{}/{}", uri, attachment["id"]);
+    let (status, _body) = make_request(&test_app, "DELETE", &attachment_uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(stored_file_count(&test_app), 0);
+
+    let (status, _headers, _bytes) = get_raw(&test_app, url).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_upload_attachment_validation() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let other_book_id = test_app
+        .create_test_book(user_id, "Other Book", "Other Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", "")
+        .await;
+    let uri = format!("/books/{}/journals/{}/attachments", book_id, journal_id);
+
+    // Content is sniffed, so a script named like an image is refused
+    let (status, _body) = make_multipart_request(
+        &test_app,
+        &uri,
+        user_id,
+        "file",
+        "cover.png",
+        b"<script>alert(1)</script>",
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let mut oversized = PNG_BYTES.to_vec();
+    oversized.resize(6 * 1024 * 1024, 0);
+    let (status, _body) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "big.png", &oversized).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (status, _body) =
+        make_multipart_request(&test_app, &uri, user_id, "image", "map.png", PNG_BYTES).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The journal must belong to the book in the path
+    let (status, _body) = make_multipart_request(
+        &test_app,
+        &format!(
+            "/books/{}/journals/{}/attachments",
+            other_book_id, journal_id
+        ),
+        user_id,
+        "file",
+        "map.png",
+        PNG_BYTES,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    assert_eq!(stored_file_count(&test_app), 0);
+}
+
+#[tokio::test]
+async fn test_attachments_of_private_journals_are_hidden() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", "")
+        .await;
+    sqlx::query("UPDATE journal_entries SET visibility = 'private' WHERE id = ?")
+        .bind(journal_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    let uri = format!("/books/{}/journals/{}/attachments", book_id, journal_id);
+
+    let (status, _body) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "map.png", PNG_BYTES).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, list) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    let (status, _body) = make_request(&test_app, "GET", &uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _body) =
+        make_multipart_request(&test_app, &uri, other_user_id, "file", "map.png", PNG_BYTES).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let attachment_uri = format!("{}/{}", uri, list[0]["id"]);
+    let (status, _body) =
+        make_request(&test_app, "DELETE", &attachment_uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(stored_file_count(&test_app), 1);
+}
+
+#[tokio::test]
+async fn test_deleting_journal_removes_attachment_files() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", "")
+        .await;
+    let uri = format!("/books/{}/journals/{}/attachments", book_id, journal_id);
+
+    let (status, _body) =
+        make_multipart_request(&test_app, &uri, user_id, "file", "map.png", PNG_BYTES).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stored_file_count(&test_app), 1);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(stored_file_count(&test_app), 0);
+}
+
+#[tokio::test]
+async fn test_extract_inline_journal_images() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let content = format!(
+        "<p>Before</p><img src=\"data:image/png;base64,{}\"><p>After</p><img src=\"data:image/svg+xml;base64,PHN2Zz4=\">",
+        PNG_BASE64
+    );
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", &content)
+        .await;
+
+    let storage = FileStorage::new(test_app.uploads_dir());
+    let extracted = extract_inline_journal_images(&test_app.pool, &storage)
+        .await
+        .unwrap();
+    assert_eq!(extracted, 1);
+
+    let (status, journal) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, attachments) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/journals/{}/attachments", book_id, journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let url = attachments[0]["url"].as_str().unwrap();
+
+    // The PNG is replaced by its attachment URL; the unsupported SVG is left alone
+    assert_eq!(
+        journal["content"],
+        format!(
+            "<p>Before</p><img src=\"{}\"><p>After</p><img src=\"data:image/svg+xml;base64,PHN2Zz4=\">",
+            url
+        )
+    );
+
+    let (status, _headers, bytes) = get_raw(&test_app, url).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bytes, PNG_BYTES);
+
+    // Running it again has nothing left to extract
+    let extracted = extract_inline_journal_images(&test_app.pool, &storage)
+        .await
+        .unwrap();
+    assert_eq!(extracted, 0);
+}
+
+#[tokio::test]
+async fn test_inline_images_are_refused_on_write() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Notes", "")
+        .await;
+    let content = format!("<img src=\"data:image/png;base64,{}\">", PNG_BASE64);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        Some(json!({ "title": "Map", "content": content })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        Some(json!({ "content": content })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,221 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+#[tokio::test]
+async fn test_threaded_journal_comments() {
+    let test_app = TestApp::new().await;
+    let author_id = test_app.create_test_user().await;
+    let reader_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(author_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(author_id, book_id, "Thoughts", "Loved it")
+        .await;
+    let comments_uri = format!("/journals/{}/comments", journal_id);
+
+    let (status, threads) = make_request(
+        &test_app,
+        "POST",
+        &comments_uri,
+        reader_id,
+        Some(json!({ "content": "Me too!" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let comment_id = threads[0]["id"].as_i64().unwrap();
+    assert_eq!(threads[0]["user"]["id"], reader_id);
+
+    let (status, threads) = make_request(
+        &test_app,
+        "POST",
+        &comments_uri,
+        author_id,
+        Some(json!({ "content": "Which part?", "parent_id": comment_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(threads.as_array().unwrap().len(), 1);
+    assert_eq!(threads[0]["replies"][0]["content"], "Which part?");
+    assert_eq!(threads[0]["replies"][0]["parent_id"], comment_id);
+
+    // Replies have to be to a comment on the same entry
+    let other_journal_id = test_app
+        .create_test_journal(author_id, book_id, "Other", "Other entry")
+        .await;
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/journals/{}/comments", other_journal_id),
+        reader_id,
+        Some(json!({ "content": "Wrong thread", "parent_id": comment_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &comments_uri,
+        reader_id,
+        Some(json!({ "content": "  " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_status, book) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        reader_id,
+        None,
+    )
+    .await;
+    let journal = book["journals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|j| j["id"] == journal_id)
+        .unwrap();
+    assert_eq!(journal["comment_count"], 2);
+}
+
+#[tokio::test]
+async fn test_journal_authors_can_delete_comments_on_their_entries() {
+    let test_app = TestApp::new().await;
+    let author_id = test_app.create_test_user().await;
+    let commenter_id = test_app.create_test_user().await;
+    let bystander_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(author_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(author_id, book_id, "Thoughts", "Loved it")
+        .await;
+    let comments_uri = format!("/journals/{}/comments", journal_id);
+
+    let mut comment_ids = Vec::new();
+    for content in ["First", "Second"] {
+        let (_status, threads) = make_request(
+            &test_app,
+            "POST",
+            &comments_uri,
+            commenter_id,
+            Some(json!({ "content": content })),
+        )
+        .await;
+        comment_ids.push(threads.as_array().unwrap().last().unwrap()["id"].clone());
+    }
+
+    // Someone who neither wrote the comment nor the entry can't delete it
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("{}/{}", comments_uri, comment_ids[0]),
+        bystander_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("{}/{}", comments_uri, comment_ids[0]),
+        author_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("{}/{}", comments_uri, comment_ids[1]),
+        commenter_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_status, threads) = make_request(&test_app, "GET", &comments_uri, author_id, None).await;
+    assert_eq!(threads.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_journal_reactions() {
+    let test_app = TestApp::new().await;
+    let author_id = test_app.create_test_user().await;
+    let reader_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(author_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(author_id, book_id, "Thoughts", "Loved it")
+        .await;
+    let reactions_uri = format!("/journals/{}/reactions", journal_id);
+
+    for (user_id, emoji) in [(reader_id, "❤️"), (author_id, "❤️"), (reader_id, "😂")] {
+        let (status, _body) = make_request(
+            &test_app,
+            "POST",
+            &reactions_uri,
+            user_id,
+            Some(json!({ "emoji": emoji })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Reacting twice with the same emoji counts once
+    let (status, reactions) = make_request(
+        &test_app,
+        "POST",
+        &reactions_uri,
+        reader_id,
+        Some(json!({ "emoji": "❤️" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reactions[0]["emoji"], "❤️");
+    assert_eq!(reactions[0]["count"], 2);
+    assert_eq!(reactions[0]["user_ids"], json!([reader_id, author_id]));
+    assert_eq!(reactions[1]["count"], 1);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &reactions_uri,
+        reader_id,
+        Some(json!({ "emoji": "nice" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, reactions) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("{}/%F0%9F%98%82", reactions_uri),
+        reader_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reactions.as_array().unwrap().len(), 1);
+
+    let (_status, book) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        author_id,
+        None,
+    )
+    .await;
+    assert_eq!(book["journals"][0]["reactions"][0]["emoji"], "❤️");
+    assert_eq!(book["journals"][0]["reactions"][0]["count"], 2);
+}
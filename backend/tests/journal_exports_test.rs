@@ -0,0 +1,195 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use std::io::{Cursor, Read};
+use utils::{get_raw, get_raw_as, make_multipart_request, make_request};
+
+const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\nfake-png-data";
+
+fn read_zip_file(bytes: &[u8], name: &str) -> Vec<u8> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut file = archive.by_name(name).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    contents
+}
+
+fn zip_file_names(bytes: &[u8]) -> Vec<String> {
+    let archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+    archive.file_names().map(str::to_string).collect()
+}
+
+// A journal entry on "The Hobbit" with an embedded image, a tag and a rating
+async fn create_illustrated_journal(test_app: &TestApp, user_id: i64) -> (i64, i64, i64) {
+    let book_id = test_app
+        .create_test_book(user_id, "The Hobbit", "J.R.R. Tolkien")
+        .await;
+    test_app.set_rating(user_id, book_id, 4.5).await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Riddles in the Dark", "<p>Draft</p>")
+        .await;
+
+    let uri = format!("/books/{}/journals/{}/attachments", book_id, journal_id);
+    let (status, attachment) =
+        make_multipart_request(test_app, &uri, user_id, "file", "map.png", PNG_BYTES).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let tag_id = test_app
+        .create_test_tag(user_id, "Fantasy", "#00ff00")
+        .await;
+    let content = format!(
+        "<p>What has <strong>roots</strong> as nobody sees?</p><img src=\"{}\">",
+        attachment["url"].as_str().unwrap()
+    );
+    let (status, _) = make_request(
+        test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        Some(serde_json::json!({
+            "title": "Riddles in the Dark",
+            "content": content,
+            "tags": [tag_id]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    (book_id, journal_id, attachment["id"].as_i64().unwrap())
+}
+
+#[tokio::test]
+async fn test_export_book_journals_as_markdown() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let (book_id, _, attachment_id) = create_illustrated_journal(&test_app, user_id).await;
+
+    // Private entries are left out for anyone but their author
+    let private_id = test_app
+        .create_test_journal(user_id, book_id, "Secret", "<p>Shh</p>")
+        .await;
+    sqlx::query("UPDATE journal_entries SET visibility = 'private' WHERE id = ?")
+        .bind(private_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+
+    let other_user_id = test_app.create_test_user().await;
+    let (status, headers, bytes) = get_raw_as(
+        &test_app,
+        &format!("/books/{}/journals/export", book_id),
+        other_user_id,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/zip");
+    assert_eq!(
+        headers["content-disposition"],
+        "attachment; filename=\"the-hobbit-journal.zip\""
+    );
+
+    let names = zip_file_names(&bytes);
+    assert_eq!(names.len(), 2);
+    let markdown_name = names
+        .iter()
+        .find(|name| name.ends_with("-riddles-in-the-dark.md"))
+        .unwrap();
+    assert!(markdown_name.starts_with("the-hobbit/"));
+
+    let image_name = format!("the-hobbit/images/attachment-{}.png", attachment_id);
+    assert_eq!(read_zip_file(&bytes, &image_name), PNG_BYTES);
+
+    let markdown = String::from_utf8(read_zip_file(&bytes, markdown_name)).unwrap();
+    assert!(markdown.starts_with("---\ntitle: \"Riddles in the Dark\"\n"));
+    assert!(markdown.contains("book: \"The Hobbit\"\n"));
+    assert!(markdown.contains("author: \"J.R.R. Tolkien\"\n"));
+    assert!(markdown.contains("tags:\n  - \"Fantasy\"\n"));
+    assert!(markdown.contains("rating: 4.5\n"));
+    assert!(markdown.contains("What has **roots** as nobody sees?"));
+    assert!(markdown.contains(&format!("![](images/attachment-{}.png)", attachment_id)));
+    assert!(!markdown.contains("](/attachments/"));
+
+    let (status, _, _) = get_raw_as(&test_app, "/books/9999/journals/export", user_id).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _, _) = get_raw_as(
+        &test_app,
+        &format!("/books/{}/journals/export?format=pdf", book_id),
+        user_id,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Exports need to say who they're for
+    let (status, _, _) = get_raw(&test_app, &format!("/books/{}/journals/export", book_id)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_export_user_journals_as_epub() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let (_, journal_id, attachment_id) = create_illustrated_journal(&test_app, user_id).await;
+    let other_book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    let other_journal_id = test_app
+        .create_test_journal(
+            user_id,
+            other_book_id,
+            "Spice & fear",
+            "<p>Line<br>break</p>",
+        )
+        .await;
+
+    let (status, headers, bytes) = get_raw_as(
+        &test_app,
+        &format!("/users/{}/journals/export?format=epub", user_id),
+        user_id,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/epub+zip");
+
+    // The mimetype must be the first, uncompressed entry
+    let mut archive = zip::ZipArchive::new(Cursor::new(&bytes)).unwrap();
+    let mimetype = archive.by_index(0).unwrap();
+    assert_eq!(mimetype.name(), "mimetype");
+    assert_eq!(mimetype.compression(), zip::CompressionMethod::Stored);
+    drop(mimetype);
+    assert_eq!(read_zip_file(&bytes, "mimetype"), b"application/epub+zip");
+
+    let package = String::from_utf8(read_zip_file(&bytes, "OEBPS/content.opf")).unwrap();
+    assert!(package.contains(&format!("href=\"entry-{}.xhtml\"", journal_id)));
+    assert!(package.contains(&format!("href=\"entry-{}.xhtml\"", other_journal_id)));
+    assert!(package.contains(&format!(
+        "href=\"images/attachment-{}.png\" media-type=\"image/png\"",
+        attachment_id
+    )));
+
+    let nav = String::from_utf8(read_zip_file(&bytes, "OEBPS/nav.xhtml")).unwrap();
+    assert!(nav.contains("Dune"));
+    assert!(nav.contains("The Hobbit"));
+    assert!(nav.find("Dune").unwrap() < nav.find("The Hobbit").unwrap());
+
+    let chapter = String::from_utf8(read_zip_file(
+        &bytes,
+        &format!("OEBPS/entry-{}.xhtml", other_journal_id),
+    ))
+    .unwrap();
+    assert!(chapter.contains("<h1>Spice &amp; fear</h1>"));
+    assert!(chapter.contains("<p>Line<br/>break</p>"));
+
+    assert_eq!(
+        read_zip_file(
+            &bytes,
+            &format!("OEBPS/images/attachment-{}.png", attachment_id)
+        ),
+        PNG_BYTES
+    );
+
+    let (status, _, _) = get_raw_as(&test_app, "/users/9999/journals/export", user_id).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
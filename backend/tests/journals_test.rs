@@ -14,7 +14,7 @@ async fn test_get_journals_empty() {
     let (status, body) = make_request(&test_app, "GET", "/journals", user_id, None).await;
 
     assert_eq!(status, StatusCode::OK);
-    assert_eq!(body, json!([]));
+    assert_eq!(body, json!({ "journals": [], "next_cursor": null }));
 }
 
 #[tokio::test]
@@ -22,10 +22,9 @@ async fn test_get_journal_by_id_not_found() {
     let test_app = TestApp::new().await;
     let user_id = test_app.create_test_user().await;
 
-    let (status, body) = make_request(&test_app, "GET", "/journals/99999", user_id, None).await;
+    let (status, _body) = make_request(&test_app, "GET", "/journals/99999", user_id, None).await;
 
-    assert_eq!(status, StatusCode::OK);
-    assert_eq!(body, json!(null));
+    assert_eq!(status, StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
@@ -215,7 +214,7 @@ async fn test_get_all_journals_with_data() {
 
     assert_eq!(status, StatusCode::OK);
 
-    let journals_array = body.as_array().unwrap();
+    let journals_array = body["journals"].as_array().unwrap();
     assert_eq!(journals_array.len(), 2);
 
     // Verify both journals are returned
@@ -233,6 +232,7 @@ async fn test_get_all_journals_with_data() {
 
     assert_eq!(book2_journal["title"], "Journal for Book 2");
     assert_eq!(book2_journal["content"], "Thoughts about book two.");
+    assert_eq!(book2_journal["book"]["id"], book2_id);
 }
 
 #[tokio::test]
@@ -454,6 +454,7 @@ async fn test_create_book_journal_bad_request_malformed_json_syntax() {
     use axum::{body::Body, extract::Request};
     use tower::ServiceExt;
 
+    #[allow(clippy::needless_borrows_for_generic_args)]
     let request = Request::builder()
         .method("POST")
         .uri(&format!("/books/{}/journals", book_id))
@@ -470,3 +471,638 @@ async fn test_create_book_journal_bad_request_malformed_json_syntax() {
     // Malformed JSON syntax should return BAD_REQUEST
     assert_eq!(status, StatusCode::BAD_REQUEST);
 }
+
+#[tokio::test]
+async fn test_update_journal_entry_must_belong_to_book() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let other_book_id = test_app
+        .create_test_book(user_id, "Other Book", "Test Author")
+        .await;
+
+    let (_status, journal) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        Some(json!({ "title": "Original", "content": "Original content" })),
+    )
+    .await;
+    let journal_id = journal["id"].as_i64().unwrap();
+
+    // Editing through the wrong book is rejected and leaves the entry untouched
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", other_book_id, journal_id),
+        user_id,
+        Some(json!({ "title": "Hijacked" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        Some(json!({ "title": "Updated" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["title"], "Updated");
+    assert_eq!(body["content"], "Original content");
+
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/99999", book_id),
+        user_id,
+        Some(json!({ "title": "Missing" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_journal_entry() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let other_book_id = test_app
+        .create_test_book(user_id, "Other Book", "Test Author")
+        .await;
+
+    let (_status, journal) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        Some(json!({ "title": "To Delete", "content": "Soon gone" })),
+    )
+    .await;
+    let journal_id = journal["id"].as_i64().unwrap();
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/books/{}/journals/{}", other_book_id, journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_journal_tags_on_create_and_update() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let theory_id = test_app.create_test_tag(user_id, "theory", "#8B5CF6").await;
+    let quote_id = test_app
+        .create_test_tag(user_id, "favourite quote", "#F59E0B")
+        .await;
+
+    let (status, created) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        Some(json!({
+            "title": "Who did it",
+            "content": "It was the butler",
+            "tags": [theory_id, quote_id]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let journal_id = created["id"].as_i64().unwrap();
+    let names: Vec<&str> = created["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["favourite quote", "theory"]);
+
+    // Updating without tags leaves them alone
+    let (status, updated) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        Some(json!({ "title": "Who really did it" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["tags"].as_array().unwrap().len(), 2);
+
+    let (status, updated) = make_request(
+        &test_app,
+        "PUT",
+        &format!("/books/{}/journals/{}", book_id, journal_id),
+        user_id,
+        Some(json!({ "tags": [theory_id] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        updated["tags"],
+        json!([{ "id": theory_id, "name": "theory", "color": "#8B5CF6" }])
+    );
+
+    let (status, book) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book["journals"][0]["tags"][0]["id"], theory_id);
+
+    let (status, journal) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(journal["tags"][0]["name"], "theory");
+
+    // Deleting a tag in use untags the journal instead of failing
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/tags/{}", theory_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_status, journal) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(journal["tags"], json!([]));
+}
+
+#[tokio::test]
+async fn test_create_journal_with_unknown_tag() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        Some(json!({ "title": "Notes", "content": "Text", "tags": [99999] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nothing is left behind from the failed create
+    let (_status, journals) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(journals, json!([]));
+}
+
+#[tokio::test]
+async fn test_get_journals_filtered_by_tag() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let theory_id = test_app.create_test_tag(user_id, "theory", "#8B5CF6").await;
+
+    for (title, tags) in [("Theory one", vec![theory_id]), ("Plain notes", vec![])] {
+        let (status, _body) = make_request(
+            &test_app,
+            "POST",
+            &format!("/books/{}/journals", book_id),
+            user_id,
+            Some(json!({ "title": title, "content": "Text", "tags": tags })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals?tag_id={}", theory_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let titles: Vec<&str> = body["journals"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Theory one"]);
+
+    let (_status, body) = make_request(&test_app, "GET", "/journals", user_id, None).await;
+    assert_eq!(body["journals"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_journal_revisions_diff_and_restore() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(user_id, book_id, "Chapter 1", "First line\nSecond line")
+        .await;
+    let journal_uri = format!("/books/{}/journals/{}", book_id, journal_id);
+
+    let (status, revisions) = make_request(
+        &test_app,
+        "GET",
+        &format!("{}/revisions", journal_uri),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revisions.as_array().unwrap().len(), 0);
+
+    // The accidental save
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &journal_uri,
+        user_id,
+        Some(json!({ "content": "First line\nOops" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Updates that don't change the title or content don't add a revision
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &journal_uri,
+        user_id,
+        Some(json!({ "tags": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_status, revisions) = make_request(
+        &test_app,
+        "GET",
+        &format!("{}/revisions", journal_uri),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(revisions.as_array().unwrap().len(), 1);
+    assert_eq!(revisions[0]["content"], "First line\nSecond line");
+    let revision_id = revisions[0]["id"].as_i64().unwrap();
+
+    let (status, diff) = make_request(
+        &test_app,
+        "GET",
+        &format!("{}/revisions/diff?from={}", journal_uri, revision_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(diff["added"], 1);
+    assert_eq!(diff["removed"], 1);
+    assert_eq!(
+        diff["lines"],
+        json!([
+            { "kind": "equal", "text": "First line" },
+            { "kind": "removed", "text": "Second line" },
+            { "kind": "added", "text": "Oops" }
+        ])
+    );
+
+    let (status, restored) = make_request(
+        &test_app,
+        "POST",
+        &format!("{}/revisions/{}/restore", journal_uri, revision_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["content"], "First line\nSecond line");
+    assert_eq!(restored["title"], "Chapter 1");
+
+    // The overwritten version is kept too, so the restore can be undone
+    let (_status, revisions) = make_request(
+        &test_app,
+        "GET",
+        &format!("{}/revisions", journal_uri),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(revisions.as_array().unwrap().len(), 2);
+    assert_eq!(revisions[0]["content"], "First line\nOops");
+
+    // Revisions are scoped to their journal entry and book
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!(
+            "/books/99999/journals/{}/revisions/{}",
+            journal_id, revision_id
+        ),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_private_journals_are_only_visible_to_their_author() {
+    let test_app = TestApp::new().await;
+    let author_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(author_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, private_journal) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        author_id,
+        Some(
+            json!({ "title": "Just for me", "content": "Personal notes", "visibility": "private" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(private_journal["visibility"], "private");
+
+    let (status, shared_journal) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        author_id,
+        Some(json!({ "title": "For everyone", "content": "Shared notes" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(shared_journal["visibility"], "household");
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", book_id),
+        author_id,
+        Some(json!({ "title": "Title", "content": "Content", "visibility": "public" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for (user_id, expected) in [(author_id, 2), (other_id, 1)] {
+        let (_status, feed) = make_request(&test_app, "GET", "/journals", user_id, None).await;
+        assert_eq!(feed["journals"].as_array().unwrap().len(), expected);
+
+        let (_status, journals) = make_request(
+            &test_app,
+            "GET",
+            &format!("/books/{}/journals", book_id),
+            user_id,
+            None,
+        )
+        .await;
+        assert_eq!(journals.as_array().unwrap().len(), expected);
+
+        let (_status, book) = make_request(
+            &test_app,
+            "GET",
+            &format!("/books/{}", book_id),
+            user_id,
+            None,
+        )
+        .await;
+        assert_eq!(book["journals"].as_array().unwrap().len(), expected);
+    }
+
+    let private_uri = format!("/journals/{}", private_journal["id"]);
+    let (status, _body) = make_request(&test_app, "GET", &private_uri, other_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _body) = make_request(&test_app, "GET", &private_uri, author_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Other members can't read, share or delete it through the book's routes either
+    let book_journal_uri = format!("/books/{}/journals/{}", book_id, private_journal["id"]);
+    for body in [json!({}), json!({ "visibility": "household" })] {
+        let (status, updated) =
+            make_request(&test_app, "PUT", &book_journal_uri, other_id, Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(updated, json!(null));
+    }
+    let (status, _body) =
+        make_request(&test_app, "DELETE", &book_journal_uri, other_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Sharing the entry makes it visible to everyone
+    let (status, updated) = make_request(
+        &test_app,
+        "PUT",
+        &book_journal_uri,
+        author_id,
+        Some(json!({ "visibility": "household" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["visibility"], "household");
+    let (status, _body) = make_request(&test_app, "GET", &private_uri, other_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only the author can make it private again
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &book_journal_uri,
+        other_id,
+        Some(json!({ "visibility": "private" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_journal_feed_filters_and_pagination() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+    let dune_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    let hobbit_id = test_app
+        .create_test_book(user_id, "The Hobbit", "J.R.R. Tolkien")
+        .await;
+
+    for (author_id, book_id, title, created_at) in [
+        (user_id, dune_id, "Arrakis", "2026-01-05 09:00:00"),
+        (user_id, dune_id, "The spice", "2026-02-10 09:00:00"),
+        (
+            other_id,
+            hobbit_id,
+            "Second breakfast",
+            "2026-03-15 09:00:00",
+        ),
+        (
+            user_id,
+            hobbit_id,
+            "Riddles in the dark",
+            "2026-03-20 09:00:00",
+        ),
+        (other_id, dune_id, "Sandworms", "2026-04-01 09:00:00"),
+    ] {
+        let journal_id = test_app
+            .create_test_journal(author_id, book_id, title, "Notes")
+            .await;
+        sqlx::query("UPDATE journal_entries SET created_at = ? WHERE id = ?")
+            .bind(created_at)
+            .bind(journal_id)
+            .execute(&test_app.pool)
+            .await
+            .unwrap();
+    }
+
+    let titles = |body: &serde_json::Value| -> Vec<String> {
+        body["journals"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|j| j["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Newest first, two at a time
+    let (status, page) = make_request(&test_app, "GET", "/journals?limit=2", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&page), vec!["Sandworms", "Riddles in the dark"]);
+    assert_eq!(page["journals"][0]["book"]["title"], "Dune");
+
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    let (_status, page) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals?limit=2&cursor={}", cursor),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(titles(&page), vec!["Second breakfast", "The spice"]);
+
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    let (_status, page) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals?limit=2&cursor={}", cursor),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(titles(&page), vec!["Arrakis"]);
+    assert_eq!(page["next_cursor"], serde_json::Value::Null);
+
+    let (_status, page) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals?user_id={}&book_id={}", user_id, dune_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(titles(&page), vec!["The spice", "Arrakis"]);
+
+    let (_status, page) = make_request(
+        &test_app,
+        "GET",
+        "/journals?from=2026-02-10&to=2026-03-15",
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(titles(&page), vec!["Second breakfast", "The spice"]);
+
+    let (_status, page) = make_request(&test_app, "GET", "/journals?q=spice", user_id, None).await;
+    assert_eq!(titles(&page), vec!["The spice"]);
+
+    for bad_query in ["limit=0", "from=yesterday", "cursor=not-a-cursor"] {
+        let (status, _body) = make_request(
+            &test_app,
+            "GET",
+            &format!("/journals?{}", bad_query),
+            user_id,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", bad_query);
+    }
+}
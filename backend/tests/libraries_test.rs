@@ -0,0 +1,282 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::{get_raw, get_raw_in_library, make_library_request, make_request};
+
+fn titles(books: &Value) -> Vec<&str> {
+    books
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| book["title"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_books_tags_and_lists_stay_in_their_library() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+
+    // Everyone starts in the default library
+    let home_book = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    let (status, cabin) = make_request(
+        &test_app,
+        "POST",
+        "/libraries",
+        user_id,
+        Some(json!({ "name": "Cabin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let cabin_id = cabin["id"].as_i64().unwrap();
+
+    let (status, libraries) = make_request(&test_app, "GET", "/libraries", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(libraries.as_array().unwrap().len(), 2);
+
+    let (status, cabin_book) = make_library_request(
+        &test_app,
+        "POST",
+        "/books",
+        user_id,
+        cabin_id,
+        Some(json!({ "title": "Walden", "author": "Henry David Thoreau" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let cabin_book_id = cabin_book["id"].as_i64().unwrap();
+
+    let (_, books) = make_request(&test_app, "GET", "/books", user_id, None).await;
+    assert_eq!(titles(&books), vec!["Dune"]);
+    let (_, books) =
+        make_library_request(&test_app, "GET", "/books", user_id, cabin_id, None).await;
+    assert_eq!(titles(&books), vec!["Walden"]);
+
+    // Books from another library can't be reached through the active one
+    let uri = format!("/books/{}", cabin_book_id);
+    let (status, _) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let uri = format!("/books/{}/journals", cabin_book_id);
+    let (status, _) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Tags with the same name live side by side
+    let tag = json!({ "name": "Favourites", "color": "gold" });
+    let (status, _) = make_request(&test_app, "POST", "/tags", user_id, Some(tag.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, cabin_tag) =
+        make_library_request(&test_app, "POST", "/tags", user_id, cabin_id, Some(tag)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, tags) = make_library_request(&test_app, "GET", "/tags", user_id, cabin_id, None).await;
+    assert_eq!(tags.as_array().unwrap().len(), 1);
+
+    let uri = format!("/tags/{}", cabin_tag["id"]);
+    let (status, _) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Lists only take books from their own library
+    let (status, list) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        user_id,
+        Some(json!({ "type_id": 1, "name": "Summer", "books": [home_book, cabin_book_id] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let list_books = list["books"].as_array().unwrap();
+    assert_eq!(list_books.len(), 1);
+    assert_eq!(list_books[0]["id"], home_book);
+    let (_, lists) =
+        make_library_request(&test_app, "GET", "/lists", user_id, cabin_id, None).await;
+    assert_eq!(lists.as_array().unwrap().len(), 0);
+
+    // Non-members can't pick the library
+    let (status, _) =
+        make_library_request(&test_app, "GET", "/books", other_user_id, cabin_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_users_and_their_reports_stay_in_their_library() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let cabin_user_id = test_app.create_test_user().await;
+    let cabin_id = test_app.create_test_library("Cabin").await;
+    test_app.add_library_member(cabin_id, user_id).await;
+    test_app.add_library_member(cabin_id, cabin_user_id).await;
+    sqlx::query("DELETE FROM library_members WHERE library_id = 1 AND user_id = ?")
+        .bind(cabin_user_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+
+    let user_ids = |users: &Value| -> Vec<i64> {
+        users
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|user| user["id"].as_i64().unwrap())
+            .collect()
+    };
+    let (_, users) = make_request(&test_app, "GET", "/users", user_id, None).await;
+    assert!(user_ids(&users).contains(&user_id));
+    assert!(!user_ids(&users).contains(&cabin_user_id));
+    let (_, users) =
+        make_library_request(&test_app, "GET", "/users", user_id, cabin_id, None).await;
+    assert_eq!(user_ids(&users), vec![user_id, cabin_user_id]);
+
+    // Other libraries' members are out of reach through the active library
+    for uri in [
+        format!("/users/{}/year-in-review/2026", cabin_user_id),
+        format!("/users/{}/journals/export", cabin_user_id),
+        format!("/users/{}/recommendations", cabin_user_id),
+        format!("/users/{}/compatibility/{}", user_id, cabin_user_id),
+    ] {
+        let (status, _) = make_request(&test_app, "GET", &uri, user_id, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+        let (status, _) =
+            make_library_request(&test_app, "GET", &uri, user_id, cabin_id, None).await;
+        assert_ne!(status, StatusCode::NOT_FOUND, "{}", uri);
+    }
+
+    // Reports only count the active library's books
+    let cabin_book_id = test_app
+        .create_test_book(user_id, "Walden", "Henry David Thoreau")
+        .await;
+    sqlx::query("UPDATE books SET library_id = ? WHERE id = ?")
+        .bind(cabin_id)
+        .bind(cabin_book_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    test_app
+        .create_test_journal(user_id, cabin_book_id, "Woods", "<p>Simplify</p>")
+        .await;
+    let year = chrono::Utc::now().format("%Y").to_string();
+    let uri = format!("/users/{}/year-in-review/{}", user_id, year);
+    let (_, report) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(report["journal_count"], 0);
+    let (_, report) = make_library_request(&test_app, "GET", &uri, user_id, cabin_id, None).await;
+    assert_eq!(report["journal_count"], 1);
+
+    test_app.set_rating(user_id, cabin_book_id, 5.0).await;
+    test_app.set_rating(cabin_user_id, cabin_book_id, 4.0).await;
+    let (_, matrix) = make_request(&test_app, "GET", "/users/compatibility", user_id, None).await;
+    assert!(!user_ids(&matrix["users"]).contains(&cabin_user_id));
+    let (_, matrix) = make_library_request(
+        &test_app,
+        "GET",
+        "/users/compatibility",
+        user_id,
+        cabin_id,
+        None,
+    )
+    .await;
+    assert_eq!(user_ids(&matrix["users"]), vec![user_id, cabin_user_id]);
+    assert_eq!(matrix["rows"][0]["scores"][0]["shared_count"], 1);
+
+    // Only the login screen's user picker and public links work without a user
+    let (status, _, _) = get_raw(&test_app, "/books").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = get_raw(&test_app, &format!("/users/{}/recommendations", user_id)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // and the picker only sees the members of the library it names
+    let (status, _, _) = get_raw(&test_app, "/users").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, bytes) = get_raw_in_library(&test_app, "/users", 1).await;
+    assert_eq!(status, StatusCode::OK);
+    let users: Value = serde_json::from_slice(&bytes).unwrap();
+    assert!(user_ids(&users).contains(&user_id));
+    assert!(!user_ids(&users).contains(&cabin_user_id));
+    let (_, _, bytes) = get_raw_in_library(&test_app, "/users", cabin_id).await;
+    let users: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(user_ids(&users), vec![user_id, cabin_user_id]);
+}
+
+#[tokio::test]
+async fn test_genre_names_are_unique_per_library() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let cabin_id = test_app.create_test_library("Cabin").await;
+    test_app.add_library_member(cabin_id, user_id).await;
+
+    let genre = json!({ "name": "Fantasy", "color": "green" });
+    let (status, _) =
+        make_request(&test_app, "POST", "/genres", user_id, Some(genre.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = make_library_request(
+        &test_app,
+        "POST",
+        "/genres",
+        user_id,
+        cabin_id,
+        Some(genre.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) =
+        make_library_request(&test_app, "POST", "/genres", user_id, cabin_id, Some(genre)).await;
+    assert_ne!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_library_members() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let member_id = test_app.create_test_user_with_role("member").await;
+    let cabin_id = test_app.create_test_library("Cabin").await;
+    test_app.add_library_member(cabin_id, admin_id).await;
+
+    let uri = format!("/libraries/{}/members", cabin_id);
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        member_id,
+        Some(json!({ "user_id": member_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        admin_id,
+        Some(json!({ "user_id": 9999 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        admin_id,
+        Some(json!({ "user_id": member_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) =
+        make_library_request(&test_app, "GET", "/books", member_id, cabin_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/libraries/{}/members/{}", cabin_id, member_id);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, admin_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, admin_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) =
+        make_library_request(&test_app, "GET", "/books", member_id, cabin_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,291 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::make_request;
+
+// Sorted, since lists created in the same second have no set order
+fn list_names(lists: &Value) -> Vec<&str> {
+    let mut names: Vec<&str> = lists
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|list| list["name"].as_str().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+async fn create_list(test_app: &TestApp, user_id: i64, request: Value) -> i64 {
+    let (status, list) = make_request(test_app, "POST", "/lists", user_id, Some(request)).await;
+    assert_eq!(status, StatusCode::OK);
+    list["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_list_visibility_applies_to_index_and_detail() {
+    let test_app = TestApp::new().await;
+    let owner_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+
+    let household_id = create_list(
+        &test_app,
+        owner_id,
+        json!({ "type_id": 1, "name": "Summer 2026", "books": [] }),
+    )
+    .await;
+    let private_id = create_list(
+        &test_app,
+        owner_id,
+        json!({ "type_id": 2, "name": "Gift ideas", "visibility": "private", "books": [] }),
+    )
+    .await;
+
+    let (_, lists) = make_request(&test_app, "GET", "/lists", owner_id, None).await;
+    assert_eq!(list_names(&lists), vec!["Gift ideas", "Summer 2026"]);
+    let (_, lists) = make_request(&test_app, "GET", "/lists", other_user_id, None).await;
+    assert_eq!(list_names(&lists), vec!["Summer 2026"]);
+
+    // Lists in the index can be opened by everyone who sees them there
+    let (status, list) = make_request(
+        &test_app,
+        "GET",
+        &format!("/lists/{}", household_id),
+        other_user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["visibility"], "household");
+    assert_eq!(list["user_id"], owner_id);
+    let (status, _) = make_request(
+        &test_app,
+        "GET",
+        &format!("/lists/{}", private_id),
+        other_user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Seeing a list doesn't mean being able to change it
+    let uri = format!("/lists/{}", household_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        other_user_id,
+        Some(json!({ "name": "Mine now" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        owner_id,
+        Some(json!({ "visibility": "secret" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, list) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        owner_id,
+        Some(json!({ "visibility": "private" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["visibility"], "private");
+    let (_, lists) = make_request(&test_app, "GET", "/lists", other_user_id, None).await;
+    assert_eq!(lists, json!([]));
+}
+
+#[tokio::test]
+async fn test_collaborators_edit_lists_and_added_by_is_recorded() {
+    let test_app = TestApp::new().await;
+    let owner_id = test_app.create_test_user().await;
+    let collaborator_id = test_app.create_test_user().await;
+    let outsider_id = test_app.create_test_user().await;
+    sqlx::query("DELETE FROM library_members WHERE user_id = ?")
+        .bind(outsider_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+
+    let dune = test_app
+        .create_test_book(owner_id, "Dune", "Frank Herbert")
+        .await;
+    let anathem = test_app
+        .create_test_book(owner_id, "Anathem", "Neal Stephenson")
+        .await;
+    let list_id = create_list(
+        &test_app,
+        owner_id,
+        json!({ "type_id": 1, "name": "Book club", "visibility": "private", "books": [dune] }),
+    )
+    .await;
+    let uri = format!("/lists/{}", list_id);
+    let collaborators_uri = format!("{}/collaborators", uri);
+
+    // Only the owner adds collaborators, and only from the library
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &collaborators_uri,
+        collaborator_id,
+        Some(json!({ "user_id": collaborator_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &collaborators_uri,
+        owner_id,
+        Some(json!({ "user_id": outsider_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, list) = make_request(
+        &test_app,
+        "POST",
+        &collaborators_uri,
+        owner_id,
+        Some(json!({ "user_id": collaborator_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["collaborators"][0]["id"], collaborator_id);
+
+    // Collaborators see private lists and can change the books
+    let (_, lists) = make_request(&test_app, "GET", "/lists", collaborator_id, None).await;
+    assert_eq!(list_names(&lists), vec!["Book club"]);
+    let (status, list) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        collaborator_id,
+        Some(json!({ "books": [anathem, dune] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let added_by: Vec<(i64, i64)> = list["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| {
+            (
+                book["id"].as_i64().unwrap(),
+                book["added_by"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(added_by, vec![(anathem, collaborator_id), (dune, owner_id)]);
+
+    // ...but not the visibility, and they can't delete the list
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        collaborator_id,
+        Some(json!({ "visibility": "household" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, collaborator_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Collaborators can leave, after which the private list is out of sight
+    let leave_uri = format!("{}/{}", collaborators_uri, collaborator_id);
+    let (status, _) = make_request(&test_app, "DELETE", &leave_uri, collaborator_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = make_request(&test_app, "GET", &uri, collaborator_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = make_request(&test_app, "DELETE", &leave_uri, owner_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_editing_a_list_keeps_books_hidden_from_the_editor() {
+    let test_app = TestApp::new().await;
+    let owner_id = test_app.create_test_user().await;
+    let child_id = test_app.create_test_user_with_role("child").await;
+
+    let dune = test_app
+        .create_test_book(owner_id, "Dune", "Frank Herbert")
+        .await;
+    let it = test_app
+        .create_test_book(owner_id, "It", "Stephen King")
+        .await;
+    let holes = test_app
+        .create_test_book(owner_id, "Holes", "Louis Sachar")
+        .await;
+    let horror = test_app
+        .create_test_genre(owner_id, "Horror", "black")
+        .await;
+    sqlx::query("UPDATE genres SET is_restricted = 1 WHERE id = ?")
+        .bind(horror)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    test_app.add_genre_to_book(it, horror).await;
+
+    let list_id = create_list(
+        &test_app,
+        owner_id,
+        json!({ "type_id": 1, "name": "Family reads", "books": [dune, it] }),
+    )
+    .await;
+    let uri = format!("/lists/{}", list_id);
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &format!("{}/collaborators", uri),
+        owner_id,
+        Some(json!({ "user_id": child_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The child only sees Dune, and reorders the list around the book they can't see
+    let (status, list) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        child_id,
+        Some(json!({ "books": [holes, dune] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let child_view: Vec<i64> = list["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| book["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(child_view, vec![holes, dune]);
+
+    let (_, list) = make_request(&test_app, "GET", &uri, owner_id, None).await;
+    let owner_view: Vec<(i64, i64)> = list["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| {
+            (
+                book["id"].as_i64().unwrap(),
+                book["added_by"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        owner_view,
+        vec![(holes, child_id), (it, owner_id), (dune, owner_id)]
+    );
+}
@@ -0,0 +1,252 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use std::io::{Cursor, Read, Write};
+use utils::{get_raw_as, make_multipart_request, make_request};
+
+fn read_vault_note(bytes: &[u8], name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut file = archive.by_name(name).unwrap();
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    contents
+}
+
+fn zip_vault(notes: &[(&str, &str)]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in notes {
+        zip.start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_export_obsidian_vault() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune: Messiah", "Frank Herbert")
+        .await;
+    test_app.set_series(book_id, "Dune").await;
+    let genre_id = test_app
+        .create_test_genre(user_id, "Science Fiction", "#0000ff")
+        .await;
+    test_app.add_genre_to_book(book_id, genre_id).await;
+    test_app.set_rating(user_id, book_id, 4.0).await;
+    test_app
+        .set_reading_status(user_id, book_id, 3, "2025-01-01 10:00:00")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(
+            user_id,
+            book_id,
+            "Paul's regret",
+            "<h1>Prophecy</h1><p>A <strong>heavy</strong> book.</p>",
+        )
+        .await;
+    test_app
+        .create_test_journal(other_user_id, book_id, "Someone else's", "<p>Hidden</p>")
+        .await;
+
+    let (status, headers, bytes) = get_raw_as(&test_app, "/books/export/obsidian", user_id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/zip");
+
+    // Characters Obsidian can't use in note names are dropped
+    let note = read_vault_note(&bytes, "Library/Dune Messiah.md");
+    assert!(note.starts_with(&format!("---\nhome_library_book_id: {}\n", book_id)));
+    assert!(note.contains("title: \"Dune: Messiah\"\n"));
+    assert!(note.contains("author: \"Frank Herbert\"\n"));
+    assert!(note.contains("series: \"Dune\"\n"));
+    assert!(note.contains("genres:\n  - \"Science Fiction\"\n"));
+    assert!(note.contains("tags: []\n"));
+    assert!(note.contains("rating: 4\n"));
+    assert!(note.contains("status: \"TBR\"\n"));
+    assert!(note.contains(&format!(
+        "## Paul's regret\n%% journal: {} %%\n\n### Prophecy\n\nA **heavy** book.\n",
+        journal_id
+    )));
+    assert!(!note.contains("Someone else's"));
+}
+
+#[tokio::test]
+async fn test_import_obsidian_vault_round_trip() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    let unchanged_id = test_app
+        .create_test_journal(user_id, book_id, "First read", "<p>Sand everywhere.</p>")
+        .await;
+    let edited_id = test_app
+        .create_test_journal(user_id, book_id, "Second read", "<p>Draft</p>")
+        .await;
+
+    let (_, _, bytes) = get_raw_as(&test_app, "/books/export/obsidian", user_id).await;
+    let exported = read_vault_note(&bytes, "Library/Dune.md");
+
+    // Edit one section in "Obsidian" and add a new one
+    let edited = exported.replace(
+        "Draft",
+        "The *Fremen* are the point.\n\n### Ecology\n\n- spice\n- water",
+    ) + "\n## Third read\n\nFor the kids.\n";
+    let vault = zip_vault(&[
+        ("Library/Dune.md", &edited),
+        ("Library/Loose note.md", "Just thinking"),
+        (".obsidian/workspace.md", "ignored"),
+    ]);
+
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/books/import/obsidian",
+        user_id,
+        "file",
+        "vault.zip",
+        &vault,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["notes_imported"], 1);
+    assert_eq!(summary["notes_skipped"], 1);
+    assert_eq!(summary["journals_created"], 1);
+    assert_eq!(summary["journals_updated"], 1);
+    assert_eq!(summary["journals_unchanged"], 1);
+    let skipped = summary["notes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|note| note["status"] == "skipped")
+        .unwrap();
+    assert_eq!(skipped["file_name"], "Library/Loose note.md");
+    assert_eq!(skipped["reason"], "no home_library_book_id in front matter");
+
+    let (_, journal) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", edited_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(
+        journal["content"],
+        "<p>The <em>Fremen</em> are the point.</p>\n<h1>Ecology</h1>\n<ul>\n<li>spice</li>\n<li>water</li>\n</ul>"
+    );
+
+    // Only the edited entry gets a revision
+    let revisions_uri = |id: i64| format!("/books/{}/journals/{}/revisions", book_id, id);
+    let (_, revisions) =
+        make_request(&test_app, "GET", &revisions_uri(edited_id), user_id, None).await;
+    assert_eq!(revisions.as_array().unwrap().len(), 1);
+    let (_, revisions) = make_request(
+        &test_app,
+        "GET",
+        &revisions_uri(unchanged_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(revisions.as_array().unwrap().len(), 0);
+
+    let (_, journals) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/journals", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    let new_journal = journals
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|journal| journal["title"] == "Third read")
+        .unwrap();
+    assert_eq!(new_journal["content"], "<p>For the kids.</p>");
+    assert_eq!(new_journal["user_id"], user_id);
+}
+
+#[tokio::test]
+async fn test_import_single_obsidian_note() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    // A marker for someone else's entry doesn't let the import change it
+    let other_journal_id = test_app
+        .create_test_journal(other_user_id, book_id, "Theirs", "<p>Mine</p>")
+        .await;
+
+    let note = format!(
+        "---\nhome_library_book_id: \"{}\"\n---\n# Dune\n\n## Theirs\n%% journal: {} %%\n\nOverwritten?\n",
+        book_id, other_journal_id
+    );
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/books/import/obsidian",
+        user_id,
+        "file",
+        "Dune.md",
+        note.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["journals_created"], 1);
+    assert_eq!(summary["journals_updated"], 0);
+
+    let (_, journal) = make_request(
+        &test_app,
+        "GET",
+        &format!("/journals/{}", other_journal_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(journal["content"], "<p>Mine</p>");
+
+    let note = "---\nhome_library_book_id: 9999\n---\n## Lost\n";
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/books/import/obsidian",
+        user_id,
+        "file",
+        "Lost.md",
+        note.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        summary["notes"][0]["reason"],
+        "no book with home_library_book_id 9999"
+    );
+}
+
+#[tokio::test]
+async fn test_import_refuses_vaults_that_unpack_too_large() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    // Compresses to a few kilobytes but unpacks to 2MB
+    let huge_note = format!("## Notes\n{}", "a".repeat(2 * 1024 * 1024));
+    let vault = zip_vault(&[("Library/Huge.md", &huge_note)]);
+    assert!(vault.len() < 64 * 1024);
+
+    let (status, _) = make_multipart_request(
+        &test_app,
+        "/books/import/obsidian",
+        user_id,
+        "file",
+        "vault.zip",
+        &vault,
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,149 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::make_request;
+
+fn titles(books: &Value) -> Vec<&str> {
+    books
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| book["title"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_preferences_default_and_update() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let uri = format!("/users/{}/preferences", user_id);
+
+    let (status, preferences) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        preferences,
+        json!({
+            "default_sort": "updated",
+            "rating_display": "stars",
+            "hidden_genres": [],
+            "default_list_id": null,
+            "theme": "system"
+        })
+    );
+
+    let (status, preferences) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        user_id,
+        Some(json!({ "theme": "dark", "default_list_id": 4 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preferences["theme"], "dark");
+    assert_eq!(preferences["default_list_id"], 4);
+
+    // Other preferences are left alone, and null goes back to the default
+    let (status, preferences) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        user_id,
+        Some(json!({ "theme": null, "rating_display": "numeric" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preferences["theme"], "system");
+    assert_eq!(preferences["rating_display"], "numeric");
+    assert_eq!(preferences["default_list_id"], 4);
+
+    let (_, stored) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(stored, preferences);
+}
+
+#[tokio::test]
+async fn test_invalid_preferences_are_rejected() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let member_id = test_app.create_test_user_with_role("member").await;
+    let uri = format!("/users/{}/preferences", user_id);
+
+    for invalid in [
+        json!({ "font": "serif" }),
+        json!({ "font": null }),
+        json!({ "theme": "purple" }),
+        json!({ "hidden_genres": ["Horror"] }),
+        json!({ "default_list_id": "first" }),
+        // Nothing is saved when one of several preferences is invalid
+        json!({ "theme": "dark", "default_sort": "colour" }),
+    ] {
+        let (status, _) = make_request(&test_app, "PUT", &uri, user_id, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (_, preferences) = make_request(&test_app, "GET", &uri, user_id, None).await;
+    assert_eq!(preferences["theme"], "system");
+
+    // Only the user themselves and admins can see or change them
+    let (status, _) = make_request(&test_app, "GET", &uri, member_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        member_id,
+        Some(json!({ "theme": "dark" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) =
+        make_request(&test_app, "GET", "/users/9999/preferences", user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_get_books_applies_preferences() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+
+    test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    test_app
+        .create_test_book(user_id, "Anathem", "Neal Stephenson")
+        .await;
+    let horror_book = test_app
+        .create_test_book(user_id, "Bird Box", "Josh Malerman")
+        .await;
+    let horror = test_app.create_test_genre(user_id, "Horror", "black").await;
+    test_app.add_genre_to_book(horror_book, horror).await;
+
+    let uri = format!("/users/{}/preferences", user_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        user_id,
+        Some(json!({ "default_sort": "title", "hidden_genres": [horror] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, books) = make_request(&test_app, "GET", "/books", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&books), vec!["Anathem", "Dune"]);
+
+    // An explicit sort wins over the preference
+    let (_, books) = make_request(&test_app, "GET", "/books?sort=author", user_id, None).await;
+    assert_eq!(titles(&books), vec!["Dune", "Anathem"]);
+    let (status, _) = make_request(&test_app, "GET", "/books?sort=colour", user_id, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Other users keep the defaults
+    let (_, books) = make_request(&test_app, "GET", "/books?sort=title", other_user_id, None).await;
+    assert_eq!(titles(&books), vec!["Anathem", "Bird Box", "Dune"]);
+}
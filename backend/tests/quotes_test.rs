@@ -0,0 +1,492 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::{make_multipart_request, make_request};
+
+#[tokio::test]
+async fn test_create_and_list_book_quotes() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    let (status, quote) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        Some(json!({
+            "text": "Fear is the mind-killer.",
+            "page": 8,
+            "chapter": "Book One: Dune",
+            "note": "The litany"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(quote["text"], "Fear is the mind-killer.");
+    assert_eq!(quote["page"], 8);
+    assert_eq!(quote["location"], serde_json::Value::Null);
+    assert_eq!(quote["user"]["id"], user_id);
+    assert_eq!(quote["book"]["title"], "Dune");
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        Some(json!({ "text": "The spice must flow.", "location": "1520-1522" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        Some(json!({ "text": "He who controls the spice controls the universe.", "page": 3 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Ordered by page, with unpaged quotes last
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let pages: Vec<Option<i64>> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|q| q["page"].as_i64())
+        .collect();
+    assert_eq!(pages, vec![Some(3), Some(8), None]);
+
+    let (status, book) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book["quote_count"], 3);
+}
+
+#[tokio::test]
+async fn test_create_quote_validation() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        Some(json!({ "text": "   " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        user_id,
+        Some(json!({ "text": "A line", "page": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        "/books/99999/quotes",
+        user_id,
+        Some(json!({ "text": "A line" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_only_owner_can_update_or_delete_quote() {
+    let test_app = TestApp::new().await;
+    let owner_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(owner_id, "Test Book", "Test Author")
+        .await;
+
+    let (_status, quote) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/quotes", book_id),
+        owner_id,
+        Some(json!({ "text": "A line", "page": 10 })),
+    )
+    .await;
+    let quote_uri = format!("/books/{}/quotes/{}", book_id, quote["id"]);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &quote_uri,
+        other_id,
+        Some(json!({ "note": "Not mine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(&test_app, "DELETE", &quote_uri, other_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, updated) = make_request(
+        &test_app,
+        "PUT",
+        &quote_uri,
+        owner_id,
+        Some(json!({ "note": "Still great" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["note"], "Still great");
+    assert_eq!(updated["text"], "A line");
+    assert_eq!(updated["page"], 10);
+
+    let (status, _body) = make_request(&test_app, "DELETE", &quote_uri, owner_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_search_quotes() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_id = test_app.create_test_user().await;
+    let dune_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    let hobbit_id = test_app
+        .create_test_book(user_id, "The Hobbit", "J.R.R. Tolkien")
+        .await;
+
+    for (book_id, author_id, text) in [
+        (dune_id, user_id, "Fear is the mind-killer."),
+        (
+            hobbit_id,
+            user_id,
+            "In a hole in the ground there lived a hobbit.",
+        ),
+        (hobbit_id, other_id, "Not all those who wander are lost."),
+    ] {
+        let (status, _body) = make_request(
+            &test_app,
+            "POST",
+            &format!("/books/{}/quotes", book_id),
+            author_id,
+            Some(json!({ "text": text })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = make_request(&test_app, "GET", "/quotes?q=mind", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["book"]["title"], "Dune");
+
+    // Matches the book's author as well as the quote itself
+    let (_status, body) = make_request(&test_app, "GET", "/quotes?q=tolkien", user_id, None).await;
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (_status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/quotes?q=tolkien&user_id={}", other_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["text"], "Not all those who wander are lost.");
+
+    let (_status, body) = make_request(&test_app, "GET", "/quotes", user_id, None).await;
+    assert_eq!(body.as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_random_quote() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(&test_app, "GET", "/quotes/random", user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for text in ["First line", "Second line", "Third line"] {
+        make_request(
+            &test_app,
+            "POST",
+            &format!("/books/{}/quotes", book_id),
+            user_id,
+            Some(json!({ "text": text })),
+        )
+        .await;
+    }
+
+    let (status, quote) = make_request(&test_app, "GET", "/quotes/random", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(quote["text"].as_str().unwrap().ends_with("line"));
+
+    // The daily quote doesn't change between requests
+    let (status, first) =
+        make_request(&test_app, "GET", "/quotes/random?daily=true", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    for _ in 0..5 {
+        let (_status, again) =
+            make_request(&test_app, "GET", "/quotes/random?daily=true", user_id, None).await;
+        assert_eq!(again["id"], first["id"]);
+    }
+}
+
+const KINDLE_CLIPPINGS: &str = "\u{feff}Dune (Dune Chronicles, Book 1) (Herbert, Frank)\r
+- Your Highlight on page 8 | Location 170-172 | Added on Sunday, 3 March 2019 10:14:23\r
+\r
+I must not fear. Fear is the mind-killer.\r
+==========\r
+Dune (Dune Chronicles, Book 1) (Herbert, Frank)\r
+- Your Note on page 8 | Location 172 | Added on Sunday, 3 March 2019 10:15:01\r
+\r
+The litany against fear\r
+==========\r
+Dune (Dune Chronicles, Book 1) (Herbert, Frank)\r
+- Your Bookmark on page 40 | Location 610 | Added on Sunday, 3 March 2019 11:00:00\r
+\r
+\r
+==========\r
+Project Hail Mary (Andy Weir)\r
+- Your Highlight at location 1520-1522 | Added on Tuesday, March 5, 2019 10:14:23 PM\r
+\r
+Fist my bump.\r
+==========\r
+";
+
+#[tokio::test]
+async fn test_import_kindle_clippings() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let dune_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        KINDLE_CLIPPINGS.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["imported"], 2);
+    assert_eq!(summary["missing_books"], 1);
+    assert_eq!(summary["bookmarks_skipped"], 1);
+    assert_eq!(summary["books"][0]["book_id"], dune_id);
+    assert_eq!(summary["books"][0]["status"], "matched");
+    assert_eq!(summary["books"][1]["status"], "missing");
+    assert_eq!(summary["books"][1]["title"], "Project Hail Mary");
+
+    // The note is attached to the highlight it was written on
+    let (_status, quotes) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/quotes", dune_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(quotes.as_array().unwrap().len(), 1);
+    assert_eq!(
+        quotes[0]["text"],
+        "I must not fear. Fear is the mind-killer."
+    );
+    assert_eq!(quotes[0]["note"], "The litany against fear");
+    assert_eq!(quotes[0]["page"], 8);
+    assert_eq!(quotes[0]["location"], "170-172");
+    assert_eq!(quotes[0]["source"], "kindle");
+    assert_eq!(quotes[0]["clipped_at"], "2019-03-03 10:14:23");
+}
+
+#[tokio::test]
+async fn test_import_kindle_clippings_creates_missing_books_without_duplicates() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    // A dry run reports what would happen without saving anything
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle?create_missing_books=true&dry_run=true",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        KINDLE_CLIPPINGS.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["dry_run"], true);
+    assert_eq!(summary["imported"], 3);
+    assert_eq!(summary["books"][1]["status"], "created");
+    let (_status, quotes) = make_request(&test_app, "GET", "/quotes", user_id, None).await;
+    assert_eq!(quotes.as_array().unwrap().len(), 0);
+
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle?create_missing_books=true",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        KINDLE_CLIPPINGS.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["imported"], 3);
+    let created_id = summary["books"][1]["book_id"].as_i64().unwrap();
+
+    let (_status, book) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", created_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(book["title"], "Project Hail Mary");
+    assert_eq!(book["author"], "Andy Weir");
+    assert_eq!(book["quote_count"], 1);
+
+    // Importing the same file again matches the created book and adds nothing
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle?create_missing_books=true",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        KINDLE_CLIPPINGS.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["imported"], 0);
+    assert_eq!(summary["duplicates"], 3);
+    assert_eq!(summary["books"][1]["status"], "matched");
+
+    let (_status, quotes) = make_request(&test_app, "GET", "/quotes", user_id, None).await;
+    assert_eq!(quotes.as_array().unwrap().len(), 2);
+
+    let (status, _body) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle",
+        user_id,
+        "file",
+        "notes.txt",
+        b"just some text",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_import_kindle_second_notes_and_repeated_missing_books() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let dune_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    // A second note on the same highlight, and the same missing book under two spellings
+    // of its author
+    let clippings = format!(
+        "{KINDLE_CLIPPINGS}Dune (Dune Chronicles, Book 1) (Herbert, Frank)\r
+- Your Note on page 8 | Location 172 | Added on Sunday, 3 March 2019 10:20:00\r
+\r
+Read this before exams\r
+==========\r
+Project Hail Mary (Weir, Andy)\r
+- Your Highlight at location 2000-2001 | Added on Tuesday, March 5, 2019 10:20:00 PM\r
+\r
+Amaze!\r
+==========\r
+"
+    );
+
+    let (status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle?create_missing_books=true",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        clippings.as_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["imported"], 5);
+    assert_eq!(summary["books"][1]["status"], "created");
+    assert_eq!(summary["books"][2]["status"], "matched");
+    assert_eq!(
+        summary["books"][2]["book_id"],
+        summary["books"][1]["book_id"]
+    );
+
+    let (_status, quotes) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/quotes", dune_id),
+        user_id,
+        None,
+    )
+    .await;
+    let texts: Vec<(&str, Option<&str>)> = quotes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|quote| (quote["text"].as_str().unwrap(), quote["note"].as_str()))
+        .collect();
+    assert_eq!(texts.len(), 2);
+    assert!(texts.contains(&(
+        "I must not fear. Fear is the mind-killer.",
+        Some("The litany against fear")
+    )));
+    assert!(texts.contains(&("Read this before exams", None)));
+
+    // Importing again adds nothing
+    let (_status, summary) = make_multipart_request(
+        &test_app,
+        "/quotes/import/kindle?create_missing_books=true",
+        user_id,
+        "file",
+        "My Clippings.txt",
+        clippings.as_bytes(),
+    )
+    .await;
+    assert_eq!(summary["imported"], 0);
+    assert_eq!(summary["duplicates"], 5);
+}
@@ -0,0 +1,254 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+async fn create_dimension(test_app: &TestApp, user_id: i64, body: serde_json::Value) -> i64 {
+    let (status, created) =
+        make_request(test_app, "POST", "/rating-dimensions", user_id, Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    created["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_create_rating_dimension_defaults() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "POST",
+        "/rating-dimensions",
+        user_id,
+        Some(json!({ "name": "Plot" })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "Plot");
+    assert_eq!(body["min_value"], 0.0);
+    assert_eq!(body["max_value"], 5.0);
+    assert_eq!(body["increment"], 0.5);
+    assert_eq!(body["position"], 0);
+
+    let (status, body) = make_request(&test_app, "GET", "/rating-dimensions", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_create_rating_dimension_invalid_scale() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    for body in [
+        json!({ "name": "Backwards", "min_value": 5, "max_value": 1 }),
+        json!({ "name": "No Steps", "increment": 0 }),
+        json!({ "name": "Uneven", "max_value": 5, "increment": 2 }),
+    ] {
+        let (status, _body) =
+            make_request(&test_app, "POST", "/rating-dimensions", user_id, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_rating_with_dimension_scores() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let plot_id = create_dimension(&test_app, user_id, json!({ "name": "Plot" })).await;
+    let spice_id = create_dimension(
+        &test_app,
+        user_id,
+        json!({ "name": "Spice", "min_value": 0, "max_value": 3, "increment": 1 }),
+    )
+    .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/ratings", book_id),
+        user_id,
+        Some(json!({
+            "rating": 4.5,
+            "scores": [
+                { "dimension_id": plot_id, "score": 4.5 },
+                { "dimension_id": spice_id, "score": 1 }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/ratings", book_id),
+        other_user_id,
+        Some(json!({
+            "rating": 3,
+            "scores": [{ "dimension_id": plot_id, "score": 3 }]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let averages = body["dimension_averages"].as_array().unwrap();
+    assert_eq!(averages.len(), 2);
+    assert_eq!(averages[0]["name"], "Plot");
+    assert_eq!(averages[0]["average"], 3.75);
+    assert_eq!(averages[0]["rating_count"], 2);
+    assert_eq!(averages[1]["name"], "Spice");
+    assert_eq!(averages[1]["average"], 1.0);
+    assert_eq!(averages[1]["max_value"], 3.0);
+
+    let rating = body["ratings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["user_id"] == user_id)
+        .unwrap();
+    assert_eq!(rating["rating"], 4.5);
+    assert_eq!(rating["scores"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_rating_without_scores_keeps_existing_scores() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let plot_id = create_dimension(&test_app, user_id, json!({ "name": "Plot" })).await;
+
+    let uri = format!("/books/{}/ratings", book_id);
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        user_id,
+        Some(json!({ "rating": 4, "scores": [{ "dimension_id": plot_id, "score": 5 }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        user_id,
+        Some(json!({ "rating": 3.5 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(body["ratings"][0]["rating"], 3.5);
+    assert_eq!(body["ratings"][0]["scores"][0]["score"], 5.0);
+}
+
+#[tokio::test]
+async fn test_rating_rejects_off_scale_scores() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let plot_id = create_dimension(&test_app, user_id, json!({ "name": "Plot" })).await;
+    let uri = format!("/books/{}/ratings", book_id);
+
+    for body in [
+        // Overall rating must be in half stars
+        json!({ "rating": 4.1 }),
+        json!({ "rating": 4.25 }),
+        json!({ "rating": 5.5 }),
+        // Plot only allows half stars
+        json!({ "rating": 4, "scores": [{ "dimension_id": plot_id, "score": 4.25 }] }),
+        // Unknown dimension
+        json!({ "rating": 4, "scores": [{ "dimension_id": 9999, "score": 1 }] }),
+        // Duplicate dimension
+        json!({ "rating": 4, "scores": [
+            { "dimension_id": plot_id, "score": 1 },
+            { "dimension_id": plot_id, "score": 2 }
+        ] }),
+    ] {
+        let (status, _body) = make_request(&test_app, "POST", &uri, user_id, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_delete_rating_dimension_removes_scores() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let plot_id = create_dimension(&test_app, user_id, json!({ "name": "Plot" })).await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/ratings", book_id),
+        user_id,
+        Some(json!({ "rating": 4, "scores": [{ "dimension_id": plot_id, "score": 4 }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/rating-dimensions/{}", plot_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(body["dimension_averages"], json!([]));
+    assert_eq!(body["ratings"][0]["scores"], json!([]));
+
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/rating-dimensions/{}", plot_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,257 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::make_request;
+
+fn find_recommendation(body: &Value, book_id: i64) -> Option<&Value> {
+    body.as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["book"]["id"] == book_id)
+}
+
+fn reason_kinds(recommendation: &Value) -> Vec<&str> {
+    recommendation["reasons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["kind"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_recommendations_empty_library() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn test_recommendations_genre_and_tag_overlap() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let fantasy = test_app
+        .create_test_genre(user_id, "Fantasy", "#F59E0B")
+        .await;
+    let dragons = test_app
+        .create_test_tag(user_id, "dragons", "#EF4444")
+        .await;
+
+    let loved = test_app.create_test_book(user_id, "Loved", "Author").await;
+    let similar = test_app
+        .create_test_book(user_id, "Similar", "Author")
+        .await;
+    let unrelated = test_app
+        .create_test_book(user_id, "Unrelated", "Author")
+        .await;
+
+    test_app.add_genre_to_book(loved, fantasy).await;
+    test_app.add_tag_to_book(loved, dragons).await;
+    test_app.add_genre_to_book(similar, fantasy).await;
+    test_app.add_tag_to_book(similar, dragons).await;
+    test_app.set_rating(user_id, loved, 5.0).await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    // Rated books and books with no matching signals aren't suggested
+    assert!(find_recommendation(&body, loved).is_none());
+    assert!(find_recommendation(&body, unrelated).is_none());
+
+    let recommendation = find_recommendation(&body, similar).unwrap();
+    assert_eq!(
+        reason_kinds(recommendation),
+        vec!["genre_overlap", "tag_overlap"]
+    );
+    assert_eq!(recommendation["score"], 1.5);
+    assert!(
+        recommendation["reasons"][0]["explanation"]
+            .as_str()
+            .unwrap()
+            .contains("Fantasy")
+    );
+}
+
+#[tokio::test]
+async fn test_recommendations_from_similar_taste() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let friend_id = test_app.create_test_user().await;
+
+    // Three books both users rated the same way round
+    for (own, theirs) in [(5.0, 4.5), (3.0, 2.5), (1.0, 1.5)] {
+        let book_id = test_app.create_test_book(user_id, "Shared", "Author").await;
+        test_app.set_rating(user_id, book_id, own).await;
+        test_app.set_rating(friend_id, book_id, theirs).await;
+    }
+
+    let friend_favourite = test_app
+        .create_test_book(user_id, "Friend Favourite", "Author")
+        .await;
+    test_app.set_rating(friend_id, friend_favourite, 5.0).await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let recommendation = find_recommendation(&body, friend_favourite).unwrap();
+    assert_eq!(reason_kinds(recommendation), vec!["similar_taste"]);
+    assert!(
+        recommendation["reasons"][0]["explanation"]
+            .as_str()
+            .unwrap()
+            .starts_with("Test User rated it 5 stars")
+    );
+}
+
+#[tokio::test]
+async fn test_recommendations_next_in_series_and_sequenced_list() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let first = test_app
+        .create_test_book(user_id, "Book One", "Author")
+        .await;
+    let second = test_app
+        .create_test_book(user_id, "Book Two", "Author")
+        .await;
+    let third = test_app
+        .create_test_book(user_id, "Book Three", "Author")
+        .await;
+    for book_id in [first, second, third] {
+        test_app.set_series(book_id, "The Saga").await;
+    }
+    test_app
+        .set_reading_status(user_id, first, 1, "2025-01-01 10:00:00")
+        .await;
+
+    let list_first = test_app
+        .create_test_book(user_id, "List First", "Author")
+        .await;
+    let list_second = test_app
+        .create_test_book(user_id, "List Second", "Author")
+        .await;
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        user_id,
+        Some(json!({ "type_id": 1, "name": "Summer", "books": [list_first, list_second] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations", user_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only the next unread book in the series is suggested
+    let next = find_recommendation(&body, second).unwrap();
+    assert_eq!(reason_kinds(next), vec!["series_next"]);
+    assert!(find_recommendation(&body, third).is_none());
+
+    let up_next = find_recommendation(&body, list_first).unwrap();
+    let later = find_recommendation(&body, list_second).unwrap();
+    assert_eq!(reason_kinds(up_next), vec!["sequenced_list"]);
+    assert!(up_next["score"].as_f64() > later["score"].as_f64());
+
+    // Highest scores come first
+    assert_eq!(body[0]["book"]["id"], second);
+}
+
+#[tokio::test]
+async fn test_recommendations_limit_and_unknown_user() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations?limit=0", user_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        "/users/99999/recommendations",
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_recommendations_ignore_other_libraries() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let cabin_id = test_app.create_test_library("Cabin").await;
+    test_app.add_library_member(cabin_id, user_id).await;
+    let fantasy = test_app
+        .create_test_genre(user_id, "Fantasy", "#F59E0B")
+        .await;
+
+    // A loved book in another library says nothing about this one
+    let cabin_loved = test_app.create_test_book(user_id, "Loved", "Author").await;
+    sqlx::query("UPDATE books SET library_id = ? WHERE id = ?")
+        .bind(cabin_id)
+        .bind(cabin_loved)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    test_app.add_genre_to_book(cabin_loved, fantasy).await;
+    test_app.set_rating(user_id, cabin_loved, 5.0).await;
+
+    let similar = test_app
+        .create_test_book(user_id, "Similar", "Author")
+        .await;
+    test_app.add_genre_to_book(similar, fantasy).await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/recommendations", user_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([]));
+}
@@ -0,0 +1,210 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+#[tokio::test]
+async fn test_create_review_with_rating() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/reviews", book_id),
+        user_id,
+        Some(json!({
+            "headline": "A slow burn",
+            "body": "Took a while to get going but worth it.",
+            "rating": 4.5
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["book_id"], book_id);
+    assert_eq!(body["user"]["id"], user_id);
+    assert_eq!(body["headline"], "A slow burn");
+    assert_eq!(body["is_spoiler"], false);
+    assert_eq!(body["is_redacted"], false);
+    assert_eq!(body["rating"], 4.5);
+
+    // The embedded rating is the user's rating for the book
+    let (_status, rating) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/ratings", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(rating, 4.5);
+}
+
+#[tokio::test]
+async fn test_one_review_per_user_per_book() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+    let uri = format!("/books/{}/reviews", book_id);
+    let review = json!({ "headline": "Great", "body": "Loved it" });
+
+    let (status, _body) =
+        make_request(&test_app, "POST", &uri, user_id, Some(review.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(&test_app, "POST", &uri, user_id, Some(review)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_create_review_validation() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/reviews", book_id),
+        user_id,
+        Some(json!({ "headline": " ", "body": "Loved it" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/reviews", book_id),
+        user_id,
+        Some(json!({ "headline": "Great", "body": "Loved it", "rating": 6 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        "/books/99999/reviews",
+        user_id,
+        Some(json!({ "headline": "Great", "body": "Loved it" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_spoiler_reviews_redacted_until_finished() {
+    let test_app = TestApp::new().await;
+    let author_id = test_app.create_test_user().await;
+    let reader_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(author_id, "Test Book", "Test Author")
+        .await;
+    let uri = format!("/books/{}/reviews", book_id);
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &uri,
+        author_id,
+        Some(
+            json!({ "headline": "That ending!", "body": "The butler did it", "is_spoiler": true }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The reviewer always sees their own review
+    let (_status, body) = make_request(&test_app, "GET", &uri, author_id, None).await;
+    assert_eq!(body[0]["body"], "The butler did it");
+
+    // A reader who is still reading sees only the headline
+    test_app
+        .set_reading_status(reader_id, book_id, 2, "2025-01-01 10:00:00")
+        .await;
+    let (status, body) = make_request(&test_app, "GET", &uri, reader_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["headline"], "That ending!");
+    assert_eq!(body[0]["body"], json!(null));
+    assert_eq!(body[0]["is_redacted"], true);
+
+    // Once they've finished it the full review is shown
+    test_app
+        .set_reading_status(reader_id, book_id, 1, "2025-02-01 10:00:00")
+        .await;
+    let (_status, body) = make_request(&test_app, "GET", &uri, reader_id, None).await;
+    assert_eq!(body[0]["body"], "The butler did it");
+    assert_eq!(body[0]["is_redacted"], false);
+}
+
+#[tokio::test]
+async fn test_update_and_delete_review() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (_status, created) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/reviews", book_id),
+        user_id,
+        Some(json!({ "headline": "Great", "body": "Loved it" })),
+    )
+    .await;
+    let review_uri = format!("/books/{}/reviews/{}", book_id, created["id"]);
+
+    let (status, body) = make_request(
+        &test_app,
+        "PUT",
+        &review_uri,
+        user_id,
+        Some(json!({ "is_spoiler": true, "rating": 3.5 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["headline"], "Great");
+    assert_eq!(body["is_spoiler"], true);
+    assert_eq!(body["rating"], 3.5);
+
+    // Other users can't edit or delete someone else's review
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        &review_uri,
+        other_user_id,
+        Some(json!({ "headline": "Hijacked" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(&test_app, "DELETE", &review_uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _body) = make_request(&test_app, "DELETE", &review_uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}/reviews", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(body, json!([]));
+}
@@ -0,0 +1,446 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::{get_raw_as, make_request};
+
+fn book_ids(books: &Value) -> Vec<i64> {
+    books
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| book["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_child_does_not_see_restricted_books() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let child_id = test_app.create_test_user_with_role("child").await;
+
+    let open_book = test_app
+        .create_test_book(admin_id, "Dune", "Frank Herbert")
+        .await;
+    let tagged_book = test_app
+        .create_test_book(admin_id, "Dune Messiah", "Frank Herbert")
+        .await;
+    let genred_book = test_app
+        .create_test_book(admin_id, "It", "Stephen King")
+        .await;
+
+    let (status, tag) = make_request(
+        &test_app,
+        "POST",
+        "/tags",
+        admin_id,
+        Some(json!({ "name": "Adults only", "color": "red", "is_restricted": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tag["is_restricted"], true);
+    test_app
+        .add_tag_to_book(tagged_book, tag["id"].as_i64().unwrap())
+        .await;
+
+    let (status, genre) = make_request(
+        &test_app,
+        "POST",
+        "/genres",
+        admin_id,
+        Some(json!({ "name": "Horror", "color": "black", "is_restricted": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(genre["is_restricted"], true);
+    test_app
+        .add_genre_to_book(genred_book, genre["id"].as_i64().unwrap())
+        .await;
+
+    test_app
+        .create_test_journal(admin_id, tagged_book, "Thoughts", "<p>Spoilers</p>")
+        .await;
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        admin_id,
+        Some(json!({ "type_id": 1, "name": "Desert", "books": [open_book, tagged_book] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Admins and members see everything
+    let (_, books) = make_request(&test_app, "GET", "/books", admin_id, None).await;
+    assert_eq!(books.as_array().unwrap().len(), 3);
+
+    let (status, books) = make_request(&test_app, "GET", "/books", child_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book_ids(&books), vec![open_book]);
+
+    let (_, books) = make_request(&test_app, "GET", "/books?search=Dune", child_id, None).await;
+    assert_eq!(book_ids(&books), vec![open_book]);
+
+    let uri = format!("/books/{}", tagged_book);
+    let (status, _) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let uri = format!("/books/{}/journals", tagged_book);
+    let (_, journals) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    assert_eq!(journals.as_array().unwrap().len(), 0);
+
+    let (_, feed) = make_request(&test_app, "GET", "/journals", admin_id, None).await;
+    assert_eq!(feed["journals"].as_array().unwrap().len(), 1);
+    let (_, feed) = make_request(&test_app, "GET", "/journals", child_id, None).await;
+    assert_eq!(feed["journals"].as_array().unwrap().len(), 0);
+
+    let (_, lists) = make_request(&test_app, "GET", "/lists", child_id, None).await;
+    assert_eq!(book_ids(&lists[0]["books"]), vec![open_book]);
+
+    // Lifting the restriction shows the book again
+    let uri = format!("/tags/{}", tag["id"]);
+    let (status, tag) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        admin_id,
+        Some(json!({ "name": "Adults only", "color": "red", "is_restricted": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tag["is_restricted"], false);
+
+    let (_, books) = make_request(&test_app, "GET", "/books", child_id, None).await;
+    assert_eq!(books.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_only_admins_manage_tags_and_genres() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let member_id = test_app.create_test_user_with_role("member").await;
+    let tag_id = test_app
+        .create_test_tag(admin_id, "Favourites", "gold")
+        .await;
+    let genre_id = test_app
+        .create_test_genre(admin_id, "Fantasy", "green")
+        .await;
+
+    let tag = json!({ "name": "Mine", "color": "blue" });
+    let (status, _) = make_request(&test_app, "POST", "/tags", member_id, Some(tag.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let uri = format!("/tags/{}", tag_id);
+    let (status, _) = make_request(&test_app, "PUT", &uri, member_id, Some(tag)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, member_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let genre = json!({ "name": "Mine", "color": "blue" });
+    let (status, _) =
+        make_request(&test_app, "POST", "/genres", member_id, Some(genre.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let uri = format!("/genres/{}", genre_id);
+    let (status, _) = make_request(&test_app, "PUT", &uri, member_id, Some(genre)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, member_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Renaming without mentioning the restriction leaves it alone
+    sqlx::query("UPDATE genres SET is_restricted = 1 WHERE id = ?")
+        .bind(genre_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    let (status, genre) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        admin_id,
+        Some(json!({ "name": "High fantasy", "color": "green" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(genre["name"], "High fantasy");
+    assert_eq!(genre["is_restricted"], true);
+}
+
+#[tokio::test]
+async fn test_child_cannot_edit_other_users_data() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let child_id = test_app.create_test_user_with_role("child").await;
+
+    let book_id = test_app
+        .create_test_book(admin_id, "Matilda", "Roald Dahl")
+        .await;
+    let journal_id = test_app
+        .create_test_journal(admin_id, book_id, "Notes", "<p>Lovely</p>")
+        .await;
+
+    let book_uri = format!("/books/{}", book_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &book_uri,
+        child_id,
+        Some(json!({ "title": "Renamed" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &book_uri, child_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let journal_uri = format!("/books/{}/journals/{}", book_id, journal_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &journal_uri,
+        child_id,
+        Some(json!({ "title": "Mine now" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = make_request(&test_app, "DELETE", &journal_uri, child_id, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let user_uri = format!("/users/{}", admin_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &user_uri,
+        child_id,
+        Some(json!({ "name": "Hacked" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Their own book and entries are still theirs to change
+    let own_book_id = test_app
+        .create_test_book(child_id, "Holes", "Louis Sachar")
+        .await;
+    let own_journal_id = test_app
+        .create_test_journal(child_id, own_book_id, "Camp", "<p>Digging</p>")
+        .await;
+    let uri = format!("/books/{}", own_book_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        child_id,
+        Some(json!({ "title": "Holes (again)" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let uri = format!("/books/{}/journals/{}", own_book_id, own_journal_id);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, child_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_only_admins_manage_users() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let member_id = test_app.create_test_user_with_role("member").await;
+
+    let new_user = json!({ "name": "Sam", "color": "bg-green-500", "role": "child" });
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        "/users",
+        member_id,
+        Some(new_user.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, user) = make_request(&test_app, "POST", "/users", admin_id, Some(new_user)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["role"], "child");
+
+    // Members can edit themselves but not promote themselves
+    let uri = format!("/users/{}", member_id);
+    let (status, user) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        member_id,
+        Some(json!({ "name": "Renamed" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["name"], "Renamed");
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        member_id,
+        Some(json!({ "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        admin_id,
+        Some(json!({ "role": "superuser" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The household always keeps an admin. The users seeded by the migrations are
+    // admins too, so demote them first.
+    sqlx::query("UPDATE users SET role = 'member' WHERE id NOT IN (?, ?)")
+        .bind(admin_id)
+        .bind(member_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    let uri = format!("/users/{}", admin_id);
+    let (status, _) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        admin_id,
+        Some(json!({ "role": "member" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let uri = format!("/users/{}", member_id);
+    let (status, user) = make_request(
+        &test_app,
+        "PUT",
+        &uri,
+        admin_id,
+        Some(json!({ "role": "admin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["role"], "admin");
+}
+
+#[tokio::test]
+async fn test_child_does_not_see_restricted_books_in_reports() {
+    let test_app = TestApp::new().await;
+    let admin_id = test_app.create_test_user().await;
+    let child_id = test_app.create_test_user_with_role("child").await;
+
+    let open_book = test_app
+        .create_test_book(admin_id, "Dune", "Frank Herbert")
+        .await;
+    let restricted_book = test_app
+        .create_test_book(admin_id, "It", "Stephen King")
+        .await;
+    let genre_id = test_app
+        .create_test_genre(admin_id, "Horror", "black")
+        .await;
+    sqlx::query("UPDATE genres SET is_restricted = 1 WHERE id = ?")
+        .bind(genre_id)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    test_app.add_genre_to_book(restricted_book, genre_id).await;
+
+    // Both books share a genre with one the child loved, so both would be recommended
+    let liked_book = test_app
+        .create_test_book(child_id, "Holes", "Louis Sachar")
+        .await;
+    let shared_genre_id = test_app
+        .create_test_genre(admin_id, "Fiction", "blue")
+        .await;
+    for book_id in [liked_book, open_book, restricted_book] {
+        test_app.add_genre_to_book(book_id, shared_genre_id).await;
+    }
+    test_app.set_rating(child_id, liked_book, 5.0).await;
+
+    for book_id in [open_book, restricted_book] {
+        let (status, _) = make_request(
+            &test_app,
+            "POST",
+            &format!("/books/{}/quotes", book_id),
+            admin_id,
+            Some(json!({ "text": "Quoted" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        test_app
+            .create_test_journal(admin_id, book_id, "Thoughts", "<p>Noted</p>")
+            .await;
+        test_app.set_rating(admin_id, book_id, 5.0).await;
+    }
+
+    let (_, quotes) = make_request(&test_app, "GET", "/quotes", child_id, None).await;
+    assert_eq!(quotes.as_array().unwrap().len(), 1);
+    assert_eq!(quotes[0]["book_id"], open_book);
+    let uri = format!("/books/{}/quotes", restricted_book);
+    let (_, quotes) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    assert_eq!(quotes, json!([]));
+    for _ in 0..5 {
+        let (_, quote) = make_request(&test_app, "GET", "/quotes/random", child_id, None).await;
+        assert_eq!(quote["book_id"], open_book);
+    }
+
+    let uri = format!("/users/{}/recommendations", child_id);
+    let (_, recommendations) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    let recommended: Vec<i64> = recommendations
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|recommendation| recommendation["book"]["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(recommended, vec![open_book]);
+
+    let uri = format!("/users/{}/compatibility/{}", child_id, admin_id);
+    let (_, report) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    let loved = report["loved_by_other_unread_by_user"].as_array().unwrap();
+    assert_eq!(loved.len(), 1);
+    assert_eq!(loved[0]["book"]["id"], open_book);
+
+    let uri = format!("/users/{}/journals/export", admin_id);
+    let (status, _, bytes) = get_raw_as(&test_app, &uri, child_id).await;
+    assert_eq!(status, StatusCode::OK);
+    let archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    assert!(archive.file_names().all(|name| name.starts_with("dune/")));
+    assert_eq!(archive.len(), 1);
+
+    for book_id in [open_book, restricted_book] {
+        test_app
+            .set_reading_status(admin_id, book_id, 1, "2025-03-01 10:00:00")
+            .await;
+    }
+    sqlx::query("UPDATE journal_entries SET created_at = '2025-03-01 10:00:00'")
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    let uri = format!("/users/{}/year-in-review/2025", admin_id);
+    let (_, report) = make_request(&test_app, "GET", &uri, admin_id, None).await;
+    assert_eq!(report["books_read"], 2);
+    let (_, report) = make_request(&test_app, "GET", &uri, child_id, None).await;
+    assert_eq!(report["books_read"], 1);
+    assert_eq!(report["covers"].as_array().unwrap().len(), 1);
+    assert_eq!(report["covers"][0]["book_id"], open_book);
+    assert_eq!(report["highest_rated"]["id"], open_book);
+    assert_eq!(report["favourite_genre"]["name"], "Fiction");
+    assert_eq!(report["favourite_genre"]["book_count"], 1);
+    assert_eq!(report["most_journaled_book"]["id"], open_book);
+    assert_eq!(report["journal_count"], 1);
+    let (_, _, html) = get_raw_as(&test_app, &format!("{}?format=html", uri), child_id).await;
+    assert!(!String::from_utf8(html).unwrap().contains("Stephen King"));
+
+    // Nor can they write about a restricted book
+    let (status, _) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/journals", restricted_book),
+        child_id,
+        Some(json!({ "title": "Scary", "content": "Clowns" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
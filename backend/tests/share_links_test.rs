@@ -0,0 +1,229 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+use utils::{get_raw, make_request};
+
+// Opens a share link the way someone without an account would
+async fn open_shared(test_app: &TestApp, token: &str) -> (StatusCode, Value) {
+    let (status, _, bytes) = get_raw(test_app, &format!("/shared/{}", token)).await;
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn create_share_link(
+    test_app: &TestApp,
+    user_id: i64,
+    request: Value,
+) -> (StatusCode, Value) {
+    make_request(test_app, "POST", "/share-links", user_id, Some(request)).await
+}
+
+#[tokio::test]
+async fn test_shared_book_and_list() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+
+    let book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+    let other_book_id = test_app
+        .create_test_book(user_id, "Anathem", "Neal Stephenson")
+        .await;
+    test_app
+        .create_test_journal(other_user_id, book_id, "Loved it", "The spice!")
+        .await;
+    for (author_id, title) in [(other_user_id, "Secret"), (user_id, "My secret")] {
+        let journal_id = test_app
+            .create_test_journal(author_id, book_id, title, "Nobody else should see this")
+            .await;
+        sqlx::query("UPDATE journal_entries SET visibility = 'private' WHERE id = ?")
+            .bind(journal_id)
+            .execute(&test_app.pool)
+            .await
+            .unwrap();
+    }
+
+    let (status, share_link) =
+        create_share_link(&test_app, user_id, json!({ "book_id": book_id })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(share_link["open_count"], 0);
+    assert!(share_link["expires_at"].is_null());
+    let book_token = share_link["token"].as_str().unwrap();
+
+    let (status, shared) = open_shared(&test_app, book_token).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(shared["shared_by"], "Test User");
+    assert_eq!(shared["book"]["title"], "Dune");
+    assert!(shared["list"].is_null());
+    // Only household journals, with authors by name
+    let journals = shared["book"]["journals"].as_array().unwrap();
+    assert_eq!(journals.len(), 1);
+    assert_eq!(journals[0]["title"], "Loved it");
+    assert!(journals[0].get("user").is_none());
+
+    let (_, list) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        user_id,
+        Some(json!({ "type_id": 1, "name": "Summer 2026", "books": [other_book_id, book_id] })),
+    )
+    .await;
+    let (status, share_link) = create_share_link(
+        &test_app,
+        user_id,
+        json!({ "list_id": list["id"], "expires_in_days": 30 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(share_link["expires_at"].is_string());
+    let list_token = share_link["token"].as_str().unwrap();
+    assert_ne!(list_token, book_token);
+
+    let (status, shared) = open_shared(&test_app, list_token).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(shared["list"]["name"], "Summer 2026");
+    let titles: Vec<&str> = shared["list"]["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|book| book["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Anathem", "Dune"]);
+
+    // Owners see how often each link was opened
+    open_shared(&test_app, book_token).await;
+    let (status, share_links) = make_request(&test_app, "GET", "/share-links", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let open_counts: Vec<(&str, i64)> = share_links
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|link| {
+            (
+                link["token"].as_str().unwrap(),
+                link["open_count"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(open_counts, vec![(list_token, 1), (book_token, 2)]);
+
+    let (_, share_links) =
+        make_request(&test_app, "GET", "/share-links", other_user_id, None).await;
+    assert_eq!(share_links, json!([]));
+}
+
+#[tokio::test]
+async fn test_revoked_expired_and_invalid_share_links() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let other_user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune", "Frank Herbert")
+        .await;
+
+    let (status, _) = open_shared(&test_app, "not-a-token").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for invalid in [
+        json!({}),
+        json!({ "book_id": book_id, "list_id": 1 }),
+        json!({ "book_id": book_id, "expires_in_days": 0 }),
+    ] {
+        let (status, _) = create_share_link(&test_app, user_id, invalid).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // Only your own lists can be shared
+    let (_, list) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        user_id,
+        Some(json!({ "type_id": 2, "name": "Favourites", "books": [book_id] })),
+    )
+    .await;
+    let (status, _) =
+        create_share_link(&test_app, other_user_id, json!({ "list_id": list["id"] })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = create_share_link(&test_app, user_id, json!({ "book_id": 9999 })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, share_link) =
+        create_share_link(&test_app, user_id, json!({ "book_id": book_id })).await;
+    let token = share_link["token"].as_str().unwrap();
+    let uri = format!("/share-links/{}", share_link["id"]);
+
+    let (status, _) = make_request(&test_app, "DELETE", &uri, other_user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = open_shared(&test_app, token).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, share_link) = create_share_link(
+        &test_app,
+        user_id,
+        json!({ "book_id": book_id, "expires_in_days": 1 }),
+    )
+    .await;
+    let token = share_link["token"].as_str().unwrap();
+    let (status, _) = open_shared(&test_app, token).await;
+    assert_eq!(status, StatusCode::OK);
+    sqlx::query("UPDATE share_links SET expires_at = datetime('now', '-1 minute') WHERE token = ?")
+        .bind(token)
+        .execute(&test_app.pool)
+        .await
+        .unwrap();
+    let (status, _) = open_shared(&test_app, token).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_links_that_no_longer_show_anything_are_not_counted() {
+    let test_app = TestApp::new().await;
+    let owner_id = test_app.create_test_user().await;
+    let collaborator_id = test_app.create_test_user().await;
+
+    let (_, list) = make_request(
+        &test_app,
+        "POST",
+        "/lists",
+        owner_id,
+        Some(json!({ "type_id": 1, "name": "Book club", "books": [] })),
+    )
+    .await;
+    let collaborators_uri = format!("/lists/{}/collaborators", list["id"]);
+    make_request(
+        &test_app,
+        "POST",
+        &collaborators_uri,
+        owner_id,
+        Some(json!({ "user_id": collaborator_id })),
+    )
+    .await;
+    let (status, share_link) =
+        create_share_link(&test_app, collaborator_id, json!({ "list_id": list["id"] })).await;
+    assert_eq!(status, StatusCode::OK);
+    let token = share_link["token"].as_str().unwrap();
+
+    let (status, _) = open_shared(&test_app, token).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Once the collaborator is removed their link stops working, and those tries don't count
+    let uri = format!("{}/{}", collaborators_uri, collaborator_id);
+    let (status, _) = make_request(&test_app, "DELETE", &uri, owner_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = open_shared(&test_app, token).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, share_links) =
+        make_request(&test_app, "GET", "/share-links", collaborator_id, None).await;
+    assert_eq!(share_links[0]["open_count"], 1);
+}
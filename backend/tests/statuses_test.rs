@@ -0,0 +1,262 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+#[tokio::test]
+async fn test_get_statuses_defaults() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(&test_app, "GET", "/statuses", user_id, None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["UNREAD", "READING", "TBR", "READ", "DNF"]);
+
+    let read = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["name"] == "READ")
+        .unwrap();
+    assert_eq!(read["is_finished"], true);
+    assert_eq!(read["is_active"], false);
+    assert_eq!(read["is_planned"], false);
+    assert_eq!(body[2]["is_planned"], true);
+}
+
+#[tokio::test]
+async fn test_create_status_and_use_it() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, created) = make_request(
+        &test_app,
+        "POST",
+        "/statuses",
+        user_id,
+        Some(json!({ "name": "ON HOLD", "color": "#A855F7" })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["name"], "ON HOLD");
+    assert_eq!(created["color"], "#A855F7");
+    assert_eq!(created["position"], 5);
+    assert_eq!(created["is_finished"], false);
+    let status_id = created["id"].as_i64().unwrap();
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/status", book_id),
+        user_id,
+        Some(json!({ "status_id": status_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/books/{}", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["current_user_status"], status_id);
+    assert_eq!(body["statuses"][0]["status_name"], "ON HOLD");
+}
+
+#[tokio::test]
+async fn test_upsert_status_rejects_unknown_status() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/status", book_id),
+        user_id,
+        Some(json!({ "status_id": 42 })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_update_status() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "PUT",
+        "/statuses/3",
+        user_id,
+        Some(json!({ "name": "OWNED UNREAD", "position": 10 })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], 3);
+    assert_eq!(body["name"], "OWNED UNREAD");
+    assert_eq!(body["position"], 10);
+    // Unchanged fields are preserved
+    assert_eq!(body["color"], "#F59E0B");
+}
+
+#[tokio::test]
+async fn test_status_cannot_be_finished_and_active() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        "/statuses",
+        user_id,
+        Some(json!({ "name": "CONFUSED", "is_finished": true, "is_active": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // READING is already active, so marking it finished conflicts
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        "/statuses/2",
+        user_id,
+        Some(json!({ "is_finished": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _body) = make_request(
+        &test_app,
+        "PUT",
+        "/statuses/2",
+        user_id,
+        Some(json!({ "is_planned": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_random_book_defaults_to_the_planned_status() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (_, created) = make_request(
+        &test_app,
+        "POST",
+        "/statuses",
+        user_id,
+        Some(json!({ "name": "UP NEXT", "is_planned": true, "position": 0 })),
+    )
+    .await;
+    assert_eq!(created["is_planned"], true);
+    test_app
+        .set_reading_status(
+            user_id,
+            book_id,
+            created["id"].as_i64().unwrap(),
+            "2025-01-01 10:00:00",
+        )
+        .await;
+
+    // UP NEXT now comes before TBR, so it is where the picker looks
+    let (status, book) = make_request(&test_app, "GET", "/books/random", user_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(book["id"], book_id);
+
+    for id in [3, created["id"].as_i64().unwrap()] {
+        let (status, _body) = make_request(
+            &test_app,
+            "PUT",
+            &format!("/statuses/{}", id),
+            user_id,
+            Some(json!({ "is_planned": false })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _body) = make_request(&test_app, "GET", "/books/random", user_id, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_create_status_duplicate_name() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        "/statuses",
+        user_id,
+        Some(json!({ "name": "READ" })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_delete_status_in_use_is_blocked() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Test Book", "Test Author")
+        .await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "POST",
+        &format!("/books/{}/status", book_id),
+        user_id,
+        Some(json!({ "status_id": 99 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _body) = make_request(&test_app, "DELETE", "/statuses/99", user_id, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Once no book uses it, the status can be deleted
+    let (status, _body) = make_request(
+        &test_app,
+        "DELETE",
+        &format!("/books/{}/status", book_id),
+        user_id,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) = make_request(&test_app, "DELETE", "/statuses/99", user_id, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _body) = make_request(&test_app, "GET", "/statuses/99", user_id, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -1,5 +1,13 @@
-use axum::{body::Body, extract::Request, http::StatusCode};
+// Each test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderMap, StatusCode},
+};
 use http_body_util::BodyExt;
+#[allow(clippy::single_component_path_imports)]
 use serde_json;
 use tower::ServiceExt;
 
@@ -40,3 +48,161 @@ pub async fn make_request(
 
     (status, json_body)
 }
+
+/// Sends a multipart/form-data request with a single file field
+pub async fn make_multipart_request(
+    app: &TestApp,
+    uri: &str,
+    user_id: i64,
+    field_name: &str,
+    file_name: &str,
+    bytes: &[u8],
+) -> (StatusCode, serde_json::Value) {
+    let boundary = "test-boundary-7MA4YWxkTrZu0gW";
+    let mut body = Vec::new();
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field_name}\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("currentUserId", user_id.to_string())
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(Body::from(body))
+        .unwrap();
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json_body = serde_json::from_slice(&body_bytes).unwrap_or(serde_json::Value::Null);
+
+    (status, json_body)
+}
+
+/// Sends a GET without any user header and returns the raw response body
+pub async fn get_raw(app: &TestApp, uri: &str) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, headers, body_bytes.to_vec())
+}
+
+/// Sends a GET that names a library but no user, like the login screen does
+pub async fn get_raw_in_library(
+    app: &TestApp,
+    uri: &str,
+    library_id: i64,
+) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .header("currentLibraryId", library_id.to_string())
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, headers, body_bytes.to_vec())
+}
+
+/// Sends a GET as `user_id` and returns the raw response body
+pub async fn get_raw_as(
+    app: &TestApp,
+    uri: &str,
+    user_id: i64,
+) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .header("currentUserId", user_id.to_string())
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, headers, body_bytes.to_vec())
+}
+
+/// Sends a JSON request authenticated with an API token instead of the user header
+pub async fn make_token_request(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request_builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {token}"))
+        .header("content-type", "application/json");
+
+    let request = match body {
+        Some(body) => request_builder
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap(),
+        None => request_builder.body(Body::empty()).unwrap(),
+    };
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json_body = serde_json::from_slice(&body_bytes).unwrap_or(serde_json::Value::Null);
+
+    (status, json_body)
+}
+
+/// Sends a JSON request as `user_id` working in `library_id`
+pub async fn make_library_request(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    user_id: i64,
+    library_id: i64,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request_builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("currentUserId", user_id.to_string())
+        .header("currentLibraryId", library_id.to_string())
+        .header("content-type", "application/json");
+
+    let request = match body {
+        Some(body) => request_builder
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap(),
+        None => request_builder.body(Body::empty()).unwrap(),
+    };
+
+    let response = app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json_body = serde_json::from_slice(&body_bytes).unwrap_or(serde_json::Value::Null);
+
+    (status, json_body)
+}
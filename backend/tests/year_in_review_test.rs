@@ -0,0 +1,248 @@
+mod common;
+mod utils;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use utils::make_request;
+
+#[tokio::test]
+async fn test_year_in_review_empty_year() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/year-in-review/2025", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["year"], 2025);
+    assert_eq!(body["user"]["id"], user_id);
+    assert_eq!(body["books_read"], 0);
+    assert_eq!(body["total_pages"], 0);
+    assert_eq!(body["average_rating"], json!(null));
+    assert_eq!(body["highest_rated"], json!(null));
+    assert_eq!(body["covers"], json!([]));
+}
+
+#[tokio::test]
+async fn test_year_in_review_summarises_finished_books() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let genre_id = test_app
+        .create_test_genre(user_id, "Fantasy", "#F59E0B")
+        .await;
+
+    let short_book = test_app
+        .create_test_book(user_id, "Short Book", "Author One")
+        .await;
+    let long_book = test_app
+        .create_test_book(user_id, "Long Book", "Author Two")
+        .await;
+    let last_year_book = test_app
+        .create_test_book(user_id, "Last Year", "Author Three")
+        .await;
+    let reading_book = test_app
+        .create_test_book(user_id, "Still Reading", "Author Four")
+        .await;
+
+    test_app.set_page_count(short_book, 150).await;
+    test_app.set_page_count(long_book, 900).await;
+    test_app.set_page_count(last_year_book, 2000).await;
+    test_app.add_genre_to_book(short_book, genre_id).await;
+    test_app.add_genre_to_book(long_book, genre_id).await;
+
+    test_app
+        .set_reading_status(user_id, short_book, 1, "2025-02-01 10:00:00")
+        .await;
+    test_app
+        .set_reading_status(user_id, long_book, 1, "2025-06-01 10:00:00")
+        .await;
+    test_app
+        .set_reading_status(user_id, last_year_book, 1, "2024-12-31 10:00:00")
+        .await;
+    test_app
+        .set_reading_status(user_id, reading_book, 2, "2025-07-01 10:00:00")
+        .await;
+    test_app.set_rating(user_id, short_book, 2.5).await;
+    test_app.set_rating(user_id, long_book, 5.0).await;
+
+    sqlx::query(
+        "INSERT INTO journal_entries (book_id, user_id, title, content, created_at) VALUES (?, ?, 'One', 'x', '2025-03-01'), (?, ?, 'Two', 'y', '2025-03-02')",
+    )
+    .bind(long_book)
+    .bind(user_id)
+    .bind(long_book)
+    .bind(user_id)
+    .execute(&test_app.pool)
+    .await
+    .unwrap();
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/year-in-review/2025", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["books_read"], 2);
+    assert_eq!(body["total_pages"], 1050);
+    assert_eq!(body["journal_count"], 2);
+    assert_eq!(body["average_rating"], 3.75);
+    assert_eq!(body["highest_rated"]["id"], long_book);
+    assert_eq!(body["lowest_rated"]["id"], short_book);
+    assert_eq!(body["longest_book"]["id"], long_book);
+    assert_eq!(body["favourite_genre"]["name"], "Fantasy");
+    assert_eq!(body["favourite_genre"]["book_count"], 2);
+    assert_eq!(body["most_journaled_book"]["id"], long_book);
+    assert_eq!(body["most_journaled_book"]["journal_count"], 2);
+
+    let covers = body["covers"].as_array().unwrap();
+    assert_eq!(covers.len(), 2);
+    assert_eq!(covers[0]["book_id"], short_book);
+    assert_eq!(covers[1]["book_id"], long_book);
+}
+
+#[tokio::test]
+async fn test_year_in_review_html() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Dune <Deluxe>", "Frank Herbert")
+        .await;
+    test_app
+        .set_reading_status(user_id, book_id, 1, "2025-05-05 10:00:00")
+        .await;
+    test_app.set_rating(user_id, book_id, 4.5).await;
+
+    let (status, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/year-in-review/2025?format=html", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let html = body.as_str().unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Test User's 2025 in books"));
+    assert!(html.contains("Dune &lt;Deluxe&gt;"));
+    assert!(!html.contains("Dune <Deluxe>"));
+
+    // Colours that aren't #rrggbb are replaced before they reach the stylesheet
+    let genre_id = test_app
+        .create_test_genre(user_id, "Sci-Fi", "red; } body { display: none")
+        .await;
+    test_app.add_genre_to_book(book_id, genre_id).await;
+    let (_, body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/year-in-review/2025?format=html", user_id),
+        user_id,
+        None,
+    )
+    .await;
+    let html = body.as_str().unwrap();
+    assert!(html.contains("Sci-Fi"));
+    assert!(!html.contains("display: none"));
+}
+
+#[tokio::test]
+async fn test_year_in_review_user_not_found() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        "/users/99999/year-in-review/2025",
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_year_in_review_bad_format() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+
+    let (status, _body) = make_request(
+        &test_app,
+        "GET",
+        &format!("/users/{}/year-in-review/2025?format=pdf", user_id),
+        user_id,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_year_in_review_counts_the_year_a_book_was_finished() {
+    let test_app = TestApp::new().await;
+    let user_id = test_app.create_test_user().await;
+    let book_id = test_app
+        .create_test_book(user_id, "Finished Long Ago", "Author")
+        .await;
+    test_app
+        .set_reading_status(user_id, book_id, 1, "2024-03-01 10:00:00")
+        .await;
+
+    let (status, owned) = make_request(
+        &test_app,
+        "POST",
+        "/statuses",
+        user_id,
+        Some(json!({ "name": "OWNED AND READ", "is_finished": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let status_uri = format!("/books/{}/status", book_id);
+    let review_2024 = format!("/users/{}/year-in-review/2024", user_id);
+
+    // Re-saving the status or moving between finished statuses keeps the original finish date
+    for status_id in [json!(1), owned["id"].clone()] {
+        let (status, _) = make_request(
+            &test_app,
+            "POST",
+            &status_uri,
+            user_id,
+            Some(json!({ "status_id": status_id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = make_request(&test_app, "GET", &review_2024, user_id, None).await;
+        assert_eq!(body["books_read"], 1);
+        assert_eq!(body["covers"][0]["book_id"], book_id);
+    }
+
+    // Reading it again and finishing it moves it to the year of the re-read
+    for status_id in [2, 1] {
+        make_request(
+            &test_app,
+            "POST",
+            &status_uri,
+            user_id,
+            Some(json!({ "status_id": status_id })),
+        )
+        .await;
+    }
+    let (_, body) = make_request(&test_app, "GET", &review_2024, user_id, None).await;
+    assert_eq!(body["books_read"], 0);
+}
@@ -2,7 +2,11 @@ import { ImageIcon, Trash2Icon, XIcon } from 'lucide-react'
 import { useEffect, useRef, useState } from 'react'
 import { createPortal } from 'react-dom'
 import type { User } from '../../contexts/UserContext'
-import { useUpdateUserMutation } from '../../middleware/backend'
+import {
+  useDeleteUserAvatarMutation,
+  useUpdateUserMutation,
+  useUploadUserAvatarMutation,
+} from '../../middleware/backend'
 
 interface EditUserModalProps {
   user: User
@@ -17,12 +21,18 @@ export function EditUserModal({
   onClose,
   onSuccess,
 }: EditUserModalProps) {
-  const [updateUser, { isLoading }] = useUpdateUserMutation()
+  const [updateUser, { isLoading: isUpdating }] = useUpdateUserMutation()
+  const [uploadUserAvatar, { isLoading: isUploading }] =
+    useUploadUserAvatarMutation()
+  const [deleteUserAvatar, { isLoading: isDeleting }] =
+    useDeleteUserAvatarMutation()
+  const isLoading = isUpdating || isUploading || isDeleting
   const [formData, setFormData] = useState({
     name: user.name,
     color: user.color,
-    avatar_image: user.avatar_image || null,
   })
+  // The picked image, uploaded on save; null with no preview means remove the avatar
+  const [avatarFile, setAvatarFile] = useState<Blob | null>(null)
   const [errors, setErrors] = useState({
     name: '',
     image: '',
@@ -37,8 +47,8 @@ export function EditUserModal({
     setFormData({
       name: user.name,
       color: user.color,
-      avatar_image: user.avatar_image || null,
     })
+    setAvatarFile(null)
     setPreviewUrl(user.avatar_image || null)
     setErrors({ name: '', image: '' })
   }, [user, isOpen])
@@ -92,9 +102,9 @@ export function EditUserModal({
 
     try {
       // Convert image to JPEG for compatibility
-      const base64String = await convertImageToJPEG(file)
-      setFormData(prev => ({ ...prev, avatar_image: base64String }))
-      setPreviewUrl(base64String)
+      const jpeg = await convertImageToJPEG(file)
+      setAvatarFile(jpeg)
+      setPreviewUrl(URL.createObjectURL(jpeg))
       setErrors(prev => ({ ...prev, image: '' }))
     } catch (error) {
       console.error('Error processing image:', error)
@@ -105,7 +115,7 @@ export function EditUserModal({
     }
   }
 
-  const convertImageToJPEG = (file: File): Promise<string> => {
+  const convertImageToJPEG = (file: File): Promise<Blob> => {
     return new Promise((resolve, reject) => {
       const reader = new FileReader()
 
@@ -141,8 +151,17 @@ export function EditUserModal({
 
           // Draw and convert to JPEG
           ctx.drawImage(img, 0, 0, width, height)
-          const jpegDataUrl = canvas.toDataURL('image/jpeg', 0.85) // 85% quality
-          resolve(jpegDataUrl)
+          canvas.toBlob(
+            blob => {
+              if (blob) {
+                resolve(blob)
+              } else {
+                reject(new Error('Failed to convert image'))
+              }
+            },
+            'image/jpeg',
+            0.85 // 85% quality
+          )
         }
 
         img.onerror = () => {
@@ -161,7 +180,7 @@ export function EditUserModal({
   }
 
   const handleDeleteImage = () => {
-    setFormData(prev => ({ ...prev, avatar_image: null }))
+    setAvatarFile(null)
     setPreviewUrl(null)
     if (fileInputRef.current) {
       fileInputRef.current.value = ''
@@ -176,15 +195,25 @@ export function EditUserModal({
     }
 
     try {
-      const updatedUser = await updateUser({
+      let updatedUser = await updateUser({
         id: user.id,
         user: {
           name: formData.name.trim(),
           color: formData.color,
-          avatar_image: formData.avatar_image,
         },
       }).unwrap()
 
+      // Avatars are uploaded as files rather than sent in the profile
+      if (avatarFile) {
+        updatedUser = await uploadUserAvatar({
+          id: user.id,
+          file: avatarFile,
+        }).unwrap()
+      } else if (!previewUrl && user.avatar_image) {
+        await deleteUserAvatar(user.id).unwrap()
+        updatedUser = { ...updatedUser, avatar_image: null }
+      }
+
       onSuccess(updatedUser)
       onClose()
     } catch (error) {
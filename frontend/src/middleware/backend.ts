@@ -9,12 +9,17 @@ import type {
   CreateListRequest,
   Genre,
   JournalEntry,
+  JournalFeedPage,
+  JournalFeedParams,
   ListWithBooks,
   Tag,
   UpdateBookRequest,
   UpdateListRequest,
 } from '../types'
 
+// The library created for the household when libraries were added
+const HOME_LIBRARY_ID = 1
+
 // Define our API slice
 export const api = createApi({
   reducerPath: 'api',
@@ -26,6 +31,10 @@ export const api = createApi({
       const currentUserId = state.user?.currentUser?.id
       if (currentUserId) {
         headers.set('currentUserId', currentUserId.toString())
+      } else {
+        // Before anyone is selected, the login screen lists the members of the
+        // library every install starts with
+        headers.set('currentLibraryId', HOME_LIBRARY_ID.toString())
       }
       // Set content type
       headers.set('Content-Type', 'application/json')
@@ -118,8 +127,8 @@ export const api = createApi({
       ],
     }),
 
-    getJournals: builder.query<JournalEntry[], void>({
-      query: () => '/journals',
+    getJournals: builder.query<JournalFeedPage, JournalFeedParams | void>({
+      query: params => ({ url: '/journals', params: params ?? undefined }),
       providesTags: ['JournalEntry'],
     }),
 
@@ -369,7 +378,7 @@ export const api = createApi({
       User,
       {
         id: number
-        user: { name: string; color: string; avatar_image?: string | null }
+        user: { name: string; color: string }
       }
     >({
       query: ({ id, user }) => ({
@@ -379,6 +388,23 @@ export const api = createApi({
       }),
       invalidatesTags: ['User'],
     }),
+
+    uploadUserAvatar: builder.mutation<User, { id: number; file: Blob }>({
+      query: ({ id, file }) => {
+        const body = new FormData()
+        body.append('file', file, 'avatar.jpg')
+        return { url: `/users/${id}/avatar`, method: 'POST', body }
+      },
+      invalidatesTags: ['User'],
+    }),
+
+    deleteUserAvatar: builder.mutation<void, number>({
+      query: id => ({
+        url: `/users/${id}/avatar`,
+        method: 'DELETE',
+      }),
+      invalidatesTags: ['User'],
+    }),
     //#endregion
 
     //#region List Endpoints
@@ -454,6 +480,8 @@ export const {
   useGetUsersQuery,
   useSelectUserMutation,
   useUpdateUserMutation,
+  useUploadUserAvatarMutation,
+  useDeleteUserAvatarMutation,
   useUpsertRatingMutation,
   useDeleteRatingMutation,
   useGetUserRatingQuery,
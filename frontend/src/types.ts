@@ -20,6 +20,35 @@ export interface JournalEntry {
   updated_at: string
 }
 
+// Journal feed types
+export interface JournalFeedBook {
+  id: number
+  title: string
+  cover_image: string | null
+}
+
+export interface JournalFeedItem extends JournalEntry {
+  book: JournalFeedBook
+}
+
+export interface JournalFeedPage {
+  journals: JournalFeedItem[]
+  // Pass as `cursor` to get the next page; null on the last page
+  next_cursor: string | null
+}
+
+export interface JournalFeedParams {
+  user_id?: number
+  book_id?: number
+  tag_id?: number
+  // Inclusive "YYYY-MM-DD" bounds on when entries were written
+  from?: string
+  to?: string
+  q?: string
+  limit?: number
+  cursor?: string
+}
+
 export interface Tag {
   id: number
   user_id: number | null
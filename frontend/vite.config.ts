@@ -18,6 +18,10 @@ export default defineConfig({
         target: 'http://localhost:3000',
         changeOrigin: true,
       },
+      '/attachments': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
       '/tags': {
         target: 'http://localhost:3000',
         changeOrigin: true,
@@ -30,6 +34,34 @@ export default defineConfig({
         target: 'http://localhost:3000',
         changeOrigin: true,
       },
+      '/quotes': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/statuses': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/rating-dimensions': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/api-tokens': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/share-links': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/shared': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
+      '/libraries': {
+        target: 'http://localhost:3000',
+        changeOrigin: true,
+      },
     },
   },
   plugins: [react()],